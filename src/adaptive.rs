@@ -0,0 +1,217 @@
+use rand::prelude::*;
+
+use crate::common::Round;
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_type::GameType;
+use crate::iocaine::IocaineStrategy;
+use crate::strategy::{random_move, Explanation, Strategy};
+
+pub const MAX_DIFFICULTY: usize = 10;
+const START_DIFFICULTY: usize = MAX_DIFFICULTY / 2;
+const WIN_RATE_WINDOW: usize = 10;
+const WIN_RATE_TOLERANCE: f64 = 0.05;
+const CHANGE_INTERVAL: usize = 5;
+
+// Plays random moves at the start difficulty. Above it, the exploiting strategy is played more and more often, below
+// it, the computer more and more often throws the round by playing into the player's most frequent move. Every few
+// rounds the difficulty goes up when the player's rolling win rate is above the target and down when it is below.
+pub struct AdaptiveStrategy {
+    pub target_win_rate: f64,
+    pub difficulty: usize,
+    exploiting: IocaineStrategy,
+    last_change: usize,
+    explanation: Option<Explanation>,
+}
+
+impl AdaptiveStrategy {
+    pub fn new(target_win_rate: f64) -> Self {
+        Self {
+            target_win_rate,
+            difficulty: START_DIFFICULTY,
+            exploiting: IocaineStrategy::default(),
+            last_change: 0,
+            explanation: None,
+        }
+    }
+
+    // Share of the player's wins in the last decisive rounds. Draws are left out, so that 50% means an even game.
+    pub fn get_win_rate(history: &[Round], game_type: &GameType) -> Option<f64> {
+        let window: Vec<GameResult> = history
+            .iter()
+            .rev()
+            .map(|x| x.player_move.beats_other(game_type, &x.computer_move))
+            .filter(|x| *x != GameResult::Draw)
+            .take(WIN_RATE_WINDOW)
+            .collect();
+        if window.is_empty() {
+            return None;
+        }
+        let wins = window.iter().filter(|x| **x == GameResult::Win).count();
+        Some(wins as f64 / window.len() as f64)
+    }
+}
+
+// The move which does worst against the player's move frequencies. Ties are broken randomly.
+fn throw_move(history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+    let ruleset = game_type.ruleset();
+    let mut weights = vec![0.0; ruleset.moves.len()];
+    for round in history {
+        weights[round.player_move.0] += 1.0;
+    }
+    let scores: Vec<f64> = (0..weights.len())
+        .map(|x| weights.iter().enumerate().map(|(y, weight)| weight * ruleset.payoff(x, y) as f64).sum())
+        .collect();
+    let worst = scores.iter().fold(f64::INFINITY, |x, y| x.min(*y));
+    let candidates: Vec<usize> = (0..scores.len()).filter(|x| scores[*x] - worst < 1e-9).collect();
+    GameMove(candidates[rng.gen_range(0..candidates.len())])
+}
+
+impl Strategy for AdaptiveStrategy {
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        if history.len() >= self.last_change + CHANGE_INTERVAL {
+            if let Some(win_rate) = AdaptiveStrategy::get_win_rate(history, game_type) {
+                if win_rate > self.target_win_rate + WIN_RATE_TOLERANCE && self.difficulty < MAX_DIFFICULTY {
+                    self.difficulty += 1;
+                    self.last_change = history.len();
+                } else if win_rate < self.target_win_rate - WIN_RATE_TOLERANCE && self.difficulty > 0 {
+                    self.difficulty -= 1;
+                    self.last_change = history.len();
+                }
+            }
+        }
+
+        // The exploiting strategy plays every round, so that its scores follow the whole match.
+        let exploiting_move = self.exploiting.get_move(history, game_type, rng);
+        let level = rng.gen_range(0..START_DIFFICULTY);
+        if level < self.difficulty.saturating_sub(START_DIFFICULTY) {
+            self.explanation = self.exploiting.get_explanation().cloned();
+            exploiting_move
+        } else if level < START_DIFFICULTY.saturating_sub(self.difficulty) {
+            self.explanation = Some(Explanation {
+                predictor: String::from("None, your most frequent move is let through to keep the game fair"),
+                expected_move: None,
+                confidence: 0.0,
+            });
+            throw_move(history, game_type, rng)
+        } else {
+            self.explanation = Some(Explanation {
+                predictor: String::from("None, random move to keep the game fair"),
+                expected_move: None,
+                confidence: 0.0,
+            });
+            random_move(game_type, rng)
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = AdaptiveStrategy::new(self.target_win_rate);
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+
+    fn get_difficulty(&self) -> Option<usize> {
+        Some(self.difficulty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    // Plays Rock, Paper and Scissors with 6:3:1 weights, until the given number of decisive rounds is played.
+    fn play_biased_player(strategy: &mut AdaptiveStrategy, decisive_rounds: usize, seed: u64) -> Vec<Round> {
+        let game_type = bundled_game_type("normal");
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut history = Vec::new();
+        let mut count = 0;
+        while count < decisive_rounds {
+            let computer_move = strategy.get_move(&history, &game_type, &mut rng);
+            let player_move = GameMove([0, 0, 0, 0, 0, 0, 1, 1, 1, 2][rng.gen_range(0..10)]);
+            if player_move != computer_move {
+                count += 1;
+            }
+            history.push(Round { player_move, computer_move });
+        }
+        history
+    }
+
+    #[test]
+    fn test_difficulty_goes_down_for_losing_player() {
+        let mut strategy = AdaptiveStrategy::new(0.7);
+        play_biased_player(&mut strategy, 60, 3);
+        assert!(strategy.difficulty < START_DIFFICULTY);
+    }
+
+    #[test]
+    fn test_win_rate_converges_to_target() {
+        let game_type = bundled_game_type("normal");
+        for target_win_rate in [0.3, 0.45, 0.6] {
+            let mut strategy = AdaptiveStrategy::new(target_win_rate);
+            let history = play_biased_player(&mut strategy, 1000, 11);
+            let results: Vec<GameResult> = history
+                .iter()
+                .map(|x| x.player_move.beats_other(&game_type, &x.computer_move))
+                .filter(|x| *x != GameResult::Draw)
+                .skip(100)
+                .collect();
+            let win_rate = results.iter().filter(|x| **x == GameResult::Win).count() as f64 / results.len() as f64;
+            assert!((win_rate - target_win_rate).abs() < WIN_RATE_TOLERANCE, "{} {}", target_win_rate, win_rate);
+        }
+    }
+
+    #[test]
+    fn test_difficulty_goes_up_for_winning_player() {
+        let game_type = bundled_game_type("spock_lizard");
+        let mut strategy = AdaptiveStrategy::new(0.45);
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut history = Vec::new();
+        for _ in 0..40 {
+            let computer_move = strategy.get_move(&history, &game_type, &mut rng);
+            let player_move = (0..5)
+                .map(GameMove)
+                .find(|x| x.beats_other(&game_type, &computer_move) == GameResult::Win)
+                .unwrap();
+            history.push(Round { player_move, computer_move });
+        }
+        assert_eq!(strategy.get_difficulty(), Some(MAX_DIFFICULTY));
+
+        // A forfeited round is not in the history and must not restart the match.
+        strategy.get_move(&history, &game_type, &mut rng);
+        assert_eq!(strategy.get_difficulty(), Some(MAX_DIFFICULTY));
+
+        strategy.reset();
+        assert_eq!(strategy.get_difficulty(), Some(START_DIFFICULTY));
+    }
+
+    #[test]
+    fn test_get_win_rate() {
+        let game_type = bundled_game_type("normal");
+        assert_eq!(AdaptiveStrategy::get_win_rate(&[], &game_type), None);
+        let mut history = vec![
+            Round {
+                player_move: GameMove(1),
+                computer_move: GameMove(0),
+            };
+            WIN_RATE_WINDOW
+        ];
+        history.extend(
+            [Round {
+                player_move: GameMove(0),
+                computer_move: GameMove(1),
+            }; 5],
+        );
+        assert_eq!(AdaptiveStrategy::get_win_rate(&history, &game_type), Some(0.5));
+        history.extend(
+            [Round {
+                player_move: GameMove(2),
+                computer_move: GameMove(2),
+            }; 5],
+        );
+        assert_eq!(AdaptiveStrategy::get_win_rate(&history, &game_type), Some(0.5));
+    }
+}
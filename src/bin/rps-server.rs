@@ -0,0 +1,68 @@
+use std::env;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rock_paper_scissors::lan::PROTOCOL_VERSION;
+use rock_paper_scissors::lan_discovery::{get_host_name, LobbyAnnouncement, LobbyAnnouncer, ANNOUNCE_INTERVAL, DISCOVERY_PORT};
+use rock_paper_scissors::server::{Server, ServerConfig, SERVER_NAME};
+
+const STEP_INTERVAL: Duration = Duration::from_millis(10);
+
+fn get_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0)
+}
+
+fn main() -> ExitCode {
+    let config = match ServerConfig::from_args(env::args().skip(1)) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}\n{}", e, ServerConfig::USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut server = match Server::new(&config) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!(
+        "{} listening on port {}: {} ({}), log {}",
+        SERVER_NAME,
+        server.get_port(),
+        server.get_game_type().ruleset().name,
+        server.get_match_format().get_friendly_name(),
+        config.log_path.display()
+    );
+
+    // Server is listed in LAN game lobbies like a waiting host.
+    let announcement = LobbyAnnouncement {
+        version: PROTOCOL_VERSION,
+        host_name: get_host_name(),
+        profile_name: String::from(SERVER_NAME),
+        ruleset_id: server.get_game_type().ruleset().id.clone(),
+        ruleset_name: server.get_game_type().ruleset().name.clone(),
+        match_format: server.get_match_format(),
+        port: server.get_port(),
+        is_playing: false,
+    };
+    let announcer = LobbyAnnouncer::new(&announcement, DISCOVERY_PORT)
+        .map_err(|e| eprintln!("Unable to announce server: {}", e))
+        .ok();
+    let mut last_announce: Option<Instant> = None;
+
+    loop {
+        if let Some(x) = announcer.as_ref() {
+            if last_announce.is_none_or(|x| x.elapsed().as_secs_f32() >= ANNOUNCE_INTERVAL) {
+                x.announce();
+                last_announce = Some(Instant::now());
+            }
+        }
+        for entry in server.step(get_timestamp()) {
+            println!("{}", serde_json::to_string(&entry).unwrap_or_default());
+        }
+        thread::sleep(STEP_INTERVAL);
+    }
+}
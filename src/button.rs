@@ -0,0 +1,164 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+use crate::common::*;
+
+pub const BUTTON_HOVERED_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+pub const BUTTON_PRESSED_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.2);
+
+// Clicked or touched button acts as if its key was pressed, so screens handle both the same way.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HotKey(pub KeyCode);
+
+// Clicked menu item or move tile is selected, clicking it again confirms it like ENTER.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OptionButton(pub i32);
+
+// Keys pressed this frame together with keys of buttons clicked this frame. Screens read their actions from here and
+// the keyboard input itself is never changed.
+#[derive(Resource, Debug, Default)]
+pub struct Actions(Vec<KeyCode>);
+
+impl Actions {
+    pub fn just_pressed(&self, key_code: KeyCode) -> bool {
+        self.0.contains(&key_code)
+    }
+
+    pub fn any_just_pressed(&self, key_codes: impl IntoIterator<Item = KeyCode>) -> bool {
+        key_codes.into_iter().any(|x| self.just_pressed(x))
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = &KeyCode> {
+        self.0.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+pub struct ButtonPlugin;
+
+impl Plugin for ButtonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .add_systems(PreUpdate, update_actions.after(InputSystem).after(UiSystem::Focus))
+            .add_systems(Update, update_button_colors);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_actions(
+    query: Query<(Entity, &Interaction, Option<&HotKey>, Option<&OptionButton>), Changed<Interaction>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut actions: ResMut<Actions>,
+    mut selected_option: ResMut<SelectedOption>,
+    mut clicked_option: Local<Option<Entity>>,
+) {
+    actions.0 = keyboard_input.get_just_pressed().copied().collect();
+    for (entity, interaction, hot_key, option_button) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match (hot_key, option_button) {
+            (Some(x), _) => actions.0.push(x.0),
+            (None, Some(x)) if *clicked_option == Some(entity) && selected_option.get_value() == x.0 => actions.0.push(KeyCode::Return),
+            (None, Some(x)) => {
+                selected_option.set_value(x.0);
+                *clicked_option = Some(entity);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_button_colors(mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>) {
+    for (interaction, mut background_color) in &mut query {
+        *background_color = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => Color::NONE,
+        }
+        .into();
+    }
+}
+
+// Small text button, like "(B)ack" below a screen.
+pub fn spawn_button(parent: &mut ChildBuilder, text: &str, key_code: KeyCode, font: &Handle<Font>) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            HotKey(key_code),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: BUTTON_TEXT_SMALL_SIZE,
+                    color: BUTTON_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ));
+        });
+}
+
+pub fn spawn_buttons(parent: &mut ChildBuilder, buttons: &[(&str, KeyCode)], font: &Handle<Font>) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                margin: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (text, key_code) in buttons {
+                spawn_button(parent, text, *key_code, font);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_actions() {
+        let mut app = App::new();
+        app.insert_resource(Input::<KeyCode>::default())
+            .insert_resource(SelectedOption::init())
+            .init_resource::<Actions>()
+            .add_systems(Update, update_actions);
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::Left);
+        let option = app.world.spawn((Interaction::Pressed, OptionButton(3))).id();
+        app.world.spawn((Interaction::Hovered, HotKey(KeyCode::B)));
+        app.update();
+        assert_eq!(app.world.resource::<SelectedOption>().get_value(), 3);
+        let actions = app.world.resource::<Actions>();
+        assert!(actions.just_pressed(KeyCode::Left));
+        assert!(!actions.any_just_pressed([KeyCode::Return, KeyCode::B]));
+
+        // Second click on the selected option confirms it, the held key stays pressed.
+        app.world.resource_mut::<Input<KeyCode>>().clear();
+        app.world.entity_mut(option).insert(Interaction::Pressed);
+        app.update();
+        assert!(app.world.resource::<Actions>().just_pressed(KeyCode::Return));
+        assert!(app.world.resource::<Input<KeyCode>>().pressed(KeyCode::Left));
+
+        app.update();
+        assert!(app.world.resource::<Actions>().get_just_pressed().next().is_none());
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+pub const MAX_CHAT_LENGTH: usize = 60;
+pub const MAX_CHAT_HISTORY: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emote {
+    GoodGame,
+    NiceMove,
+    TooEasy,
+    Rematch,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 4] = [Emote::GoodGame, Emote::NiceMove, Emote::TooEasy, Emote::Rematch];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            Emote::GoodGame => "GG",
+            Emote::NiceMove => "Nice move!",
+            Emote::TooEasy => "Too easy!",
+            Emote::Rematch => "Rematch?",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChatContent {
+    Emote(Emote),
+    Text(String),
+}
+
+impl ChatContent {
+    pub fn get_text(&self) -> &str {
+        match self {
+            ChatContent::Emote(x) => x.get_text(),
+            ChatContent::Text(x) => x,
+        }
+    }
+
+    // Text from the other side is not trusted, empty text is dropped.
+    pub fn sanitize(self) -> Option<ChatContent> {
+        match self {
+            ChatContent::Emote(x) => Some(ChatContent::Emote(x)),
+            ChatContent::Text(x) => {
+                let text: String = x.chars().filter(|x| !x.is_control()).take(MAX_CHAT_LENGTH).collect();
+                let text = text.trim();
+                (!text.is_empty()).then(|| ChatContent::Text(text.to_string()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+    pub sender: String,
+    pub content: ChatContent,
+}
+
+// Messages of the current match, oldest are dropped when there are too many.
+#[derive(Debug, Default)]
+pub struct ChatHistory(VecDeque<ChatLine>);
+
+impl ChatHistory {
+    pub fn push(&mut self, sender: &str, content: ChatContent) {
+        if self.0.len() == MAX_CHAT_HISTORY {
+            self.0.pop_front();
+        }
+        self.0.push_back(ChatLine {
+            sender: sender.to_string(),
+            content,
+        });
+    }
+
+    pub fn get_last(&self, count: usize) -> impl Iterator<Item = &ChatLine> {
+        self.0.iter().skip(self.0.len().saturating_sub(count))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            ChatContent::Text(String::from("  hi\nthere\u{7} ")).sanitize(),
+            Some(ChatContent::Text(String::from("hithere")))
+        );
+        assert_eq!(ChatContent::Text(String::from(" \t ")).sanitize(), None);
+        assert_eq!(ChatContent::Text("x".repeat(100)).sanitize().unwrap().get_text().len(), MAX_CHAT_LENGTH);
+        assert_eq!(ChatContent::Emote(Emote::GoodGame).sanitize().unwrap().get_text(), "GG");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = ChatHistory::default();
+        for x in 0..MAX_CHAT_HISTORY + 2 {
+            history.push("Bob", ChatContent::Text(x.to_string()));
+        }
+        assert_eq!(history.len(), MAX_CHAT_HISTORY);
+        let last: Vec<&str> = history.get_last(2).map(|x| x.content.get_text()).collect();
+        assert_eq!(last, vec!["100", "101"]);
+        assert_eq!(history.get_last(1000).next().unwrap().content.get_text(), "2");
+    }
+}
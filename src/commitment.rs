@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::game_move::GameMove;
+use crate::game_type::GameType;
+
+const NONCE_LENGTH: usize = 16;
+
+// The computer picks its move before the player does and shows only SHA-256 of "<move name>:<nonce>". After the round
+// the move and nonce are revealed, so anyone can hash them again and compare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commitment {
+    pub game_move: GameMove,
+    pub nonce: String,
+    pub hash: String,
+}
+
+impl Commitment {
+    pub fn new(game_type: &GameType, game_move: GameMove, rng: &mut dyn RngCore) -> Self {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+        let nonce = to_hex(&nonce);
+        Self {
+            game_move,
+            hash: get_hash(game_move.get_name(game_type), &nonce),
+            nonce,
+        }
+    }
+
+    pub fn get_message(&self, game_type: &GameType) -> String {
+        get_message(self.game_move.get_name(game_type), &self.nonce)
+    }
+
+    pub fn verify(&self, game_type: &GameType) -> bool {
+        verify(self.game_move.get_name(game_type), &self.nonce, &self.hash)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ComputerCommitment(pub Option<Commitment>);
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub fn get_message(move_name: &str, nonce: &str) -> String {
+    format!("{}:{}", move_name, nonce)
+}
+
+pub fn get_hash(move_name: &str, nonce: &str) -> String {
+    to_hex(&Sha256::digest(get_message(move_name, nonce).as_bytes()))
+}
+
+pub fn verify(move_name: &str, nonce: &str, hash: &str) -> bool {
+    get_hash(move_name, nonce).eq_ignore_ascii_case(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::mock::StepRng;
+
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    #[test]
+    fn test_get_hash() {
+        assert_eq!(
+            get_hash("Rock", "0123456789abcdef"),
+            "f4e66c58497f14777bc6f20559729a29d6e1fbef4a9e983c0a3210e9a445ef07"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let hash = "F4E66C58497F14777BC6F20559729A29D6E1FBEF4A9E983C0A3210E9A445EF07";
+        assert!(verify("Rock", "0123456789abcdef", hash));
+        assert!(!verify("Paper", "0123456789abcdef", hash));
+        assert!(!verify("Rock", "0123456789abcdee", hash));
+    }
+
+    #[test]
+    fn test_commitment() {
+        let game_type = bundled_game_type("normal");
+        let commitment = Commitment::new(&game_type, GameMove(1), &mut StepRng::new(1, 1));
+        assert_eq!(commitment.nonce.len(), 2 * NONCE_LENGTH);
+        assert_eq!(commitment.nonce, "01000000000000000200000000000000");
+        assert_eq!(commitment.get_message(&game_type), "Paper:01000000000000000200000000000000");
+        assert!(commitment.verify(&game_type));
+
+        let forged = Commitment {
+            game_move: GameMove(2),
+            ..commitment
+        };
+        assert!(!forged.verify(&game_type));
+    }
+}
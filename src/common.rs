@@ -1,9 +1,13 @@
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl};
+use uuid::Uuid;
 
+use crate::button::{spawn_buttons, Actions};
 use crate::game_move::GameMove;
 use crate::game_result::GameResult;
 use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+use crate::match_format::MatchFormat;
 
 pub const TITLE_TEXT: &str = "rock paper scissors";
 pub const TITLE_COLOR: Color = Color::rgb(0.16471, 0.61569, 0.56078);
@@ -17,6 +21,7 @@ pub const BUTTON_TEXT_SMALL_SIZE: f32 = 16.0;
 
 pub const GAME_SELECTED_BORDER_COLOR: Color = Color::RED;
 pub const GAME_NO_SELECTED_BORDER_COLOR: Color = Color::ANTIQUE_WHITE;
+pub const GAME_MOVES_VISIBLE_COUNT: usize = 5;
 
 pub const OVERVIEW_BACKGROUND_COLOR: Color = Color::rgb(0.45098, 0.30980, 0.35294);
 pub const OVERVIEW_TITLE_COLOR: Color = Color::rgb(0.90588, 0.43529, 0.31765);
@@ -24,6 +29,12 @@ pub const OVERVIEW_SUB_TITLE_COLOR: Color = Color::rgb(0.90588, 0.53529, 0.35039
 
 pub const CLOSING_DURATION: f32 = 2.0;
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Round {
+    pub player_move: GameMove,
+    pub computer_move: GameMove,
+}
+
 #[derive(Resource, Debug, PartialEq, Eq)]
 pub struct GameStatistics {
     pub last_round_result: Option<GameResult>,
@@ -32,6 +43,11 @@ pub struct GameStatistics {
     pub wins: u32,
     pub loses: u32,
     pub draws: u32,
+    pub timeouts: u32,
+    // Rounds lost because time ran out without a move, they are not in `rounds`.
+    pub forfeits: u32,
+    pub rounds: Vec<Round>,
+    pub match_id: Uuid,
 }
 
 impl GameStatistics {
@@ -43,6 +59,10 @@ impl GameStatistics {
             wins: 0,
             loses: 0,
             draws: 0,
+            timeouts: 0,
+            forfeits: 0,
+            rounds: Vec::new(),
+            match_id: Uuid::new_v4(),
         }
     }
 
@@ -53,6 +73,10 @@ impl GameStatistics {
         self.wins = 0;
         self.loses = 0;
         self.draws = 0;
+        self.timeouts = 0;
+        self.forfeits = 0;
+        self.rounds.clear();
+        self.match_id = Uuid::new_v4();
     }
 
     pub fn totals(&self) -> u32 {
@@ -85,7 +109,13 @@ pub enum AppState {
     #[default]
     Menu,
     Playing,
+    MatchSetup,
+    HotSeat,
+    SplitKeyboard,
+    Lan,
     GameOverview,
+    RulesetInfo,
+    Statistics,
     Credits,
     Closing,
 }
@@ -108,13 +138,6 @@ pub enum GameState {
     RoundFinish,
 }
 
-#[derive(Debug, Resource)]
-pub struct GameResources {
-    pub sounds: GameSounds,
-    pub images: GameImages,
-    pub font: GameFont,
-}
-
 #[derive(Debug, Resource)]
 pub struct GameSounds {
     pub mode_switch: Handle<bevy_kira_audio::AudioSource>,
@@ -141,25 +164,11 @@ pub fn play_sound(audio: &Res<Audio>, is_sound_on: bool, sound: &Handle<bevy_kir
 #[derive(Debug, Resource)]
 pub struct GameImages {
     pub joystick: Handle<Image>,
-    pub rock: Handle<Image>,
-    pub paper: Handle<Image>,
-    pub scissors: Handle<Image>,
-    pub spock: Handle<Image>,
-    pub lizard: Handle<Image>,
-    pub fire: Handle<Image>,
-    pub water: Handle<Image>,
 }
 
 pub fn setup_game_images(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(GameImages {
         joystick: asset_server.load("images/joystick-50.png"),
-        rock: asset_server.load("images/rock-100.png"),
-        paper: asset_server.load("images/paper-100.png"),
-        scissors: asset_server.load("images/scissors-100.png"),
-        spock: asset_server.load("images/spock-100.png"),
-        lizard: asset_server.load("images/lizard-100.png"),
-        fire: asset_server.load("images/fire-100.png"),
-        water: asset_server.load("images/water-100.png"),
     });
 }
 
@@ -179,3 +188,201 @@ pub fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut comm
         commands.entity(entity).despawn_recursive();
     }
 }
+
+// Score of a hot seat or split keyboard match, results are kept from the first player's point of view.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct LocalMatch {
+    pub first_move: Option<GameMove>,
+    pub last_round: Option<(GameMove, GameMove)>,
+    pub first_wins: u32,
+    pub second_wins: u32,
+    pub draws: u32,
+}
+
+impl LocalMatch {
+    pub fn add_round(&mut self, game_type: &GameType, first_move: GameMove, second_move: GameMove) -> GameResult {
+        let result = first_move.beats_other(game_type, &second_move);
+        match result {
+            GameResult::Win => self.first_wins += 1,
+            GameResult::Lose => self.second_wins += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+        self.first_move = None;
+        self.last_round = Some((first_move, second_move));
+        result
+    }
+
+    pub fn get_winner(&self, match_format: &MatchFormat) -> Option<GameResult> {
+        match_format.get_winner(self.first_wins, self.second_wins)
+    }
+}
+
+pub fn get_score_text(local_match: &LocalMatch, game_settings: &GameSettings) -> String {
+    format!(
+        "{}: {}, {}: {}, draws: {}",
+        game_settings.player_options.name, local_match.first_wins, game_settings.second_player_options.name, local_match.second_wins, local_match.draws
+    )
+}
+
+pub fn spawn_panel<T: Component>(commands: &mut Commands, marker: T, spawn_children: impl FnOnce(&mut ChildBuilder)) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        width: Val::Percent(100.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: MENU_BACKGROUND_COLOR.into(),
+                    ..default()
+                })
+                .with_children(spawn_children);
+        });
+}
+
+pub fn spawn_text(parent: &mut ChildBuilder, text: String, font_size: f32, color: Color, font: &Handle<Font>) {
+    parent.spawn(
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size,
+                color,
+                font: font.clone(),
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::all(Val::Px(10.0)),
+            ..default()
+        }),
+    );
+}
+
+// Both moves of last round with result of each player, shared by local two player modes.
+pub fn spawn_round_result<T: Component>(
+    commands: &mut Commands,
+    marker: T,
+    font: &Handle<Font>,
+    game_type: &GameType,
+    game_settings: &GameSettings,
+    local_match: &LocalMatch,
+) {
+    let (first_move, second_move) = local_match.last_round.expect("Last local round is not set.");
+    let first_result = first_move.beats_other(game_type, &second_move);
+    let second_result = second_move.beats_other(game_type, &first_move);
+    let winner = local_match.get_winner(&game_settings.match_format);
+
+    spawn_panel(commands, marker, |parent| {
+        spawn_text(
+            parent,
+            GameMove::get_phrase(game_type, &first_move, &second_move),
+            TITLE_SIZE,
+            TITLE_COLOR,
+            font,
+        );
+        for (options, game_move, result) in [
+            (&game_settings.player_options, first_move, first_result),
+            (&game_settings.second_player_options, second_move, second_result),
+        ] {
+            spawn_text(
+                parent,
+                format!("{} ({}): {}", options.name, game_move.get_name(game_type), result.get_friendly_name()),
+                24.0,
+                OVERVIEW_SUB_TITLE_COLOR,
+                font,
+            );
+        }
+        spawn_text(parent, get_score_text(local_match, game_settings), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        if game_settings.match_format != MatchFormat::Endless {
+            spawn_text(
+                parent,
+                game_settings
+                    .match_format
+                    .get_status_text(winner, &game_settings.player_options.name, &game_settings.second_player_options.name),
+                16.0,
+                OVERVIEW_TITLE_COLOR,
+                font,
+            );
+        }
+        if winner.is_some() {
+            spawn_buttons(parent, &[("(F)inish", KeyCode::F)], font);
+        } else {
+            spawn_buttons(parent, &[("(C)ontinue", KeyCode::C), ("(F)inish", KeyCode::F)], font);
+        }
+    });
+}
+
+// States of a local two player mode, the result screen goes on with the next round or back to the menu.
+pub trait LocalMatchState: States {
+    const NOT_INIT: Self;
+    const NEXT_ROUND: Self;
+}
+
+pub fn confirm_local_result_action<S: LocalMatchState>(
+    actions: Res<Actions>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    local_match: Res<LocalMatch>,
+    mut local_match_state: ResMut<NextState<S>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let is_match_over = local_match.get_winner(&game_settings.match_format).is_some();
+    if actions.just_pressed(KeyCode::C) && !is_match_over {
+        local_match_state.set(S::NEXT_ROUND);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::F) {
+        local_match_state.set(S::NOT_INIT);
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    #[test]
+    fn test_add_round() {
+        let game_type = bundled_game_type("normal");
+        let mut local_match = LocalMatch {
+            first_move: Some(GameMove(1)),
+            ..default()
+        };
+        assert_eq!(local_match.add_round(&game_type, GameMove(1), GameMove(0)), GameResult::Win);
+        assert_eq!(local_match.add_round(&game_type, GameMove(1), GameMove(2)), GameResult::Lose);
+        assert_eq!(local_match.add_round(&game_type, GameMove(2), GameMove(2)), GameResult::Draw);
+        assert_eq!(local_match.add_round(&game_type, GameMove(0), GameMove(2)), GameResult::Win);
+        assert_eq!(
+            local_match,
+            LocalMatch {
+                first_move: None,
+                last_round: Some((GameMove(0), GameMove(2))),
+                first_wins: 2,
+                second_wins: 1,
+                draws: 1,
+            }
+        );
+        assert_eq!(local_match.get_winner(&MatchFormat::FirstTo(2)), Some(GameResult::Win));
+        assert_eq!(local_match.get_winner(&MatchFormat::WinByTwo(2)), None);
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy_kira_audio::Audio;
 
+use crate::button::{Actions, HotKey};
 use crate::common::*;
 use crate::game_settings::GameSettings;
 
@@ -175,19 +176,23 @@ pub fn setup_credits_screen(mut commands: Commands, game_font: Res<GameFont>, ga
                                 },))
                                 .with_children(|parent| {
                                     parent
-                                        .spawn(NodeBundle {
-                                            style: Style {
-                                                width: Val::Px(250.0),
-                                                height: Val::Px(50.0),
-                                                margin: UiRect::all(Val::Px(10.0)),
-                                                border: UiRect::all(Val::Px(5.0)),
-                                                justify_content: JustifyContent::Center,
-                                                align_items: AlignItems::Center,
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(250.0),
+                                                    height: Val::Px(50.0),
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    border: UiRect::all(Val::Px(5.0)),
+                                                    justify_content: JustifyContent::Center,
+                                                    align_items: AlignItems::Center,
+                                                    ..default()
+                                                },
+                                                border_color: Color::WHITE.into(),
+                                                background_color: Color::NONE.into(),
                                                 ..default()
                                             },
-                                            border_color: Color::WHITE.into(),
-                                            ..default()
-                                        })
+                                            HotKey(KeyCode::B),
+                                        ))
                                         .with_children(|parent| {
                                             parent.spawn(
                                                 TextBundle::from_section(
@@ -211,13 +216,13 @@ pub fn setup_credits_screen(mut commands: Commands, game_font: Res<GameFont>, ga
 }
 
 pub fn confirm_button_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut app_state: ResMut<NextState<AppState>>,
     audio: Res<Audio>,
     game_settings: Res<GameSettings>,
     game_sounds: Res<GameSounds>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::B) {
+    if actions.just_pressed(KeyCode::B) {
         app_state.set(AppState::Menu);
 
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
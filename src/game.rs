@@ -1,14 +1,29 @@
+use std::net::TcpListener;
+
 use bevy::app::App;
 use bevy::prelude::*;
 use bevy::ui::Style;
 use bevy_kira_audio::Audio;
-use rand::Rng;
+use rand::rngs::OsRng;
 
+use crate::adaptive::MAX_DIFFICULTY;
+use crate::button::{Actions, HotKey, OptionButton};
+use crate::commitment::{Commitment, ComputerCommitment};
 use crate::common::*;
 use crate::game_move::GameMove;
-use crate::game_result::GameResult;
+use crate::game_result::{GameResult, ResultCounts};
+use crate::game_rng::GameRng;
 use crate::game_settings::GameSettings;
 use crate::game_type::GameType;
+use crate::history::{get_timestamp, MatchHistory, RoundRecord};
+use crate::lan::{accept, listen, DEFAULT_PORT, PROTOCOL_VERSION};
+use crate::lan_discovery::{get_host_name, LobbyAnnouncement, LobbyAnnouncer, ANNOUNCE_INTERVAL};
+use crate::lan_game::get_announcer;
+use crate::match_format::MatchFormat;
+use crate::opponent::ComputerOpponent;
+use crate::round_timer::{get_countdown_text, RoundCountdown, TimeoutAction};
+use crate::spectator::{get_round_played, get_welcome, Spectators};
+use crate::strategy::random_move;
 
 #[derive(Component)]
 pub struct OnGameScreen;
@@ -19,22 +34,48 @@ pub struct OnRoundOverview;
 #[derive(Component)]
 pub struct OnGamePanel;
 
+#[derive(Component)]
+pub struct OnGameMoveName;
+
+#[derive(Component)]
+pub struct OnOpponentThinking;
+
+#[derive(Component)]
+pub struct OnCommitmentHash;
+
+#[derive(Component)]
+pub struct OnRoundCountdown;
+
+// Match against the computer is announced on LAN when it is allowed in settings, so others can watch it. Nobody can
+// join it as a player.
+#[derive(Resource, Debug, Default)]
+pub struct GameSpectators {
+    listener: Option<TcpListener>,
+    announcer: Option<LobbyAnnouncer>,
+    spectators: Spectators,
+    announce_timer: Timer,
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<GameState>()
-            .add_systems(OnEnter(AppState::Playing), init_game_setup)
+            .init_resource::<GameSpectators>()
+            .add_systems(OnEnter(AppState::Playing), (init_game_setup, start_game_spectators))
+            .add_systems(Update, update_game_spectators.run_if(in_state(AppState::Playing)))
             .add_systems(OnEnter(GameState::PlayerMoveRender), setup_game_screen)
             .add_systems(OnEnter(GameState::PlayerMove), setup_player_move_screen)
             .add_systems(
                 Update,
-                (switch_game_move, confirm_button_action, confirm_game_move).run_if(in_state(GameState::PlayerMove)),
+                (switch_game_move, confirm_button_action, confirm_game_move, update_round_countdown)
+                    .chain()
+                    .run_if(in_state(GameState::PlayerMove)),
             )
             .add_systems(Update, confirm_sub_button_action.run_if(in_state(GameState::RoundFinish)))
             .add_systems(OnEnter(GameState::RoundFinish), setup_round_overview_screen)
             .add_systems(OnExit(GameState::RoundFinish), despawn_screen::<OnRoundOverview>)
-            .add_systems(OnExit(AppState::Playing), despawn_screen::<OnGameScreen>);
+            .add_systems(OnExit(AppState::Playing), (despawn_screen::<OnGameScreen>, close_game_spectators));
     }
 }
 
@@ -42,30 +83,90 @@ pub fn init_game_setup(mut game_state: ResMut<NextState<GameState>>) {
     game_state.set(GameState::PlayerMoveRender)
 }
 
+pub fn start_game_spectators(mut game_spectators: ResMut<GameSpectators>, game_settings: Res<GameSettings>, game_type: Res<GameType>) {
+    if !game_settings.is_watchable {
+        return;
+    }
+    let listener = match listen(DEFAULT_PORT) {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("Match cannot be watched: {}", e);
+            return;
+        }
+    };
+    let ruleset = game_type.ruleset();
+    let announcement = LobbyAnnouncement {
+        version: PROTOCOL_VERSION,
+        host_name: get_host_name(),
+        profile_name: game_settings.player_options.name.clone(),
+        ruleset_id: ruleset.id.clone(),
+        ruleset_name: ruleset.name.clone(),
+        match_format: game_settings.match_format,
+        port: DEFAULT_PORT,
+        is_playing: true,
+    };
+    *game_spectators = GameSpectators {
+        listener: Some(listener),
+        announcer: get_announcer(&announcement),
+        spectators: Spectators::default(),
+        announce_timer: Timer::from_seconds(ANNOUNCE_INTERVAL, TimerMode::Repeating),
+    };
+}
+
+fn get_score(statistics: &GameStatistics) -> ResultCounts {
+    ResultCounts {
+        wins: statistics.wins as usize,
+        loses: statistics.loses as usize,
+        draws: statistics.draws as usize,
+    }
+}
+
+pub fn update_game_spectators(
+    time: Res<Time>,
+    mut game_spectators: ResMut<GameSpectators>,
+    game_settings: Res<GameSettings>,
+    game_type: Res<GameType>,
+    statistics: Res<GameStatistics>,
+    computer_opponent: Res<ComputerOpponent>,
+) {
+    let game_spectators = &mut *game_spectators;
+    let Some(listener) = &game_spectators.listener else {
+        return;
+    };
+    if game_spectators.announce_timer.tick(time.delta()).just_finished() {
+        if let Some(x) = &game_spectators.announcer {
+            x.announce();
+        }
+    }
+    loop {
+        match accept(listener) {
+            Ok(Some(x)) => game_spectators.spectators.add(x),
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Unable to accept LAN connection: {}", e);
+                break;
+            }
+        }
+    }
+    let score = get_score(&statistics);
+    let players = [game_settings.player_options.name.clone(), computer_opponent.opponent.get_friendly_name()];
+    let welcome = get_welcome(&game_type, game_settings.match_format, players, score.totals() as u32, &score);
+    game_spectators.spectators.update(|_| false, Some(&welcome));
+}
+
+pub fn close_game_spectators(mut game_spectators: ResMut<GameSpectators>) {
+    game_spectators.spectators.close();
+    *game_spectators = GameSpectators::default();
+}
+
 pub fn setup_game_screen(
     mut commands: Commands,
     mut game_state: ResMut<NextState<GameState>>,
     game_font: Res<GameFont>,
-    game_images: Res<GameImages>,
+    asset_server: Res<AssetServer>,
     game_type: Res<GameType>,
     game_settings: Res<GameSettings>,
 ) {
-    let button_style = Style {
-        width: Val::Px(250.0),
-        height: Val::Px(250.0),
-        margin: UiRect::all(Val::Px(10.0)),
-        border: UiRect::all(Val::Px(10.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-
-    let button_icon_style = Style {
-        width: Val::Px(150.0),
-        height: Val::Px(150.0),
-        ..default()
-    };
-
     let font = &game_font.0;
 
     commands
@@ -151,19 +252,56 @@ pub fn setup_game_screen(
                                     OnGamePanel,
                                 ))
                                 .with_children(|parent| {
-                                    spawn_game_move_button(parent, &button_style, true, GameMove::Rock, &button_icon_style, &game_images.rock);
-                                    spawn_game_move_button(parent, &button_style, false, GameMove::Paper, &button_icon_style, &game_images.paper);
-                                    spawn_game_move_button(parent, &button_style, false, GameMove::Scissors, &button_icon_style, &game_images.scissors);
-
-                                    if *game_type == GameType::SpockLizard {
-                                        spawn_game_move_button(parent, &button_style, false, GameMove::Spock, &button_icon_style, &game_images.spock);
-                                        spawn_game_move_button(parent, &button_style, false, GameMove::Lizard, &button_icon_style, &game_images.lizard);
-                                    } else if *game_type == GameType::FireWater {
-                                        spawn_game_move_button(parent, &button_style, false, GameMove::Fire, &button_icon_style, &game_images.fire);
-                                        spawn_game_move_button(parent, &button_style, false, GameMove::Water, &button_icon_style, &game_images.water);
-                                    }
+                                    spawn_game_move_buttons(parent, &game_type, &asset_server, font);
                                 });
 
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    get_game_move_name_text(&game_type, 1),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: BUTTON_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                ),
+                                OnGamePanel,
+                                OnGameMoveName,
+                            ));
+
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    String::new(),
+                                    TextStyle {
+                                        font_size: 10.0,
+                                        color: BUTTON_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::top(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                                OnGamePanel,
+                                OnCommitmentHash,
+                            ));
+
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    String::new(),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SIZE,
+                                        color: TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::top(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                                OnGamePanel,
+                                OnRoundCountdown,
+                            ));
+
                             parent
                                 .spawn((
                                     NodeBundle {
@@ -181,19 +319,23 @@ pub fn setup_game_screen(
                                 ))
                                 .with_children(|parent| {
                                     parent
-                                        .spawn(NodeBundle {
-                                            style: Style {
-                                                width: Val::Px(250.0),
-                                                height: Val::Px(50.0),
-                                                margin: UiRect::all(Val::Px(10.0)),
-                                                border: UiRect::all(Val::Px(5.0)),
-                                                justify_content: JustifyContent::Center,
-                                                align_items: AlignItems::Center,
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(250.0),
+                                                    height: Val::Px(50.0),
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    border: UiRect::all(Val::Px(5.0)),
+                                                    justify_content: JustifyContent::Center,
+                                                    align_items: AlignItems::Center,
+                                                    ..default()
+                                                },
+                                                border_color: Color::WHITE.into(),
+                                                background_color: Color::NONE.into(),
                                                 ..default()
                                             },
-                                            border_color: Color::WHITE.into(),
-                                            ..default()
-                                        })
+                                            HotKey(KeyCode::F),
+                                        ))
                                         .with_children(|parent| {
                                             parent.spawn(
                                                 TextBundle::from_section(
@@ -218,75 +360,53 @@ pub fn setup_game_screen(
     game_state.set(GameState::PlayerMove);
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn setup_player_move_screen(
     mut query_1: Query<&mut Visibility, With<OnGamePanel>>,
-    mut query_2: Query<(&mut BorderColor, &GameMove), With<GameMove>>,
+    mut query_2: Query<(&mut BorderColor, &mut Style, &GameMove), With<GameMove>>,
+    mut query_3: Query<&mut Text, With<OnGameMoveName>>,
+    mut query_4: Query<&mut Text, (With<OnCommitmentHash>, Without<OnGameMoveName>)>,
+    mut query_5: Query<&mut Text, (With<OnRoundCountdown>, Without<OnGameMoveName>, Without<OnCommitmentHash>)>,
     selected_option: Res<SelectedOption>,
+    game_type: Res<GameType>,
+    statistics: Res<GameStatistics>,
+    mut computer_opponent: ResMut<ComputerOpponent>,
+    mut computer_commitment: ResMut<ComputerCommitment>,
+    mut game_rng: ResMut<GameRng>,
+    mut round_countdown: ResMut<RoundCountdown>,
+    game_settings: Res<GameSettings>,
 ) {
     debug!("setup_player_move_screen");
 
-    for (mut border_color, game_move) in &mut query_2 {
-        match game_move {
-            GameMove::Rock => {
-                if selected_option.value == 1 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-
-            GameMove::Paper => {
-                if selected_option.value == 2 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
+    round_countdown.0 = game_settings.round_time_limit.map(|x| Timer::from_seconds(x as f32, TimerMode::Once));
+    for mut text in &mut query_5 {
+        text.sections[0].value = round_countdown.0.as_ref().map_or(String::new(), get_countdown_text);
+    }
 
-            GameMove::Scissors => {
-                if selected_option.value == 3 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-            GameMove::Spock => {
-                if selected_option.value == 4 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-            GameMove::Lizard => {
-                if selected_option.value == 5 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-            GameMove::Fire => {
-                if selected_option.value == 4 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-            GameMove::Water => {
-                if selected_option.value == 5 {
-                    *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                } else {
-                    *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                }
-            }
-        }
+    // The nonce comes from the OS, so that the hash does not give the move away even when the seed is known.
+    let computer_move = computer_opponent.get_move(&statistics.rounds, &game_type, &mut *game_rng);
+    let commitment = Commitment::new(&game_type, computer_move, &mut OsRng);
+    for mut text in &mut query_4 {
+        text.sections[0].value = get_commitment_hash_text(&commitment.hash);
     }
+    computer_commitment.0 = Some(commitment);
+
+    update_game_move_buttons(&mut query_2, &mut query_3, &selected_option, &game_type);
 
     for mut visibility in &mut query_1 {
         *visibility = Visibility::Visible;
     }
 }
 
-pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFont>, game_statistics: Res<GameStatistics>) {
+pub fn setup_round_overview_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_statistics: Res<GameStatistics>,
+    game_type: Res<GameType>,
+    computer_opponent: Res<ComputerOpponent>,
+    computer_commitment: Res<ComputerCommitment>,
+    game_settings: Res<GameSettings>,
+) {
     let font = &game_font.0;
 
     commands
@@ -296,9 +416,9 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     width: Val::Percent(90.0),
-                    height: Val::Percent(35.0),
+                    height: Val::Percent(40.0),
                     position_type: PositionType::Absolute,
-                    top: Val::Percent(35.0),
+                    top: Val::Percent(30.0),
                     left: Val::Percent(5.0),
                     ..default()
                 },
@@ -323,12 +443,26 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                 }),
             );
 
+            if let Some(commitment) = &computer_commitment.0 {
+                parent.spawn(
+                    TextBundle::from_section(
+                        get_commitment_reveal_text(commitment, &game_type),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 10.0,
+                            color: OVERVIEW_SUB_TITLE_COLOR,
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+            }
+
             parent.spawn(
                 TextBundle::from_section(
-                    GameMove::get_phrase(
-                        &game_statistics.last_player_move.expect("Last player move is not set."),
-                        &game_statistics.last_computer_move.expect("Last computer move is not set."),
-                    ),
+                    match game_statistics.last_player_move {
+                        Some(x) => GameMove::get_phrase(&game_type, &x, &game_statistics.last_computer_move.expect("Last computer move is not set.")),
+                        None => String::from("Time is up, round is forfeited"),
+                    },
                     TextStyle {
                         font: font.clone(),
                         font_size: 24.0,
@@ -344,8 +478,19 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
             parent.spawn(
                 TextBundle::from_section(
                     format!(
-                        "Wins: {0}, Loses: {1}, Draws: {2}",
-                        game_statistics.wins, game_statistics.loses, game_statistics.draws
+                        "Wins: {0}, Loses: {1}, Draws: {2}{3}, Opponent: {4}{5}",
+                        game_statistics.wins,
+                        game_statistics.loses,
+                        game_statistics.draws,
+                        match game_statistics.timeouts {
+                            0 => String::new(),
+                            x => format!(", Timeouts: {}", x),
+                        },
+                        computer_opponent.opponent.get_friendly_name(),
+                        match computer_opponent.get_difficulty() {
+                            Some(x) => format!(" (difficulty {}/{})", x, MAX_DIFFICULTY),
+                            None => String::new(),
+                        }
                     ),
                     TextStyle {
                         font: font.clone(),
@@ -359,6 +504,21 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                 }),
             );
 
+            if game_settings.match_format != MatchFormat::Endless {
+                parent.spawn(TextBundle::from_section(
+                    game_settings.match_format.get_status_text(
+                        game_settings.match_format.get_winner(game_statistics.wins, game_statistics.loses),
+                        &game_settings.player_options.name,
+                        &computer_opponent.opponent.get_friendly_name(),
+                    ),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        color: OVERVIEW_TITLE_COLOR,
+                    },
+                ));
+            }
+
             parent
                 .spawn(NodeBundle {
                     style: Style {
@@ -373,19 +533,23 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                 })
                 .with_children(|parent| {
                     parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                width: Val::Px(250.0),
-                                height: Val::Px(50.0),
-                                margin: UiRect::all(Val::Px(10.0)),
-                                border: UiRect::all(Val::Px(5.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(250.0),
+                                    height: Val::Px(50.0),
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    border: UiRect::all(Val::Px(5.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                border_color: Color::WHITE.into(),
+                                background_color: Color::NONE.into(),
                                 ..default()
                             },
-                            border_color: Color::WHITE.into(),
-                            ..default()
-                        })
+                            HotKey(KeyCode::C),
+                        ))
                         .with_children(|parent| {
                             parent.spawn(
                                 TextBundle::from_section(
@@ -404,19 +568,23 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                         });
 
                     parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                width: Val::Px(250.0),
-                                height: Val::Px(50.0),
-                                margin: UiRect::all(Val::Px(10.0)),
-                                border: UiRect::all(Val::Px(5.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(250.0),
+                                    height: Val::Px(50.0),
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    border: UiRect::all(Val::Px(5.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                border_color: Color::WHITE.into(),
+                                background_color: Color::NONE.into(),
                                 ..default()
                             },
-                            border_color: Color::WHITE.into(),
-                            ..default()
-                        })
+                            HotKey(KeyCode::F),
+                        ))
                         .with_children(|parent| {
                             parent.spawn(
                                 TextBundle::from_section(
@@ -433,58 +601,290 @@ pub fn setup_round_overview_screen(mut commands: Commands, game_font: Res<GameFo
                                 }),
                             );
                         });
+
+                    if computer_opponent.get_explanation().is_some() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(250.0),
+                                        height: Val::Px(50.0),
+                                        margin: UiRect::all(Val::Px(10.0)),
+                                        border: UiRect::all(Val::Px(5.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    border_color: Color::WHITE.into(),
+                                    background_color: Color::NONE.into(),
+                                    ..default()
+                                },
+                                HotKey(KeyCode::T),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        "(T)hinking",
+                                        TextStyle {
+                                            font_size: BUTTON_TEXT_SMALL_SIZE,
+                                            color: Color::WHITE,
+                                            font: font.clone(),
+                                        },
+                                    )
+                                    .with_style(Style {
+                                        margin: UiRect::all(Val::Px(10.0)),
+                                        ..default()
+                                    }),
+                                );
+                            });
+                    }
                 });
         });
+
+    if let Some(explanation) = computer_opponent.get_explanation() {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        width: Val::Percent(90.0),
+                        position_type: PositionType::Absolute,
+                        top: Val::Percent(72.0),
+                        left: Val::Percent(5.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: OVERVIEW_BACKGROUND_COLOR.into(),
+                    visibility: if game_settings.show_opponent_thinking {
+                        Visibility::Visible
+                    } else {
+                        Visibility::Hidden
+                    },
+                    ..default()
+                },
+                OnRoundOverview,
+                OnOpponentThinking,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Opponent thinking",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 18.0,
+                        color: OVERVIEW_TITLE_COLOR,
+                    },
+                ));
+                for line in explanation.get_lines(&game_type) {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            line,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 14.0,
+                                color: OVERVIEW_SUB_TITLE_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+                }
+            });
+    }
+}
+
+fn get_commitment_hash_text(hash: &str) -> String {
+    format!("Computer move SHA-256: {}", hash)
+}
+
+fn get_commitment_reveal_text(commitment: &Commitment, game_type: &GameType) -> String {
+    format!(
+        "Committed: {}\nRevealed: {}\nSHA-256 of revealed move matches: {}",
+        commitment.hash,
+        commitment.get_message(game_type),
+        if commitment.verify(game_type) { "yes" } else { "NO" }
+    )
+}
+
+pub fn get_game_move_name_text(game_type: &GameType, selected_value: i32) -> String {
+    format!(
+        "{} ({}/{})",
+        GameMove(selected_value as usize - 1).get_name(game_type),
+        selected_value,
+        game_type.max_number_of_moves()
+    )
+}
+
+fn get_visible_game_moves_start(selected_index: usize, number_of_moves: usize) -> usize {
+    if number_of_moves <= GAME_MOVES_VISIBLE_COUNT {
+        0
+    } else {
+        selected_index
+            .saturating_sub(GAME_MOVES_VISIBLE_COUNT / 2)
+            .min(number_of_moves - GAME_MOVES_VISIBLE_COUNT)
+    }
+}
+
+fn update_game_move_buttons(
+    query: &mut Query<(&mut BorderColor, &mut Style, &GameMove), With<GameMove>>,
+    query_name: &mut Query<&mut Text, With<OnGameMoveName>>,
+    selected_option: &SelectedOption,
+    game_type: &GameType,
+) {
+    let selected_index = selected_option.value as usize - 1;
+    let start = get_visible_game_moves_start(selected_index, game_type.ruleset().moves.len());
+
+    for (mut border_color, mut style, game_move) in query {
+        *border_color = if game_move.0 == selected_index {
+            GAME_SELECTED_BORDER_COLOR.into()
+        } else {
+            GAME_NO_SELECTED_BORDER_COLOR.into()
+        };
+        style.display = if (start..start + GAME_MOVES_VISIBLE_COUNT).contains(&game_move.0) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for mut text in query_name {
+        text.sections[0].value = get_game_move_name_text(game_type, selected_option.value);
+    }
 }
 
-fn spawn_game_move_button(parent: &mut ChildBuilder, style: &Style, bordered: bool, game_move: GameMove, icon_style: &Style, image: &Handle<Image>) {
+// Buttons of all moves of the game type, first move is selected.
+pub fn spawn_game_move_buttons(parent: &mut ChildBuilder, game_type: &GameType, asset_server: &AssetServer, font: &Handle<Font>) {
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(250.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        border: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let button_icon_style = Style {
+        width: Val::Px(150.0),
+        height: Val::Px(150.0),
+        ..default()
+    };
+
+    for (index, ruleset_move) in game_type.ruleset().moves.iter().enumerate() {
+        spawn_game_move_button(
+            parent,
+            GameMoveButtonOptions {
+                style: &button_style,
+                bordered: index == 0,
+                visible: index < GAME_MOVES_VISIBLE_COUNT,
+                game_move: GameMove(index),
+                icon_style: &button_icon_style,
+                icon: ruleset_move.icon.as_ref().map(|x| asset_server.load(x)),
+                text: &ruleset_move.name,
+                font,
+            },
+        );
+    }
+}
+
+struct GameMoveButtonOptions<'a> {
+    style: &'a Style,
+    bordered: bool,
+    visible: bool,
+    game_move: GameMove,
+    icon_style: &'a Style,
+    icon: Option<Handle<Image>>,
+    text: &'a str,
+    font: &'a Handle<Font>,
+}
+
+fn spawn_game_move_button(parent: &mut ChildBuilder, options: GameMoveButtonOptions) {
     parent
         .spawn((
-            NodeBundle {
-                style: style.clone(),
-                border_color: if bordered {
+            ButtonBundle {
+                style: Style {
+                    display: if options.visible { Display::Flex } else { Display::None },
+                    ..options.style.clone()
+                },
+                border_color: if options.bordered {
                     GAME_SELECTED_BORDER_COLOR.into()
                 } else {
                     GAME_NO_SELECTED_BORDER_COLOR.into()
                 },
+                background_color: Color::NONE.into(),
                 ..default()
             },
-            game_move,
+            OptionButton(options.game_move.0 as i32 + 1),
+            options.game_move,
         ))
-        .with_children(|parent| {
-            parent.spawn(ImageBundle {
-                style: icon_style.clone(),
-                image: UiImage::new(image.clone()),
-                ..default()
-            });
+        .with_children(|parent| match options.icon {
+            Some(icon) => {
+                parent.spawn(ImageBundle {
+                    style: options.icon_style.clone(),
+                    image: UiImage::new(icon),
+                    ..default()
+                });
+            }
+            None => {
+                parent.spawn(
+                    TextBundle::from_section(
+                        options.text,
+                        TextStyle {
+                            font_size: BUTTON_TEXT_SMALL_SIZE,
+                            color: BUTTON_TITLE_COLOR,
+                            font: options.font.clone(),
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+            }
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_sub_button_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut selected_option: ResMut<SelectedOption>,
     mut game_state: ResMut<NextState<GameState>>,
     mut app_state: ResMut<NextState<AppState>>,
+    mut query: Query<&mut Visibility, With<OnOpponentThinking>>,
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
-    game_settings: Res<GameSettings>,
+    mut game_settings: ResMut<GameSettings>,
+    game_statistics: Res<GameStatistics>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::C) {
+    let is_match_over = game_settings.match_format.get_winner(game_statistics.wins, game_statistics.loses).is_some();
+    if actions.just_pressed(KeyCode::C) && !is_match_over {
         selected_option.set_value(1);
         game_state.set(GameState::PlayerMove);
 
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
-    } else if keyboard_input.just_pressed(KeyCode::F) {
+    } else if actions.just_pressed(KeyCode::F) || actions.just_pressed(KeyCode::C) {
         selected_option.set_value(1);
         game_state.set(GameState::NotInit);
         app_state.set(AppState::GameOverview);
 
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::T) && !query.is_empty() {
+        game_settings.show_opponent_thinking = !game_settings.show_opponent_thinking;
+        game_settings.fetch();
+        for mut visibility in &mut query {
+            *visibility = if game_settings.show_opponent_thinking {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+        }
+
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_button_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut selected_option: ResMut<SelectedOption>,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
@@ -494,7 +894,7 @@ pub fn confirm_button_action(
     game_settings: ResMut<GameSettings>,
     game_statistics: Res<GameStatistics>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::F) {
+    if actions.just_pressed(KeyCode::F) {
         selected_option.set_value(1);
         if game_statistics.last_round_result.is_none() {
             game_state.set(GameState::NotInit);
@@ -507,9 +907,11 @@ pub fn confirm_button_action(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn switch_game_move(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<(&mut BorderColor, &GameMove), With<GameMove>>,
+    actions: Res<Actions>,
+    mut query: Query<(&mut BorderColor, &mut Style, &GameMove), With<GameMove>>,
+    mut query_name: Query<&mut Text, With<OnGameMoveName>>,
     mut selected_option: ResMut<SelectedOption>,
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
@@ -517,82 +919,103 @@ pub fn switch_game_move(
     game_type: Res<GameType>,
 ) {
     let mut left_or_down: bool = false;
-    if keyboard_input.just_pressed(KeyCode::Left) {
+    if actions.just_pressed(KeyCode::Left) {
         left_or_down = true;
 
         if selected_option.value > 1 {
             selected_option.value -= 1;
+        } else {
+            selected_option.value = game_type.max_number_of_moves();
         }
-    } else if keyboard_input.just_pressed(KeyCode::Right) {
+    } else if actions.just_pressed(KeyCode::Right) {
         left_or_down = true;
 
         if selected_option.value < game_type.max_number_of_moves() {
             selected_option.value += 1;
+        } else {
+            selected_option.value = 1;
         }
     }
 
+    // Clicked move is selected too.
+    if selected_option.is_changed() {
+        update_game_move_buttons(&mut query, &mut query_name, &selected_option, &game_type);
+    }
     if left_or_down {
-        for (mut border_color, game_move) in &mut query {
-            match game_move {
-                GameMove::Rock => {
-                    if selected_option.value == 1 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
 
-                GameMove::Paper => {
-                    if selected_option.value == 2 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
+// Player move is None when the round was forfeited because time ran out.
+#[allow(clippy::too_many_arguments)]
+fn finish_round(
+    player_move: Option<GameMove>,
+    is_timeout: bool,
+    statistics: &mut GameStatistics,
+    game_type: &GameType,
+    game_settings: &GameSettings,
+    computer_commitment: &ComputerCommitment,
+    computer_opponent: &ComputerOpponent,
+    match_history: &MatchHistory,
+    game_spectators: &mut GameSpectators,
+) -> GameResult {
+    let computer_move: GameMove = computer_commitment.0.as_ref().expect("Computer move is not committed.").game_move;
+    let game_result = match player_move {
+        Some(x) => x.beats_other(game_type, &computer_move),
+        None => GameResult::Lose,
+    };
 
-                GameMove::Scissors => {
-                    if selected_option.value == 3 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
-                GameMove::Spock => {
-                    if selected_option.value == 4 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
-                GameMove::Lizard => {
-                    if selected_option.value == 5 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
-                GameMove::Fire => {
-                    if selected_option.value == 4 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
-                GameMove::Water => {
-                    if selected_option.value == 5 {
-                        *border_color = GAME_SELECTED_BORDER_COLOR.into();
-                    } else {
-                        *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
-                    }
-                }
-            }
+    match game_result {
+        GameResult::Win => {
+            statistics.wins += 1;
         }
-        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        GameResult::Lose => {
+            statistics.loses += 1;
+        }
+        GameResult::Draw => {
+            statistics.draws += 1;
+        }
+    }
+    if is_timeout {
+        statistics.timeouts += 1;
+    }
+    statistics.last_round_result = Some(game_result);
+    statistics.last_computer_move = Some(computer_move);
+    statistics.last_player_move = player_move;
+    match player_move {
+        Some(x) => statistics.rounds.push(Round { player_move: x, computer_move }),
+        None => statistics.forfeits += 1,
     }
+    let record = RoundRecord {
+        timestamp: get_timestamp(),
+        profile: game_settings.player_options.uuid,
+        match_id: statistics.match_id,
+        game_type: game_type.ruleset().id.clone(),
+        player_move: player_move.map(|x| x.get_name(game_type).to_string()),
+        opponent_move: computer_move.get_name(game_type).to_string(),
+        result: game_result,
+        opponent: computer_opponent.opponent,
+        timeout: is_timeout,
+    };
+    if let Err(e) = match_history.append(&record) {
+        warn!("Unable to save round to history: {}", e);
+    }
+    // Forfeited round has no move to show, spectators get the score with the next round.
+    if let Some(x) = player_move {
+        let score = get_score(statistics);
+        let message = get_round_played(game_type, score.totals() as u32 - 1, (x, computer_move), &score);
+        game_spectators.spectators.broadcast(&message);
+    }
+    debug!("computer: {}", computer_move.get_name(game_type));
+    if let Some(x) = player_move {
+        debug!("player: {}", x.get_name(game_type));
+    }
+    game_result
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_game_move(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut query: Query<&mut Visibility, With<OnGamePanel>>,
     selected_option: ResMut<SelectedOption>,
     audio: Res<Audio>,
@@ -600,34 +1023,32 @@ pub fn confirm_game_move(
     game_type: Res<GameType>,
     mut statistics: ResMut<GameStatistics>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut round_countdown: ResMut<RoundCountdown>,
     game_settings: Res<GameSettings>,
+    computer_commitment: Res<ComputerCommitment>,
+    computer_opponent: Res<ComputerOpponent>,
+    match_history: Res<MatchHistory>,
+    mut game_spectators: ResMut<GameSpectators>,
 ) {
-    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
-        let player_move: Option<GameMove> = GameMove::from_i32(*game_type, selected_option.get_value());
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        let player_move: Option<GameMove> = GameMove::from_i32(&game_type, selected_option.get_value());
         if let Some(x) = player_move {
-            let computer_move: GameMove = get_computer_move(&game_type);
-            let game_result = x.beats_other(&game_type, &computer_move);
-
             for mut visibility in &mut query {
                 *visibility = Visibility::Hidden;
             }
 
-            match game_result {
-                GameResult::Win => {
-                    statistics.wins += 1;
-                }
-                GameResult::Lose => {
-                    statistics.loses += 1;
-                }
-                GameResult::Draw => {
-                    statistics.draws += 1;
-                }
-            }
-            statistics.last_round_result = Some(game_result);
-            statistics.last_computer_move = Some(computer_move);
-            statistics.last_player_move = Some(x);
-            debug!("computer: {:?}", statistics.last_computer_move);
-            debug!("player: {:?}", statistics.last_player_move);
+            finish_round(
+                Some(x),
+                false,
+                &mut statistics,
+                &game_type,
+                &game_settings,
+                &computer_commitment,
+                &computer_opponent,
+                &match_history,
+                &mut game_spectators,
+            );
+            round_countdown.0 = None;
 
             game_state.set(GameState::RoundFinish);
         }
@@ -640,26 +1061,76 @@ pub fn confirm_game_move(
     }
 }
 
-fn get_computer_move(game_type: &GameType) -> GameMove {
-    let mut rng = rand::thread_rng();
-    let random_value = rng.gen_range(1..=game_type.max_number_of_moves());
-    match random_value {
-        1 => GameMove::Rock,
-        2 => GameMove::Paper,
-        3 => GameMove::Scissors,
-        4 => {
-            if *game_type == GameType::SpockLizard {
-                GameMove::Spock
-            } else {
-                GameMove::Fire
-            }
-        }
-        _ => {
-            if *game_type == GameType::SpockLizard {
-                GameMove::Lizard
-            } else {
-                GameMove::Water
-            }
-        }
+#[allow(clippy::too_many_arguments)]
+pub fn update_round_countdown(
+    time: Res<Time>,
+    mut query: Query<&mut Visibility, With<OnGamePanel>>,
+    mut query_text: Query<&mut Text, With<OnRoundCountdown>>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    game_type: Res<GameType>,
+    mut statistics: ResMut<GameStatistics>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut round_countdown: ResMut<RoundCountdown>,
+    mut game_rng: ResMut<GameRng>,
+    game_settings: Res<GameSettings>,
+    computer_commitment: Res<ComputerCommitment>,
+    computer_opponent: Res<ComputerOpponent>,
+    match_history: Res<MatchHistory>,
+    mut game_spectators: ResMut<GameSpectators>,
+) {
+    let Some(timer) = &mut round_countdown.0 else {
+        return;
+    };
+    timer.tick(time.delta());
+    for mut text in &mut query_text {
+        text.sections[0].value = get_countdown_text(timer);
+    }
+    if !timer.finished() {
+        return;
+    }
+
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+    let player_move = match game_settings.timeout_action {
+        TimeoutAction::RandomMove => Some(random_move(&game_type, &mut *game_rng)),
+        TimeoutAction::Forfeit => None,
+    };
+    let game_result = finish_round(
+        player_move,
+        true,
+        &mut statistics,
+        &game_type,
+        &game_settings,
+        &computer_commitment,
+        &computer_opponent,
+        &match_history,
+        &mut game_spectators,
+    );
+    round_countdown.0 = None;
+    game_state.set(GameState::RoundFinish);
+
+    let result_sound = match game_result {
+        GameResult::Win => &game_sounds.win,
+        GameResult::Lose => &game_sounds.lose,
+        GameResult::Draw => &game_sounds.drawn,
+    };
+    play_sound(&audio, game_settings.is_sound_on, result_sound);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_visible_game_moves_start() {
+        assert_eq!(get_visible_game_moves_start(0, 3), 0);
+        assert_eq!(get_visible_game_moves_start(2, 3), 0);
+        assert_eq!(get_visible_game_moves_start(0, 101), 0);
+        assert_eq!(get_visible_game_moves_start(2, 101), 0);
+        assert_eq!(get_visible_game_moves_start(3, 101), 1);
+        assert_eq!(get_visible_game_moves_start(50, 101), 48);
+        assert_eq!(get_visible_game_moves_start(100, 101), 96);
     }
 }
@@ -1,8 +1,21 @@
+use std::iter;
+
 use bevy::prelude::*;
 use bevy_kira_audio::Audio;
 
+use crate::button::{Actions, HotKey};
 use crate::common::*;
+use crate::game_result::GameResult;
+use crate::game_rng::GameRng;
 use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+use crate::history::{get_timestamp, HistoryQuery, MatchHistory, MatchOpponent, MatchRecord, MatchResults};
+use crate::match_format::MatchFormat;
+use crate::opponent::ComputerOpponent;
+use crate::predictability::{Entropy, Predictability};
+use crate::rating::{RatingEntry, Ratings};
+
+const MAX_TIPS: usize = 4;
 
 #[derive(Component)]
 pub struct OnGameOverview;
@@ -11,14 +24,121 @@ pub struct GameOverviewPlugin;
 
 impl Plugin for GameOverviewPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::GameOverview), setup_score_overview_screen)
+        app.add_systems(OnEnter(AppState::GameOverview), (rate_match, record_match, setup_score_overview_screen).chain())
             .add_systems(Update, confirm_button_action.run_if(in_state(AppState::GameOverview)))
             .add_systems(OnExit(AppState::GameOverview), despawn_screen::<OnGameOverview>);
     }
 }
 
-pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFont>, game_statistics: Res<GameStatistics>, game_settings: Res<GameSettings>) {
+fn get_predictability_lines(predictability: &Predictability, game_type: &GameType) -> Vec<String> {
+    let bits = |x: Option<Entropy>| x.map_or(String::from("-"), |x| format!("{:.2} bits", x.bits));
+    let mut lines = vec![match predictability.get_score() {
+        Some(x) => format!("Predictability: {}/100", x),
+        None => String::from("Predictability: not enough rounds"),
+    }];
+    if predictability.get_score().is_some() {
+        lines.push(format!(
+            "Move entropy: {}, after your last move: {}, after last result: {}",
+            bits(predictability.entropy),
+            bits(predictability.entropy_given_move),
+            bits(predictability.entropy_given_result)
+        ));
+    }
+    lines.extend(predictability.get_tips(game_type).into_iter().take(MAX_TIPS));
+    lines
+}
+
+fn get_rating_line(entry: Option<&RatingEntry>, computer_opponent: &ComputerOpponent) -> String {
+    match entry {
+        Some(x) => format!(
+            "Rating vs {}: {:.0} ± {:.0} ({:+.0})",
+            computer_opponent.opponent.get_friendly_name(),
+            x.rating.rating,
+            x.rating.deviation * 2.0,
+            x.get_change().unwrap_or(0.0)
+        ),
+        None => format!("Rating vs {}: not rated yet", computer_opponent.opponent.get_friendly_name()),
+    }
+}
+
+pub fn rate_match(
+    mut ratings: ResMut<Ratings>,
+    game_statistics: Res<GameStatistics>,
+    game_settings: Res<GameSettings>,
+    game_type: Res<GameType>,
+    computer_opponent: Res<ComputerOpponent>,
+) {
+    // Forfeited rounds count as lost.
+    let results: Vec<GameResult> = game_statistics
+        .rounds
+        .iter()
+        .map(|x| x.player_move.beats_other(&game_type, &x.computer_move))
+        .chain(iter::repeat_n(GameResult::Lose, game_statistics.forfeits as usize))
+        .collect();
+    let is_updated = ratings.update(
+        game_settings.player_options.uuid,
+        computer_opponent.opponent,
+        &game_type,
+        game_statistics.match_id,
+        &results,
+        get_timestamp(),
+    );
+    if is_updated {
+        if let Err(e) = ratings.save() {
+            warn!("Unable to save ratings: {}", e);
+        }
+    }
+}
+
+pub fn record_match(
+    match_results: Res<MatchResults>,
+    game_statistics: Res<GameStatistics>,
+    game_settings: Res<GameSettings>,
+    game_type: Res<GameType>,
+    computer_opponent: Res<ComputerOpponent>,
+) {
+    if game_statistics.totals() == 0 {
+        return;
+    }
+    let record = MatchRecord {
+        timestamp: get_timestamp(),
+        profile: game_settings.player_options.uuid,
+        match_id: game_statistics.match_id,
+        game_type: game_type.ruleset().id.clone(),
+        opponent: MatchOpponent::Computer(computer_opponent.opponent),
+        format: game_settings.match_format,
+        wins: game_statistics.wins,
+        loses: game_statistics.loses,
+        draws: game_statistics.draws,
+        winner: game_settings.match_format.get_winner(game_statistics.wins, game_statistics.loses),
+        forfeit: false,
+    };
+    if let Err(e) = match_results.append(&record) {
+        warn!("Unable to save match result: {}", e);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn setup_score_overview_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_statistics: Res<GameStatistics>,
+    game_settings: Res<GameSettings>,
+    game_rng: Res<GameRng>,
+    game_type: Res<GameType>,
+    match_history: Res<MatchHistory>,
+    ratings: Res<Ratings>,
+    computer_opponent: Res<ComputerOpponent>,
+) {
     let font = &game_font.0;
+    let rating = ratings.get(game_settings.player_options.uuid, computer_opponent.opponent, &game_type.ruleset().id);
+    let lifetime_rounds = match_history.query(&HistoryQuery {
+        profile: Some(game_settings.player_options.uuid),
+        game_type: Some(game_type.ruleset().id.clone()),
+        ..default()
+    });
+    let lifetime_count = |result: GameResult| lifetime_rounds.iter().filter(|x| x.result == result).count();
+    let predictability = Predictability::new(&game_statistics.rounds, &game_type);
 
     commands
         .spawn((
@@ -69,7 +189,7 @@ pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFo
                                 flex_direction: FlexDirection::Column,
                                 align_items: AlignItems::Center,
                                 width: Val::Percent(100.0),
-                                height: Val::Percent(40.0),
+                                height: Val::Percent(60.0),
                                 ..default()
                             },
                             background_color: MENU_BACKGROUND_COLOR.into(),
@@ -91,14 +211,30 @@ pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFo
                                 }),
                             );
 
+                            if game_settings.match_format != MatchFormat::Endless {
+                                parent.spawn(TextBundle::from_section(
+                                    game_settings.match_format.get_status_text(
+                                        game_settings.match_format.get_winner(game_statistics.wins, game_statistics.loses),
+                                        &game_settings.player_options.name,
+                                        &computer_opponent.opponent.get_friendly_name(),
+                                    ),
+                                    TextStyle {
+                                        font_size: 32.0,
+                                        color: OVERVIEW_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                ));
+                            }
+
                             parent.spawn(
                                 TextBundle::from_section(
                                     format!(
-                                        "Total: {0}, wins: {1}, loses: {2}, draws: {3}",
+                                        "Total: {0}, wins: {1}, loses: {2}, draws: {3}, timeouts: {4}",
                                         game_statistics.totals(),
                                         game_statistics.wins,
                                         game_statistics.loses,
-                                        game_statistics.draws
+                                        game_statistics.draws,
+                                        game_statistics.timeouts
                                     ),
                                     TextStyle {
                                         font_size: 32.0,
@@ -112,6 +248,75 @@ pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFo
                                 }),
                             );
 
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    format!(
+                                        "All {0} matches: {1} rounds, wins: {2}, loses: {3}, draws: {4}",
+                                        game_type.get_friendly_name(),
+                                        lifetime_rounds.len(),
+                                        lifetime_count(GameResult::Win),
+                                        lifetime_count(GameResult::Lose),
+                                        lifetime_count(GameResult::Draw)
+                                    ),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: OVERVIEW_SUB_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    get_rating_line(rating, &computer_opponent),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: OVERVIEW_SUB_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    format!("Seed: {}", game_rng.seed),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: OVERVIEW_SUB_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            for (i, line) in get_predictability_lines(&predictability, &game_type).into_iter().enumerate() {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        line,
+                                        TextStyle {
+                                            font_size: if i == 0 { BUTTON_TEXT_SMALL_SIZE } else { 12.0 },
+                                            color: if i == 0 { OVERVIEW_TITLE_COLOR } else { OVERVIEW_SUB_TITLE_COLOR },
+                                            font: font.clone(),
+                                        },
+                                    )
+                                    .with_style(Style {
+                                        margin: UiRect::all(Val::Px(5.0)),
+                                        ..default()
+                                    }),
+                                );
+                            }
+
                             parent
                                 .spawn((NodeBundle {
                                     style: Style {
@@ -126,19 +331,23 @@ pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFo
                                 },))
                                 .with_children(|parent| {
                                     parent
-                                        .spawn(NodeBundle {
-                                            style: Style {
-                                                width: Val::Px(250.0),
-                                                height: Val::Px(50.0),
-                                                margin: UiRect::new(Val::Px(20.0), Val::Px(20.0), Val::Px(80.0), Val::Px(20.0)),
-                                                border: UiRect::all(Val::Px(5.0)),
-                                                justify_content: JustifyContent::Center,
-                                                align_items: AlignItems::Center,
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(250.0),
+                                                    height: Val::Px(50.0),
+                                                    margin: UiRect::new(Val::Px(20.0), Val::Px(20.0), Val::Px(80.0), Val::Px(20.0)),
+                                                    border: UiRect::all(Val::Px(5.0)),
+                                                    justify_content: JustifyContent::Center,
+                                                    align_items: AlignItems::Center,
+                                                    ..default()
+                                                },
+                                                border_color: Color::WHITE.into(),
+                                                background_color: Color::NONE.into(),
                                                 ..default()
                                             },
-                                            border_color: Color::WHITE.into(),
-                                            ..default()
-                                        })
+                                            HotKey(KeyCode::C),
+                                        ))
                                         .with_children(|parent| {
                                             parent.spawn(
                                                 TextBundle::from_section(
@@ -161,17 +370,20 @@ pub fn setup_score_overview_screen(mut commands: Commands, game_font: Res<GameFo
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_button_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut game_statistics: ResMut<GameStatistics>,
+    mut computer_opponent: ResMut<ComputerOpponent>,
     audio: Res<Audio>,
     game_settings: Res<GameSettings>,
     game_sounds: Res<GameSounds>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::C) {
+    if actions.just_pressed(KeyCode::C) {
         game_statistics.reset_scores();
+        computer_opponent.reset();
         game_state.set(GameState::NotInit);
         app_state.set(AppState::Menu);
 
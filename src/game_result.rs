@@ -1,4 +1,6 @@
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GameResult {
     Win,
     Lose,
@@ -15,6 +17,35 @@ impl GameResult {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResultCounts {
+    pub wins: usize,
+    pub loses: usize,
+    pub draws: usize,
+}
+
+impl ResultCounts {
+    pub fn add(&mut self, result: GameResult) {
+        match result {
+            GameResult::Win => self.wins += 1,
+            GameResult::Lose => self.loses += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+    }
+
+    pub fn totals(&self) -> usize {
+        self.wins + self.loses + self.draws
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.totals() == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.totals() as f64
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::game_result::GameResult::{Draw, Lose, Win};
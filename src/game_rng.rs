@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+const SEED_ARGUMENT: &str = "--seed";
+
+// Every random decision of a match draws from this generator. It is reseeded when a match starts, so that the same seed
+// and the same player moves always give the same match.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub seed_argument: Option<u64>,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed_argument: Option<u64>) -> Self {
+        let seed = seed_argument.unwrap_or_else(random);
+        Self {
+            seed,
+            seed_argument,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    // Seed from the command line wins over the one from settings. Without both, every match gets a new seed.
+    pub fn start_match(&mut self, settings_seed: Option<u64>) {
+        let seed = self.seed_argument.or(settings_seed).unwrap_or_else(random);
+        self.reseed(seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+pub fn get_seed_argument(args: impl Iterator<Item = String>) -> Option<u64> {
+    let mut args = args.skip_while(|x| x != SEED_ARGUMENT).skip(1);
+    match args.next() {
+        Some(x) => match x.parse() {
+            Ok(x) => Some(x),
+            Err(_) => {
+                warn!("Invalid seed argument: {}", x);
+                None
+            }
+        },
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::Round;
+    use crate::game_move::GameMove;
+    use crate::opponent::{ComputerOpponent, Opponent};
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    fn args(x: &[&str]) -> impl Iterator<Item = String> {
+        x.iter().map(|x| x.to_string()).collect::<Vec<String>>().into_iter()
+    }
+
+    #[test]
+    fn test_get_seed_argument() {
+        assert_eq!(get_seed_argument(args(&["rps"])), None);
+        assert_eq!(get_seed_argument(args(&["rps", "--seed", "42"])), Some(42));
+        assert_eq!(get_seed_argument(args(&["rps", "--seed"])), None);
+        assert_eq!(get_seed_argument(args(&["rps", "--seed", "x"])), None);
+    }
+
+    #[test]
+    fn test_start_match() {
+        let mut game_rng = GameRng::new(None);
+        game_rng.start_match(Some(7));
+        assert_eq!(game_rng.seed, 7);
+
+        let mut game_rng = GameRng::new(Some(3));
+        assert_eq!(game_rng.seed, 3);
+        game_rng.start_match(Some(7));
+        assert_eq!(game_rng.seed, 3);
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_match() {
+        let game_type = bundled_game_type("rps_15");
+        let player_moves = [3, 3, 7, 0, 14, 2, 3, 3, 9, 9, 9, 1, 5, 3, 3, 7];
+        let play = |opponent: Opponent, seed: u64| {
+            let mut game_rng = GameRng::new(None);
+            game_rng.start_match(Some(seed));
+            let mut computer_opponent = ComputerOpponent::new(opponent, 45);
+            let mut history = Vec::new();
+            for player_move in player_moves {
+                let computer_move = computer_opponent.get_move(&history, &game_type, &mut game_rng);
+                history.push(Round {
+                    player_move: GameMove(player_move),
+                    computer_move,
+                });
+            }
+            history
+        };
+
+        for opponent in Opponent::ALL {
+            assert_eq!(play(opponent, 11), play(opponent, 11), "{:?}", opponent);
+        }
+        assert_ne!(play(Opponent::Random, 11), play(Opponent::Random, 12));
+    }
+}
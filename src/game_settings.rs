@@ -1,15 +1,55 @@
+use crate::lan::default_lan_address;
+use crate::match_format::MatchFormat;
+use crate::opponent::Opponent;
 use crate::player_options::PlayerOptions;
+use crate::round_timer::TimeoutAction;
+use crate::split_keyboard::default_split_keyboard_keys;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::Path;
+use uuid::Uuid;
 
 const GAME_SETTINGS_FILE_PATH: &str = "./GAME_SETTINGS";
+pub const DEFAULT_TARGET_WIN_RATE: u32 = 45;
+const MIN_TARGET_WIN_RATE: u32 = 20;
+const MAX_TARGET_WIN_RATE: u32 = 70;
+const TARGET_WIN_RATE_STEP: u32 = 5;
+
 #[derive(Resource, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameSettings {
     pub player_options: PlayerOptions,
+    #[serde(default = "PlayerOptions::new")]
+    pub second_player_options: PlayerOptions,
     pub is_sound_on: bool,
+    #[serde(default)]
+    pub opponent: Opponent,
+    #[serde(default = "default_target_win_rate")]
+    pub target_win_rate: u32,
+    #[serde(default)]
+    pub show_opponent_thinking: bool,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub match_format: MatchFormat,
+    #[serde(default)]
+    pub round_time_limit: Option<u32>,
+    #[serde(default)]
+    pub timeout_action: TimeoutAction,
+    #[serde(default = "default_split_keyboard_keys")]
+    pub split_keyboard_keys: [Vec<String>; 2],
+    #[serde(default = "default_lan_address")]
+    pub lan_address: String,
+    #[serde(default)]
+    pub muted_profiles: Vec<Uuid>,
+    // Matches against the computer are announced on LAN only when the player allowed it.
+    #[serde(default)]
+    pub is_watchable: bool,
+}
+
+fn default_target_win_rate() -> u32 {
+    DEFAULT_TARGET_WIN_RATE
 }
 
 impl GameSettings {
@@ -39,10 +79,30 @@ impl GameSettings {
             .unwrap_or_else(|_| panic!("Unable to write to file: {}", GAME_SETTINGS_FILE_PATH));
     }
 
+    pub fn next_target_win_rate(&self) -> u32 {
+        if self.target_win_rate + TARGET_WIN_RATE_STEP > MAX_TARGET_WIN_RATE {
+            MIN_TARGET_WIN_RATE
+        } else {
+            self.target_win_rate + TARGET_WIN_RATE_STEP
+        }
+    }
+
     fn new() -> Self {
         Self {
             player_options: PlayerOptions::new(),
+            second_player_options: PlayerOptions::new(),
             is_sound_on: true,
+            opponent: Opponent::default(),
+            target_win_rate: DEFAULT_TARGET_WIN_RATE,
+            show_opponent_thinking: false,
+            seed: None,
+            match_format: MatchFormat::default(),
+            round_time_limit: None,
+            timeout_action: TimeoutAction::default(),
+            split_keyboard_keys: default_split_keyboard_keys(),
+            lan_address: default_lan_address(),
+            muted_profiles: Vec::new(),
+            is_watchable: false,
         }
     }
     fn new_and_persist() -> Self {
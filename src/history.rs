@@ -0,0 +1,264 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game_result::GameResult;
+use crate::match_format::MatchFormat;
+use crate::opponent::Opponent;
+
+const MATCH_HISTORY_FILE_PATH: &str = "./MATCH_HISTORY";
+const MATCH_RESULTS_FILE_PATH: &str = "./MATCH_RESULTS";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub timestamp: u64,
+    pub profile: Uuid,
+    pub match_id: Uuid,
+    pub game_type: String,
+    // None when the round was forfeited because time ran out.
+    pub player_move: Option<String>,
+    pub opponent_move: String,
+    pub result: GameResult,
+    pub opponent: Opponent,
+    #[serde(default)]
+    pub timeout: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    pub profile: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub game_type: Option<String>,
+    pub opponent: Option<Opponent>,
+    pub since: Option<u64>,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, record: &RoundRecord) -> bool {
+        self.profile.is_none_or(|x| x == record.profile)
+            && self.match_id.is_none_or(|x| x == record.match_id)
+            && self.game_type.as_ref().is_none_or(|x| *x == record.game_type)
+            && self.opponent.is_none_or(|x| x == record.opponent)
+            && self.since.is_none_or(|x| record.timestamp >= x)
+    }
+}
+
+// Opponent of a recorded match. Computer opponents are written as before, so older match results still load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MatchOpponent {
+    Computer(Opponent),
+    Player(PlayerOpponent),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerOpponent {
+    // Player on another machine.
+    Lan,
+}
+
+// Winner is Win when the player won the match, Lose when the computer did and None when the match was finished before
+// it was decided. Forfeited LAN match is won by the player who stayed connected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRecord {
+    pub timestamp: u64,
+    pub profile: Uuid,
+    pub match_id: Uuid,
+    pub game_type: String,
+    pub opponent: MatchOpponent,
+    pub format: MatchFormat,
+    pub wins: u32,
+    pub loses: u32,
+    pub draws: u32,
+    pub winner: Option<GameResult>,
+    #[serde(default)]
+    pub forfeit: bool,
+}
+
+fn append_line<T: Serialize>(path: &Path, record: &T) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+fn load_lines<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|x| !x.trim().is_empty())
+        .filter_map(|x| match serde_json::from_str(&x) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                warn!("Skipping invalid history line: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Rounds are appended as JSON lines, one round per line, so a crash can lose at most the line being written.
+#[derive(Resource, Debug)]
+pub struct MatchHistory {
+    path: PathBuf,
+}
+
+impl MatchHistory {
+    pub fn init() -> Self {
+        MatchHistory::new(Path::new(MATCH_HISTORY_FILE_PATH))
+    }
+
+    pub fn new(path: &Path) -> Self {
+        Self { path: path.to_path_buf() }
+    }
+
+    pub fn append(&self, record: &RoundRecord) -> std::io::Result<()> {
+        append_line(&self.path, record)
+    }
+
+    pub fn load(&self) -> Vec<RoundRecord> {
+        load_lines(&self.path)
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Vec<RoundRecord> {
+        self.load().into_iter().filter(|x| query.matches(x)).collect()
+    }
+}
+
+// Match results are kept apart from rounds, one line per finished match.
+#[derive(Resource, Debug)]
+pub struct MatchResults {
+    path: PathBuf,
+}
+
+impl MatchResults {
+    pub fn init() -> Self {
+        MatchResults::new(Path::new(MATCH_RESULTS_FILE_PATH))
+    }
+
+    pub fn new(path: &Path) -> Self {
+        Self { path: path.to_path_buf() }
+    }
+
+    pub fn append(&self, record: &MatchRecord) -> std::io::Result<()> {
+        append_line(&self.path, record)
+    }
+
+    pub fn load(&self) -> Vec<MatchRecord> {
+        load_lines(&self.path)
+    }
+}
+
+pub fn get_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn record(profile: Uuid, game_type: &str, result: GameResult, timestamp: u64) -> RoundRecord {
+        RoundRecord {
+            timestamp,
+            profile,
+            match_id: Uuid::nil(),
+            game_type: String::from(game_type),
+            player_move: Some(String::from("Rock")),
+            opponent_move: String::from("Paper"),
+            result,
+            opponent: Opponent::Markov(2),
+            timeout: false,
+        }
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let path = std::env::temp_dir().join(format!("rps-history-{}", Uuid::new_v4()));
+        let history = MatchHistory::new(&path);
+        assert!(history.load().is_empty());
+
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let records = [
+            record(first, "normal", GameResult::Win, 10),
+            record(second, "normal", GameResult::Lose, 20),
+            record(first, "rps_7", GameResult::Draw, 30),
+        ];
+        for x in &records {
+            history.append(x).unwrap();
+        }
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"not json\n\n").unwrap();
+
+        assert_eq!(history.load(), records);
+        assert_eq!(
+            history.query(&HistoryQuery {
+                profile: Some(first),
+                ..default()
+            }),
+            vec![records[0].clone(), records[2].clone()]
+        );
+        assert_eq!(
+            history.query(&HistoryQuery {
+                game_type: Some(String::from("normal")),
+                since: Some(15),
+                ..default()
+            }),
+            vec![records[1].clone()]
+        );
+        assert!(history
+            .query(&HistoryQuery {
+                opponent: Some(Opponent::Random),
+                ..default()
+            })
+            .is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_match_results() {
+        let path = std::env::temp_dir().join(format!("rps-results-{}", Uuid::new_v4()));
+        let results = MatchResults::new(&path);
+        assert!(results.load().is_empty());
+
+        let record = MatchRecord {
+            timestamp: 10,
+            profile: Uuid::new_v4(),
+            match_id: Uuid::new_v4(),
+            game_type: String::from("normal"),
+            opponent: MatchOpponent::Computer(Opponent::Frequency),
+            format: MatchFormat::BestOf(5),
+            wins: 3,
+            loses: 1,
+            draws: 2,
+            winner: Some(GameResult::Win),
+            forfeit: false,
+        };
+        results.append(&record).unwrap();
+        let lan_record = MatchRecord {
+            opponent: MatchOpponent::Player(PlayerOpponent::Lan),
+            forfeit: true,
+            ..record.clone()
+        };
+        results.append(&lan_record).unwrap();
+        assert_eq!(results.load(), vec![record, lan_record]);
+
+        let opponent = MatchOpponent::Computer(Opponent::Markov(2));
+        assert_eq!(serde_json::to_string(&opponent).unwrap(), serde_json::to_string(&Opponent::Markov(2)).unwrap());
+        assert_eq!(
+            serde_json::from_str::<MatchOpponent>(r#""Lan""#).unwrap(),
+            MatchOpponent::Player(PlayerOpponent::Lan)
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+use std::cmp::Reverse;
+
+use crate::game_result::{GameResult, ResultCounts};
+use crate::history::RoundRecord;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HistoryStatistics {
+    pub totals: ResultCounts,
+    pub per_game_type: Vec<(String, ResultCounts)>,
+    pub per_move: Vec<(String, ResultCounts)>,
+    pub longest_win_streak: usize,
+    pub longest_lose_streak: usize,
+    pub timeouts: usize,
+}
+
+fn add_to_group(groups: &mut Vec<(String, ResultCounts)>, key: &str, result: GameResult) {
+    match groups.iter_mut().find(|(x, _)| x == key) {
+        Some((_, counts)) => counts.add(result),
+        None => {
+            let mut counts = ResultCounts::default();
+            counts.add(result);
+            groups.push((key.to_string(), counts));
+        }
+    }
+}
+
+impl HistoryStatistics {
+    // Records are expected in the order they were played. Moves are grouped by name, so the same move of different game
+    // types is counted together.
+    pub fn new(records: &[RoundRecord]) -> Self {
+        let mut statistics = HistoryStatistics::default();
+        let mut win_streak = 0;
+        let mut lose_streak = 0;
+        for record in records {
+            statistics.totals.add(record.result);
+            add_to_group(&mut statistics.per_game_type, &record.game_type, record.result);
+            if let Some(x) = &record.player_move {
+                add_to_group(&mut statistics.per_move, x, record.result);
+            }
+            if record.timeout {
+                statistics.timeouts += 1;
+            }
+
+            win_streak = if record.result == GameResult::Win { win_streak + 1 } else { 0 };
+            lose_streak = if record.result == GameResult::Lose { lose_streak + 1 } else { 0 };
+            statistics.longest_win_streak = statistics.longest_win_streak.max(win_streak);
+            statistics.longest_lose_streak = statistics.longest_lose_streak.max(lose_streak);
+        }
+        statistics.per_game_type.sort_by_key(|(_, x)| Reverse(x.totals()));
+        statistics.per_move.sort_by_key(|(_, x)| Reverse(x.totals()));
+        statistics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::opponent::Opponent;
+
+    use super::*;
+
+    fn record(game_type: &str, player_move: Option<&str>, result: GameResult) -> RoundRecord {
+        RoundRecord {
+            timestamp: 0,
+            profile: Uuid::nil(),
+            match_id: Uuid::nil(),
+            game_type: String::from(game_type),
+            player_move: player_move.map(String::from),
+            opponent_move: String::from("Rock"),
+            result,
+            opponent: Opponent::Random,
+            timeout: player_move.is_none(),
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let statistics = HistoryStatistics::new(&[]);
+        assert_eq!(statistics, HistoryStatistics::default());
+        assert_eq!(statistics.totals.win_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let records = [
+            record("normal", Some("Rock"), GameResult::Win),
+            record("normal", Some("Paper"), GameResult::Win),
+            record("rps_7", Some("Rock"), GameResult::Draw),
+            record("normal", Some("Rock"), GameResult::Lose),
+            record("normal", Some("Scissors"), GameResult::Lose),
+            record("normal", Some("Rock"), GameResult::Lose),
+            record("rps_7", Some("Sponge"), GameResult::Win),
+            record("rps_7", Some("Rock"), GameResult::Win),
+            record("rps_7", Some("Rock"), GameResult::Win),
+            record("normal", None, GameResult::Lose),
+        ];
+        let statistics = HistoryStatistics::new(&records);
+        assert_eq!(statistics.totals, ResultCounts { wins: 5, loses: 4, draws: 1 });
+        assert_eq!(
+            statistics.per_game_type,
+            vec![
+                (String::from("normal"), ResultCounts { wins: 2, loses: 4, draws: 0 }),
+                (String::from("rps_7"), ResultCounts { wins: 3, loses: 0, draws: 1 }),
+            ]
+        );
+        assert_eq!(statistics.per_move[0], (String::from("Rock"), ResultCounts { wins: 3, loses: 2, draws: 1 }));
+        assert_eq!(statistics.per_move.len(), 4);
+        assert_eq!(statistics.timeouts, 1);
+        assert_eq!(statistics.longest_win_streak, 3);
+        assert_eq!(statistics.longest_lose_streak, 3);
+        assert_eq!(statistics.per_game_type[1].1.win_rate(), 0.75);
+    }
+}
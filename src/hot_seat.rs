@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::button::{spawn_button, spawn_buttons, Actions};
+use crate::common::*;
+use crate::game::{get_game_move_name_text, spawn_game_move_buttons, switch_game_move, OnGameMoveName};
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum HotSeatState {
+    #[default]
+    NotInit,
+    FirstPlayerMove,
+    PassToSecondPlayer,
+    SecondPlayerMove,
+    RoundFinish,
+}
+
+impl LocalMatchState for HotSeatState {
+    const NOT_INIT: Self = HotSeatState::NotInit;
+    const NEXT_ROUND: Self = HotSeatState::FirstPlayerMove;
+}
+
+#[derive(Component)]
+pub struct OnHotSeatMoveScreen;
+
+#[derive(Component)]
+pub struct OnHotSeatPassScreen;
+
+#[derive(Component)]
+pub struct OnHotSeatResultScreen;
+
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<HotSeatState>()
+            .add_systems(OnEnter(AppState::HotSeat), init_hot_seat)
+            .add_systems(OnEnter(HotSeatState::FirstPlayerMove), setup_move_screen)
+            .add_systems(OnEnter(HotSeatState::SecondPlayerMove), setup_move_screen)
+            .add_systems(
+                Update,
+                (switch_game_move, confirm_hot_seat_move).run_if(in_state(HotSeatState::FirstPlayerMove).or_else(in_state(HotSeatState::SecondPlayerMove))),
+            )
+            .add_systems(OnExit(HotSeatState::FirstPlayerMove), despawn_screen::<OnHotSeatMoveScreen>)
+            .add_systems(OnExit(HotSeatState::SecondPlayerMove), despawn_screen::<OnHotSeatMoveScreen>)
+            .add_systems(OnEnter(HotSeatState::PassToSecondPlayer), setup_pass_screen)
+            .add_systems(Update, confirm_pass.run_if(in_state(HotSeatState::PassToSecondPlayer)))
+            .add_systems(OnExit(HotSeatState::PassToSecondPlayer), despawn_screen::<OnHotSeatPassScreen>)
+            .add_systems(OnEnter(HotSeatState::RoundFinish), setup_result_screen)
+            .add_systems(Update, confirm_local_result_action::<HotSeatState>.run_if(in_state(HotSeatState::RoundFinish)))
+            .add_systems(OnExit(HotSeatState::RoundFinish), despawn_screen::<OnHotSeatResultScreen>);
+    }
+}
+
+pub fn init_hot_seat(mut local_match: ResMut<LocalMatch>, mut hot_seat_state: ResMut<NextState<HotSeatState>>) {
+    *local_match = LocalMatch::default();
+    hot_seat_state.set(HotSeatState::FirstPlayerMove);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn setup_move_screen(
+    mut commands: Commands,
+    mut selected_option: ResMut<SelectedOption>,
+    game_font: Res<GameFont>,
+    asset_server: Res<AssetServer>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    hot_seat_state: Res<State<HotSeatState>>,
+    local_match: Res<LocalMatch>,
+) {
+    let font = &game_font.0;
+    let player_name = match hot_seat_state.get() {
+        HotSeatState::SecondPlayerMove => &game_settings.second_player_options.name,
+        _ => &game_settings.player_options.name,
+    };
+    // Every player starts from the first move, so the selection does not give the first player's move away.
+    selected_option.set_value(1);
+
+    spawn_panel(&mut commands, OnHotSeatMoveScreen, |parent| {
+        spawn_text(parent, format!("{}, choose your move", player_name), TITLE_SIZE, TITLE_COLOR, font);
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                spawn_game_move_buttons(parent, &game_type, &asset_server, font);
+            });
+        parent.spawn((
+            TextBundle::from_section(
+                get_game_move_name_text(&game_type, 1),
+                TextStyle {
+                    font_size: BUTTON_TEXT_SMALL_SIZE,
+                    color: BUTTON_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ),
+            OnGameMoveName,
+        ));
+        spawn_text(parent, get_score_text(&local_match, &game_settings), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_buttons(parent, &[("ENTER to lock in", KeyCode::Return), ("(F)inish", KeyCode::F)], font);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn confirm_hot_seat_move(
+    actions: Res<Actions>,
+    selected_option: Res<SelectedOption>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    hot_seat_state: Res<State<HotSeatState>>,
+    mut next_hot_seat_state: ResMut<NextState<HotSeatState>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut local_match: ResMut<LocalMatch>,
+) {
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        let Some(game_move) = GameMove::from_i32(&game_type, selected_option.get_value()) else {
+            return;
+        };
+        match (hot_seat_state.get(), local_match.first_move) {
+            (HotSeatState::SecondPlayerMove, Some(first_move)) => {
+                let result = local_match.add_round(&game_type, first_move, game_move);
+                next_hot_seat_state.set(HotSeatState::RoundFinish);
+                let result_sound = match result {
+                    GameResult::Draw => &game_sounds.drawn,
+                    _ => &game_sounds.win,
+                };
+                play_sound(&audio, game_settings.is_sound_on, result_sound);
+            }
+            _ => {
+                local_match.first_move = Some(game_move);
+                next_hot_seat_state.set(HotSeatState::PassToSecondPlayer);
+                play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+        }
+    } else if actions.just_pressed(KeyCode::F) {
+        next_hot_seat_state.set(HotSeatState::NotInit);
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+pub fn setup_pass_screen(mut commands: Commands, game_font: Res<GameFont>, game_settings: Res<GameSettings>) {
+    let font = &game_font.0;
+    spawn_panel(&mut commands, OnHotSeatPassScreen, |parent| {
+        spawn_text(
+            parent,
+            format!("Pass to {}", game_settings.second_player_options.name),
+            TITLE_SIZE,
+            TITLE_COLOR,
+            font,
+        );
+        spawn_button(
+            parent,
+            &format!("{}, press ENTER when ready", game_settings.second_player_options.name),
+            KeyCode::Return,
+            font,
+        );
+    });
+}
+
+pub fn confirm_pass(
+    actions: Res<Actions>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut hot_seat_state: ResMut<NextState<HotSeatState>>,
+) {
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        hot_seat_state.set(HotSeatState::SecondPlayerMove);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+pub fn setup_result_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    local_match: Res<LocalMatch>,
+) {
+    spawn_round_result(&mut commands, OnHotSeatResultScreen, &game_font.0, &game_type, &game_settings, &local_match);
+}
@@ -0,0 +1,316 @@
+use rand::prelude::*;
+
+use crate::common::Round;
+use crate::game_move::GameMove;
+use crate::game_type::GameType;
+use crate::strategy::{Explanation, Strategy};
+
+const MAX_MATCH_LENGTH: usize = 20;
+const SCORE_DECAY: f64 = 0.95;
+const META_LAYERS: [&str; 3] = ["first guess", "second guess", "third guess"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Predictor {
+    HistoryMatch,
+    MovesMatch,
+    Frequency,
+    Random,
+}
+
+impl Predictor {
+    const ALL: [Predictor; 4] = [Predictor::HistoryMatch, Predictor::MovesMatch, Predictor::Frequency, Predictor::Random];
+
+    fn get_friendly_name(&self) -> &str {
+        match self {
+            Predictor::HistoryMatch => "History matching",
+            Predictor::MovesMatch => "Move sequence matching",
+            Predictor::Frequency => "Frequency",
+            Predictor::Random => "Random",
+        }
+    }
+
+    // Predicts the next value of `moves`, where `other_moves` are the moves played against them.
+    fn predict(&self, moves: &[usize], other_moves: &[usize], number_of_moves: usize, rng: &mut dyn RngCore) -> Option<usize> {
+        match self {
+            Predictor::HistoryMatch => {
+                let pairs: Vec<(usize, usize)> = moves.iter().copied().zip(other_moves.iter().copied()).collect();
+                find_longest_match(&pairs).map(|x| moves[x])
+            }
+            Predictor::MovesMatch => find_longest_match(moves).map(|x| moves[x]),
+            Predictor::Frequency => {
+                let mut counts = vec![0; number_of_moves];
+                for x in moves {
+                    counts[*x] += 1;
+                }
+                let best = counts.iter().max().copied().unwrap_or(0);
+                if best > 0 {
+                    counts.iter().position(|x| *x == best)
+                } else {
+                    None
+                }
+            }
+            Predictor::Random => Some(rng.gen_range(0..number_of_moves)),
+        }
+    }
+}
+
+// Finds the longest suffix of the sequence which also occurred earlier and returns the index following that occurrence.
+fn find_longest_match<T: PartialEq>(sequence: &[T]) -> Option<usize> {
+    let length = sequence.len();
+    for match_length in (1..=MAX_MATCH_LENGTH.min(length.saturating_sub(1))).rev() {
+        let suffix = &sequence[length - match_length..];
+        for end in (match_length..length).rev() {
+            if &sequence[end - match_length..end] == suffix {
+                return Some(end);
+            }
+        }
+    }
+    None
+}
+
+// The move which does best against the given one. Lowest index wins ties, so that the meta layers are deterministic.
+fn beat(game_type: &GameType, game_move: usize) -> usize {
+    let ruleset = game_type.ruleset();
+    (0..ruleset.moves.len()).fold(0, |best, x| {
+        if ruleset.payoff(x, game_move) > ruleset.payoff(best, game_move) {
+            x
+        } else {
+            best
+        }
+    })
+}
+
+fn beat_times(game_type: &GameType, game_move: usize, times: usize) -> usize {
+    (0..times).fold(game_move, |x, _| beat(game_type, x))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    predictor: Predictor,
+    layer: usize,
+    is_computer_prediction: bool,
+    expected_move: Option<usize>,
+    game_move: usize,
+}
+
+impl Candidate {
+    fn new(game_type: &GameType, predictor: Predictor, layer: usize, prediction: Option<usize>, is_computer_prediction: bool, rng: &mut dyn RngCore) -> Self {
+        let shift = if is_computer_prediction { 1 } else { 0 };
+        let expected_move = prediction.map(|x| beat_times(game_type, x, 2 * layer + shift));
+        Self {
+            predictor,
+            layer,
+            is_computer_prediction,
+            expected_move,
+            game_move: match expected_move {
+                Some(x) => beat(game_type, x),
+                None => rng.gen_range(0..game_type.ruleset().moves.len()),
+            },
+        }
+    }
+
+    fn get_friendly_name(&self) -> String {
+        format!(
+            "{}, {} on {} move",
+            self.predictor.get_friendly_name(),
+            META_LAYERS[self.layer],
+            if self.is_computer_prediction { "computer" } else { "your" }
+        )
+    }
+}
+
+// Every predictor guesses the player's next move and the move the player expects from us. Each guess is answered on
+// several meta layers: the naive answer, the answer to the player's second guess and the answer to their third guess.
+// Every candidate is scored on past rounds, and the best one picks the move.
+#[derive(Default)]
+pub struct IocaineStrategy {
+    candidates: Vec<Candidate>,
+    scores: Vec<f64>,
+    hits: Vec<f64>,
+    scored_rounds: f64,
+    number_of_rounds: usize,
+    explanation: Option<Explanation>,
+}
+
+impl IocaineStrategy {
+    fn get_candidates(history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> Vec<Candidate> {
+        let number_of_moves = game_type.ruleset().moves.len();
+        let player_moves: Vec<usize> = history.iter().map(|x| x.player_move.0).collect();
+        let computer_moves: Vec<usize> = history.iter().map(|x| x.computer_move.0).collect();
+
+        let mut candidates = Vec::new();
+        for predictor in Predictor::ALL {
+            let player_prediction = predictor.predict(&player_moves, &computer_moves, number_of_moves, rng);
+            let computer_prediction = predictor.predict(&computer_moves, &player_moves, number_of_moves, rng);
+            for layer in 0..META_LAYERS.len() {
+                candidates.push(Candidate::new(game_type, predictor, layer, player_prediction, false, rng));
+                candidates.push(Candidate::new(game_type, predictor, layer, computer_prediction, true, rng));
+            }
+        }
+        candidates
+    }
+}
+
+impl Strategy for IocaineStrategy {
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        if let Some(round) = history.last() {
+            if history.len() == self.number_of_rounds + 1 && !self.candidates.is_empty() {
+                let ruleset = game_type.ruleset();
+                for ((score, hits), candidate) in self.scores.iter_mut().zip(self.hits.iter_mut()).zip(&self.candidates) {
+                    let payoff = ruleset.payoff(candidate.game_move, round.player_move.0);
+                    *score = *score * SCORE_DECAY + payoff as f64;
+                    *hits = *hits * SCORE_DECAY + if payoff > 0 { 1.0 } else { 0.0 };
+                }
+                self.scored_rounds = self.scored_rounds * SCORE_DECAY + 1.0;
+            }
+        }
+        self.number_of_rounds = history.len();
+
+        self.candidates = IocaineStrategy::get_candidates(history, game_type, rng);
+        self.scores.resize(self.candidates.len(), 0.0);
+        self.hits.resize(self.candidates.len(), 0.0);
+        let best = self.scores.iter().fold(f64::NEG_INFINITY, |x, y| x.max(*y));
+        let index = self.scores.iter().position(|x| *x == best).unwrap_or(0);
+
+        let candidate = &self.candidates[index];
+        self.explanation = Some(Explanation {
+            predictor: candidate.get_friendly_name(),
+            expected_move: candidate.expected_move.map(GameMove),
+            confidence: if self.scored_rounds > 0.0 {
+                self.hits[index] / self.scored_rounds
+            } else {
+                0.0
+            },
+        });
+        GameMove(candidate.game_move)
+    }
+
+    fn reset(&mut self) {
+        *self = IocaineStrategy::default();
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_result::GameResult;
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    fn play(game_type: &GameType, player_moves: impl Iterator<Item = usize>) -> (u32, u32) {
+        let mut strategy = IocaineStrategy::default();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut history = Vec::new();
+        let (mut wins, mut loses) = (0, 0);
+        for player_move in player_moves {
+            let computer_move = strategy.get_move(&history, game_type, &mut rng);
+            match computer_move.beats_other(game_type, &GameMove(player_move)) {
+                GameResult::Win => wins += 1,
+                GameResult::Lose => loses += 1,
+                GameResult::Draw => {}
+            }
+            history.push(Round {
+                player_move: GameMove(player_move),
+                computer_move,
+            });
+        }
+        (wins, loses)
+    }
+
+    #[test]
+    fn test_find_longest_match() {
+        assert_eq!(find_longest_match::<usize>(&[]), None);
+        assert_eq!(find_longest_match(&[1]), None);
+        assert_eq!(find_longest_match(&[1, 2, 3]), None);
+        assert_eq!(find_longest_match(&[1, 2, 3, 1]), Some(1));
+        assert_eq!(find_longest_match(&[1, 2, 1, 3, 1, 2, 1]), Some(3));
+    }
+
+    #[test]
+    fn test_beat() {
+        let normal = bundled_game_type("normal");
+        let id = |x: &str| normal.ruleset().find_move(x).unwrap();
+        assert_eq!(beat(&normal, id("Rock")), id("Paper"));
+        assert_eq!(beat_times(&normal, id("Rock"), 3), id("Rock"));
+    }
+
+    #[test]
+    fn test_exploits_repeated_move() {
+        let (wins, loses) = play(&bundled_game_type("normal"), std::iter::repeat_n(0, 100));
+        assert!(wins >= 90, "{} {}", wins, loses);
+    }
+
+    #[test]
+    fn test_explanation() {
+        let game_type = bundled_game_type("normal");
+        let mut strategy = IocaineStrategy::default();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut history = Vec::new();
+        for _ in 0..30 {
+            let computer_move = strategy.get_move(&history, &game_type, &mut rng);
+            history.push(Round {
+                player_move: GameMove(2),
+                computer_move,
+            });
+        }
+        strategy.get_move(&history, &game_type, &mut rng);
+        let explanation = strategy.get_explanation().unwrap();
+        assert_eq!(explanation.expected_move, Some(GameMove(2)));
+        assert!(explanation.confidence > 0.8, "{:?}", explanation);
+    }
+
+    #[test]
+    fn test_exploits_cycle() {
+        let (wins, loses) = play(&bundled_game_type("spock_lizard"), [0, 3, 1, 1, 4].into_iter().cycle().take(200));
+        assert!(wins >= 160, "{} {}", wins, loses);
+    }
+
+    #[test]
+    fn test_exploits_second_guessing_player() {
+        // The player answers the computer's last move, so the computer should answer that answer.
+        let game_type = bundled_game_type("rps_101");
+        let mut strategy = IocaineStrategy::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut history: Vec<Round> = Vec::new();
+        let mut wins = 0;
+        for _ in 0..200 {
+            let player_move = history.last().map(|x| beat(&game_type, x.computer_move.0)).unwrap_or(0);
+            let computer_move = strategy.get_move(&history, &game_type, &mut rng);
+            if computer_move.beats_other(&game_type, &GameMove(player_move)) == GameResult::Win {
+                wins += 1;
+            }
+            history.push(Round {
+                player_move: GameMove(player_move),
+                computer_move,
+            });
+        }
+        assert!(wins >= 160, "{}", wins);
+    }
+
+    #[test]
+    fn test_reset() {
+        let game_type = bundled_game_type("normal");
+        let mut strategy = IocaineStrategy::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        let history = [Round {
+            player_move: GameMove(0),
+            computer_move: GameMove(1),
+        }];
+        strategy.get_move(&history[..0], &game_type, &mut rng);
+        strategy.get_move(&history, &game_type, &mut rng);
+        assert!(strategy.scores.iter().any(|x| *x != 0.0));
+
+        // A forfeited round leaves the history as it was, the scores must stay.
+        let scores = strategy.scores.clone();
+        strategy.get_move(&history, &game_type, &mut rng);
+        assert_eq!(strategy.scores, scores);
+
+        strategy.reset();
+        assert!(strategy.scores.is_empty());
+        assert_eq!(strategy.number_of_rounds, 0);
+    }
+}
@@ -0,0 +1,891 @@
+use std::fmt::{Display, Formatter};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::chat::ChatContent;
+use crate::commitment::{verify, Commitment};
+use crate::game_move::GameMove;
+use crate::game_result::{GameResult, ResultCounts};
+use crate::game_type::GameType;
+use crate::match_format::MatchFormat;
+use crate::ruleset::{Ruleset, Rulesets};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7878;
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+// Dropped Wi-Fi does not close the connection, silence longer than this does.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+
+// Every message is one line of JSON. Both sides start with Hello, then the host proposes the ruleset and the guest
+// accepts it only when it has the very same ruleset. Each round both sides send Commit with the hash of their move and
+// Reveal only after the opponent's commit arrived, so nobody can wait for the other move. Spectator starts with Spectate
+// instead of Hello and gets only rounds which were already revealed. Guest that lost the connection sends Resume instead
+// of Hello, then both sides send again their messages of the last two rounds and duplicates are skipped. Dedicated server
+// sends OpponentForfeited to the player who stayed when the other one did not resume in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LanMessage {
+    Hello {
+        version: u32,
+        name: String,
+        profile: Uuid,
+    },
+    Ruleset {
+        id: String,
+        fingerprint: String,
+        match_format: MatchFormat,
+    },
+    RulesetAccepted,
+    RulesetRejected {
+        reason: String,
+    },
+    Commit {
+        round: u32,
+        hash: String,
+    },
+    Reveal {
+        round: u32,
+        game_move: String,
+        nonce: String,
+    },
+    Rematch,
+    Bye,
+    Ping,
+    Resume {
+        version: u32,
+        profile: Uuid,
+    },
+    Resumed,
+    OpponentForfeited,
+    Spectate {
+        version: u32,
+        name: String,
+    },
+    SpectatorWelcome {
+        version: u32,
+        id: String,
+        fingerprint: String,
+        match_format: MatchFormat,
+        players: [String; 2],
+        round: u32,
+        score: [usize; 3],
+    },
+    RoundPlayed {
+        round: u32,
+        moves: [String; 2],
+        score: [usize; 3],
+    },
+    MatchRestarted,
+    Chat {
+        content: ChatContent,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanError {
+    Io(String),
+    Closed,
+    InvalidMessage(String),
+    VersionMismatch(u32),
+    RulesetMismatch(String),
+    UnexpectedMessage(String),
+    InvalidReveal,
+    OpponentLeft,
+    OpponentForfeited,
+    MatchEnded,
+    TimedOut,
+}
+
+impl LanError {
+    // Lost connection can be resumed, other errors end the match.
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(self, LanError::Io(_) | LanError::Closed | LanError::TimedOut)
+    }
+}
+
+impl Display for LanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LanError::Io(x) => write!(f, "Network error: {}", x),
+            LanError::Closed => write!(f, "Connection was closed."),
+            LanError::InvalidMessage(x) => write!(f, "Invalid message: {}", x),
+            LanError::VersionMismatch(x) => {
+                write!(f, "Opponent uses protocol version {}, this game uses {}.", x, PROTOCOL_VERSION)
+            }
+            LanError::RulesetMismatch(x) => write!(f, "Rulesets do not match: {}", x),
+            LanError::UnexpectedMessage(x) => write!(f, "Unexpected message: {}", x),
+            LanError::InvalidReveal => write!(f, "Opponent revealed a move that does not match its commitment."),
+            LanError::OpponentLeft => write!(f, "Opponent left the game."),
+            LanError::OpponentForfeited => write!(f, "Opponent did not come back, match is forfeited."),
+            LanError::MatchEnded => write!(f, "Match ended."),
+            LanError::TimedOut => write!(f, "Connection timed out."),
+        }
+    }
+}
+
+impl From<std::io::Error> for LanError {
+    fn from(e: std::io::Error) -> Self {
+        LanError::Io(e.to_string())
+    }
+}
+
+// Hash of move names and all outcomes, so rulesets with the same id but different rules are not treated as equal.
+pub fn get_ruleset_fingerprint(ruleset: &Ruleset) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(ruleset.id.as_bytes());
+    for game_move in &ruleset.moves {
+        hasher.update(b"\n");
+        hasher.update(game_move.name.as_bytes());
+    }
+    hasher.update(b"\n");
+    for first in 0..ruleset.moves.len() {
+        for second in 0..ruleset.moves.len() {
+            hasher.update([ruleset.payoff(first, second) as u8]);
+        }
+    }
+    hasher.finalize().iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub fn default_lan_address() -> String {
+    format!("127.0.0.1:{}", DEFAULT_PORT)
+}
+
+pub fn listen(port: u16) -> Result<TcpListener, LanError> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+pub fn accept(listener: &TcpListener) -> Result<Option<LanConnection>, LanError> {
+    match listener.accept() {
+        Ok((stream, _)) => LanConnection::new(stream).map(Some),
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Non-blocking connection, so it can be polled every frame.
+#[derive(Debug)]
+pub struct LanConnection {
+    stream: TcpStream,
+    read_buffer: Vec<u8>,
+    write_buffer: Vec<u8>,
+    unread: Vec<LanMessage>,
+    is_closed: bool,
+    last_sent: Instant,
+    last_received: Instant,
+}
+
+impl LanConnection {
+    pub fn new(stream: TcpStream) -> Result<Self, LanError> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            read_buffer: Vec::new(),
+            write_buffer: Vec::new(),
+            unread: Vec::new(),
+            is_closed: false,
+            last_sent: Instant::now(),
+            last_received: Instant::now(),
+        })
+    }
+
+    // Address without port uses the default port.
+    pub fn connect(address: &str) -> Result<Self, LanError> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        let socket_address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| LanError::Io(format!("Unknown address {}", address)))?;
+        LanConnection::new(TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?)
+    }
+
+    pub fn send(&mut self, message: &LanMessage) -> Result<(), LanError> {
+        let line = serde_json::to_string(message).map_err(|e| LanError::InvalidMessage(e.to_string()))?;
+        self.write_buffer.extend_from_slice(line.as_bytes());
+        self.write_buffer.push(b'\n');
+        self.last_sent = Instant::now();
+        self.flush()
+    }
+
+    // Sends Ping when nothing was sent for a while and fails when nothing was received for too long.
+    pub fn keep_alive(&mut self) -> Result<(), LanError> {
+        if self.last_sent.elapsed() >= PING_INTERVAL {
+            self.send(&LanMessage::Ping)?;
+        }
+        if self.last_received.elapsed() >= CONNECTION_TIMEOUT {
+            return Err(LanError::TimedOut);
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), LanError> {
+        while !self.write_buffer.is_empty() {
+            match self.stream.write(&self.write_buffer) {
+                Ok(0) => return Err(LanError::Closed),
+                Ok(x) => {
+                    self.write_buffer.drain(..x);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    // Messages received before the connection closed are returned first, the next call fails with Closed.
+    pub fn receive(&mut self) -> Result<Vec<LanMessage>, LanError> {
+        let mut buffer = [0u8; 4096];
+        while !self.is_closed {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => self.is_closed = true,
+                Ok(x) => {
+                    self.read_buffer.extend_from_slice(&buffer[..x]);
+                    self.last_received = Instant::now();
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut messages = std::mem::take(&mut self.unread);
+        while let Some(index) = self.read_buffer.iter().position(|x| *x == b'\n') {
+            let line: Vec<u8> = self.read_buffer.drain(..=index).collect();
+            let message = serde_json::from_slice(&line).map_err(|e| LanError::InvalidMessage(e.to_string()))?;
+            messages.push(message);
+        }
+        if self.read_buffer.len() > MAX_MESSAGE_LENGTH {
+            return Err(LanError::InvalidMessage(String::from("Message is too long.")));
+        }
+        if self.is_closed && messages.is_empty() {
+            return Err(LanError::Closed);
+        }
+        Ok(messages)
+    }
+
+    // Messages are returned again by the next receive, so whoever reads the first message can hand the connection over.
+    pub fn unread(&mut self, messages: Vec<LanMessage>) {
+        self.unread = messages;
+    }
+}
+
+// Sends queued messages and handles received ones. Messages queued while handling, like rejection of the ruleset, are
+// sent even when handling fails.
+pub fn poll(connection: &mut LanConnection, session: &mut LanSession, rulesets: &Rulesets) -> Result<Vec<LanEvent>, LanError> {
+    for message in session.take_outgoing() {
+        connection.send(&message)?;
+    }
+    connection.keep_alive()?;
+    let mut events = Vec::new();
+    let mut result = Ok(());
+    for message in connection.receive()? {
+        match session.handle(message, rulesets) {
+            Ok(x) => events.extend(x),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    for message in session.take_outgoing() {
+        connection.send(&message)?;
+    }
+    result.map(|_| events)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanRole {
+    Host,
+    Guest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanEvent {
+    Connected,
+    OpponentCommitted,
+    RoundFinished(GameResult),
+    RematchRequested,
+    MatchRestarted,
+    Resumed,
+    Chat(ChatContent),
+}
+
+// One side of a LAN match, results are kept from this side's point of view. Messages to send are queued and taken
+// by the connection.
+#[derive(Debug)]
+pub struct LanSession {
+    pub role: LanRole,
+    pub name: String,
+    pub profile: Uuid,
+    pub match_id: Uuid,
+    pub opponent_name: Option<String>,
+    pub opponent_profile: Option<Uuid>,
+    pub game_type: GameType,
+    pub match_format: MatchFormat,
+    pub is_ready: bool,
+    pub round: u32,
+    pub commitment: Option<Commitment>,
+    pub opponent_hash: Option<String>,
+    pub last_round: Option<(GameMove, GameMove)>,
+    pub score: ResultCounts,
+    pub is_rematch_requested: bool,
+    pub is_rematch_offered: bool,
+    outgoing: Vec<LanMessage>,
+    // Own commits and reveals which may not have reached the opponent, sent again on resume.
+    round_messages: Vec<LanMessage>,
+}
+
+impl LanSession {
+    fn new(role: LanRole, name: String, profile: Uuid, game_type: GameType, match_format: MatchFormat) -> Self {
+        Self {
+            role,
+            outgoing: vec![LanMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: name.clone(),
+                profile,
+            }],
+            name,
+            profile,
+            match_id: Uuid::new_v4(),
+            opponent_name: None,
+            opponent_profile: None,
+            game_type,
+            match_format,
+            is_ready: false,
+            round: 0,
+            commitment: None,
+            opponent_hash: None,
+            last_round: None,
+            score: ResultCounts::default(),
+            is_rematch_requested: false,
+            is_rematch_offered: false,
+            round_messages: Vec::new(),
+        }
+    }
+
+    pub fn new_host(name: String, profile: Uuid, game_type: GameType, match_format: MatchFormat) -> Self {
+        LanSession::new(LanRole::Host, name, profile, game_type, match_format)
+    }
+
+    // Guest gets ruleset and match format from the host.
+    pub fn new_guest(name: String, profile: Uuid) -> Self {
+        LanSession::new(LanRole::Guest, name, profile, GameType::None, MatchFormat::default())
+    }
+
+    pub fn take_outgoing(&mut self) -> Vec<LanMessage> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    pub fn get_opponent_name(&self) -> &str {
+        self.opponent_name.as_deref().unwrap_or("Opponent")
+    }
+
+    pub fn get_winner(&self) -> Option<GameResult> {
+        self.match_format.get_winner(self.score.wins as u32, self.score.loses as u32)
+    }
+
+    // Returns false when the move cannot be locked in now.
+    pub fn commit(&mut self, game_move: GameMove, rng: &mut dyn RngCore) -> bool {
+        if !self.is_ready || self.commitment.is_some() || self.get_winner().is_some() {
+            return false;
+        }
+        let commitment = Commitment::new(&self.game_type, game_move, rng);
+        self.push_round_message(LanMessage::Commit {
+            round: self.round,
+            hash: commitment.hash.clone(),
+        });
+        self.commitment = Some(commitment);
+        self.reveal();
+        true
+    }
+
+    // Rematch can be asked for only when the match is decided, then no commit can be on the way.
+    pub fn request_rematch(&mut self) -> Option<LanEvent> {
+        if !self.is_ready || self.is_rematch_requested || self.get_winner().is_none() {
+            return None;
+        }
+        self.is_rematch_requested = true;
+        self.outgoing.push(LanMessage::Rematch);
+        self.restart_if_agreed()
+    }
+
+    // Returns false before the opponent joined.
+    pub fn chat(&mut self, content: ChatContent) -> bool {
+        if self.opponent_name.is_none() {
+            return false;
+        }
+        self.outgoing.push(LanMessage::Chat { content });
+        true
+    }
+
+    pub fn leave(&mut self) {
+        self.outgoing.push(LanMessage::Bye);
+    }
+
+    // Guest asks the host to resume the match over a new connection.
+    pub fn rejoin(&mut self) {
+        self.outgoing = vec![LanMessage::Resume {
+            version: PROTOCOL_VERSION,
+            profile: self.profile,
+        }];
+    }
+
+    // Rematch has to be asked for again after the connection was lost.
+    fn resume(&mut self) -> Option<LanEvent> {
+        self.outgoing.extend(self.round_messages.iter().cloned());
+        self.is_rematch_requested = false;
+        self.is_rematch_offered = false;
+        Some(LanEvent::Resumed)
+    }
+
+    fn push_round_message(&mut self, message: LanMessage) {
+        self.round_messages.push(message.clone());
+        self.outgoing.push(message);
+    }
+
+    fn reveal(&mut self) {
+        if let (Some(commitment), Some(_)) = (&self.commitment, &self.opponent_hash) {
+            let message = LanMessage::Reveal {
+                round: self.round,
+                game_move: commitment.game_move.get_name(&self.game_type).to_string(),
+                nonce: commitment.nonce.clone(),
+            };
+            self.push_round_message(message);
+        }
+    }
+
+    fn restart_if_agreed(&mut self) -> Option<LanEvent> {
+        if !(self.is_rematch_requested && self.is_rematch_offered) {
+            return None;
+        }
+        // Rounds keep counting, so messages of the previous match are skipped as old ones.
+        self.match_id = Uuid::new_v4();
+        self.score = ResultCounts::default();
+        self.round_messages.clear();
+        self.last_round = None;
+        self.commitment = None;
+        self.opponent_hash = None;
+        self.is_rematch_requested = false;
+        self.is_rematch_offered = false;
+        Some(LanEvent::MatchRestarted)
+    }
+
+    pub fn handle(&mut self, message: LanMessage, rulesets: &Rulesets) -> Result<Option<LanEvent>, LanError> {
+        match message {
+            LanMessage::Hello { version, name, profile } if self.opponent_name.is_none() => {
+                if version != PROTOCOL_VERSION {
+                    return Err(LanError::VersionMismatch(version));
+                }
+                self.opponent_name = Some(name);
+                self.opponent_profile = Some(profile);
+                if self.role == LanRole::Host {
+                    let ruleset = self.game_type.ruleset();
+                    self.outgoing.push(LanMessage::Ruleset {
+                        id: ruleset.id.clone(),
+                        fingerprint: get_ruleset_fingerprint(ruleset),
+                        match_format: self.match_format,
+                    });
+                }
+                Ok(None)
+            }
+            LanMessage::Ruleset { id, fingerprint, match_format } if self.role == LanRole::Guest && self.opponent_name.is_some() && !self.is_ready => {
+                let ruleset = rulesets
+                    .0
+                    .iter()
+                    .find(|x| x.id == id && x.validate().is_ok() && get_ruleset_fingerprint(x) == fingerprint);
+                match ruleset {
+                    Some(x) => {
+                        self.game_type = GameType::Ruleset(x.clone());
+                        self.match_format = match_format;
+                        self.is_ready = true;
+                        self.outgoing.push(LanMessage::RulesetAccepted);
+                        Ok(Some(LanEvent::Connected))
+                    }
+                    None => {
+                        let reason = format!("ruleset {} is missing or different on {}", id, self.name);
+                        self.outgoing.push(LanMessage::RulesetRejected { reason: reason.clone() });
+                        Err(LanError::RulesetMismatch(reason))
+                    }
+                }
+            }
+            LanMessage::RulesetAccepted if self.role == LanRole::Host && self.opponent_name.is_some() && !self.is_ready => {
+                self.is_ready = true;
+                Ok(Some(LanEvent::Connected))
+            }
+            LanMessage::RulesetRejected { reason } if self.role == LanRole::Host => Err(LanError::RulesetMismatch(reason)),
+            LanMessage::Commit { round, hash } if self.is_ready && (round < self.round || self.opponent_hash.as_ref() == Some(&hash)) => Ok(None),
+            LanMessage::Reveal { round, .. } if self.is_ready && round < self.round => Ok(None),
+            LanMessage::Commit { round, hash } if self.is_ready && round == self.round && self.opponent_hash.is_none() => {
+                self.opponent_hash = Some(hash);
+                self.reveal();
+                Ok(Some(LanEvent::OpponentCommitted))
+            }
+            LanMessage::Reveal { round, game_move, nonce } if self.is_ready && round == self.round && self.commitment.is_some() => {
+                let hash = self.opponent_hash.take().ok_or(LanError::InvalidReveal)?;
+                if !verify(&game_move, &nonce, &hash) {
+                    return Err(LanError::InvalidReveal);
+                }
+                let opponent_move = GameMove(self.game_type.ruleset().find_move(&game_move).ok_or(LanError::InvalidReveal)?);
+                let own_move = self.commitment.take().unwrap().game_move;
+                let result = own_move.beats_other(&self.game_type, &opponent_move);
+                self.score.add(result);
+                self.last_round = Some((own_move, opponent_move));
+                self.round += 1;
+                let round = self.round;
+                self.round_messages.retain(|x| match x {
+                    LanMessage::Commit { round: x, .. } | LanMessage::Reveal { round: x, .. } => x + 1 >= round,
+                    _ => false,
+                });
+                Ok(Some(LanEvent::RoundFinished(result)))
+            }
+            LanMessage::Rematch if self.is_ready && !self.is_rematch_offered && self.get_winner().is_some() => {
+                self.is_rematch_offered = true;
+                Ok(Some(self.restart_if_agreed().unwrap_or(LanEvent::RematchRequested)))
+            }
+            LanMessage::Resume { version, profile } if self.role == LanRole::Host && self.is_ready && self.opponent_profile == Some(profile) => {
+                if version != PROTOCOL_VERSION {
+                    return Err(LanError::VersionMismatch(version));
+                }
+                self.outgoing.push(LanMessage::Resumed);
+                Ok(self.resume())
+            }
+            LanMessage::Resumed if self.role == LanRole::Guest && self.is_ready => Ok(self.resume()),
+            LanMessage::OpponentForfeited if self.role == LanRole::Guest && self.is_ready => Err(LanError::OpponentForfeited),
+            LanMessage::Chat { content } if self.opponent_name.is_some() => Ok(content.sanitize().map(LanEvent::Chat)),
+            LanMessage::Ping => Ok(None),
+            LanMessage::Bye => Err(LanError::OpponentLeft),
+            x => Err(LanError::UnexpectedMessage(format!("{:?}", x))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::rngs::mock::StepRng;
+
+    use crate::test_support::{bundled_game_type, bundled_rulesets};
+
+    use super::*;
+
+    fn new_sessions(match_format: MatchFormat) -> (LanSession, LanSession) {
+        let host = LanSession::new_host(String::from("Host"), Uuid::new_v4(), bundled_game_type("normal"), match_format);
+        let guest = LanSession::new_guest(String::from("Guest"), Uuid::new_v4());
+        (host, guest)
+    }
+
+    // Delivers queued messages both ways until nothing is left, returns events of host and guest.
+    fn exchange(host: &mut LanSession, guest: &mut LanSession) -> Result<(Vec<LanEvent>, Vec<LanEvent>), LanError> {
+        let rulesets = bundled_rulesets();
+        let mut events = (Vec::new(), Vec::new());
+        loop {
+            let to_guest = host.take_outgoing();
+            let to_host = guest.take_outgoing();
+            if to_guest.is_empty() && to_host.is_empty() {
+                return Ok(events);
+            }
+            for message in to_guest {
+                events.1.extend(guest.handle(message, rulesets)?);
+            }
+            for message in to_host {
+                events.0.extend(host.handle(message, rulesets)?);
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_line() {
+        let message = LanMessage::Commit {
+            round: 2,
+            hash: String::from("abc"),
+        };
+        let line = serde_json::to_string(&message).unwrap();
+        assert_eq!(line, r#"{"type":"Commit","round":2,"hash":"abc"}"#);
+        assert_eq!(serde_json::from_str::<LanMessage>(&line).unwrap(), message);
+    }
+
+    #[test]
+    fn test_ruleset_fingerprint() {
+        let rulesets = bundled_rulesets();
+        let normal = rulesets.0.iter().find(|x| x.id == "normal").unwrap();
+        let spock_lizard = rulesets.0.iter().find(|x| x.id == "spock_lizard").unwrap();
+        assert_eq!(get_ruleset_fingerprint(normal), get_ruleset_fingerprint(&normal.clone()));
+        assert_ne!(get_ruleset_fingerprint(normal), get_ruleset_fingerprint(spock_lizard));
+    }
+
+    #[test]
+    fn test_match() {
+        let (mut host, mut guest) = new_sessions(MatchFormat::FirstTo(2));
+        assert!(!host.commit(GameMove(0), &mut StepRng::new(1, 1)));
+        assert_eq!(exchange(&mut host, &mut guest).unwrap(), (vec![LanEvent::Connected], vec![LanEvent::Connected]));
+        assert_eq!(guest.game_type, host.game_type);
+        assert_eq!(guest.match_format, MatchFormat::FirstTo(2));
+        assert_eq!(guest.get_opponent_name(), "Host");
+
+        // Paper beats Rock, the guest cannot learn the host's move before committing its own.
+        assert!(host.commit(GameMove(1), &mut StepRng::new(1, 1)));
+        assert!(!host.commit(GameMove(2), &mut StepRng::new(1, 1)));
+        assert_eq!(exchange(&mut host, &mut guest).unwrap(), (vec![], vec![LanEvent::OpponentCommitted]));
+        assert!(guest.commit(GameMove(0), &mut StepRng::new(2, 1)));
+        assert_eq!(
+            exchange(&mut host, &mut guest).unwrap(),
+            (
+                vec![LanEvent::OpponentCommitted, LanEvent::RoundFinished(GameResult::Win)],
+                vec![LanEvent::RoundFinished(GameResult::Lose)]
+            )
+        );
+        assert_eq!(host.last_round, Some((GameMove(1), GameMove(0))));
+        assert_eq!(guest.last_round, Some((GameMove(0), GameMove(1))));
+
+        for (host_move, guest_move) in [(2, 2), (2, 1)] {
+            assert!(host.commit(GameMove(host_move), &mut StepRng::new(3, 1)));
+            assert!(guest.commit(GameMove(guest_move), &mut StepRng::new(4, 1)));
+            exchange(&mut host, &mut guest).unwrap();
+        }
+        assert_eq!(host.score, ResultCounts { wins: 2, loses: 0, draws: 1 });
+        assert_eq!(guest.get_winner(), Some(GameResult::Lose));
+        assert!(!host.commit(GameMove(0), &mut StepRng::new(1, 1)));
+
+        assert!(guest.chat(ChatContent::Text(String::from(" gg\n"))));
+        assert_eq!(
+            exchange(&mut host, &mut guest).unwrap(),
+            (vec![LanEvent::Chat(ChatContent::Text(String::from("gg")))], vec![])
+        );
+
+        assert_eq!(host.request_rematch(), None);
+        assert_eq!(exchange(&mut host, &mut guest).unwrap(), (vec![], vec![LanEvent::RematchRequested]));
+        assert_eq!(guest.request_rematch(), Some(LanEvent::MatchRestarted));
+        assert_eq!(exchange(&mut host, &mut guest).unwrap(), (vec![LanEvent::MatchRestarted], vec![]));
+        assert_eq!(host.score, ResultCounts::default());
+        assert_eq!(guest.get_winner(), None);
+        assert_eq!(host.round, guest.round);
+    }
+
+    #[test]
+    fn test_rematch_with_commit_in_flight() {
+        let (mut host, mut guest) = new_sessions(MatchFormat::FirstTo(1));
+        exchange(&mut host, &mut guest).unwrap();
+        assert!(host.commit(GameMove(0), &mut StepRng::new(1, 1)));
+        assert!(guest.commit(GameMove(0), &mut StepRng::new(2, 1)));
+        exchange(&mut host, &mut guest).unwrap();
+        assert_eq!(host.request_rematch(), None);
+        assert!(!host.is_rematch_requested);
+        assert!(host.take_outgoing().is_empty());
+        assert!(guest.handle(LanMessage::Rematch, bundled_rulesets()).is_err());
+
+        // Guest's commit and reveal of the deciding round arrive once more after the rematch was agreed.
+        assert!(host.commit(GameMove(1), &mut StepRng::new(3, 1)));
+        assert!(guest.commit(GameMove(0), &mut StepRng::new(4, 1)));
+        exchange(&mut host, &mut guest).unwrap();
+        let in_flight = guest.round_messages.clone();
+        assert_eq!(host.get_winner(), Some(GameResult::Win));
+        assert_eq!(guest.get_winner(), Some(GameResult::Lose));
+        assert_eq!(guest.request_rematch(), None);
+        assert_eq!(host.request_rematch(), None);
+        assert_eq!(
+            exchange(&mut host, &mut guest).unwrap(),
+            (vec![LanEvent::MatchRestarted], vec![LanEvent::MatchRestarted])
+        );
+        assert_eq!(in_flight.len(), 2);
+        for message in in_flight {
+            assert_eq!(host.handle(message, bundled_rulesets()), Ok(None));
+        }
+
+        assert!(host.commit(GameMove(2), &mut StepRng::new(5, 1)));
+        assert!(guest.commit(GameMove(0), &mut StepRng::new(6, 1)));
+        exchange(&mut host, &mut guest).unwrap();
+        assert_eq!(host.score, ResultCounts { wins: 0, loses: 1, draws: 0 });
+        assert_eq!(guest.get_winner(), Some(GameResult::Win));
+    }
+
+    #[test]
+    fn test_invalid_reveal() {
+        let (mut host, mut guest) = new_sessions(MatchFormat::Endless);
+        exchange(&mut host, &mut guest).unwrap();
+        host.commit(GameMove(0), &mut StepRng::new(1, 1));
+        guest.commit(GameMove(0), &mut StepRng::new(1, 1));
+        let rulesets = bundled_rulesets();
+        for message in guest.take_outgoing() {
+            host.handle(message, rulesets).unwrap();
+        }
+        for message in host.take_outgoing() {
+            let message = match message {
+                LanMessage::Reveal { round, nonce, .. } => LanMessage::Reveal {
+                    round,
+                    game_move: String::from("Paper"),
+                    nonce,
+                },
+                x => x,
+            };
+            if let Err(e) = guest.handle(message, rulesets) {
+                assert_eq!(e, LanError::InvalidReveal);
+                return;
+            }
+        }
+        panic!("Changed move was accepted.");
+    }
+
+    // Messages in flight are lost when the connection drops, resume sends them again.
+    #[test]
+    fn test_resume() {
+        let rulesets = bundled_rulesets();
+        let (mut host, mut guest) = new_sessions(MatchFormat::BestOf(5));
+        exchange(&mut host, &mut guest).unwrap();
+        let guest_profile = host.opponent_profile.unwrap();
+
+        // Both commits are lost.
+        assert!(host.commit(GameMove(0), &mut StepRng::new(1, 1)));
+        assert!(guest.commit(GameMove(2), &mut StepRng::new(2, 1)));
+        host.take_outgoing();
+        guest.take_outgoing();
+        guest.rejoin();
+        let (host_events, guest_events) = exchange(&mut host, &mut guest).unwrap();
+        assert_eq!(
+            host_events,
+            vec![LanEvent::Resumed, LanEvent::OpponentCommitted, LanEvent::RoundFinished(GameResult::Win)]
+        );
+        assert_eq!(
+            guest_events,
+            vec![LanEvent::Resumed, LanEvent::OpponentCommitted, LanEvent::RoundFinished(GameResult::Lose)]
+        );
+
+        // Host got the guest's reveal, but its own reveal is lost.
+        assert!(host.commit(GameMove(1), &mut StepRng::new(3, 1)));
+        assert!(guest.commit(GameMove(1), &mut StepRng::new(4, 1)));
+        let to_guest = host.take_outgoing();
+        for message in guest.take_outgoing() {
+            host.handle(message, rulesets).unwrap();
+        }
+        for message in to_guest {
+            guest.handle(message, rulesets).unwrap();
+        }
+        for message in guest.take_outgoing() {
+            host.handle(message, rulesets).unwrap();
+        }
+        host.take_outgoing();
+        assert_eq!((host.round, guest.round), (2, 1));
+
+        guest.rejoin();
+        let (host_events, guest_events) = exchange(&mut host, &mut guest).unwrap();
+        assert_eq!(host_events, vec![LanEvent::Resumed]);
+        assert_eq!(guest_events, vec![LanEvent::Resumed, LanEvent::RoundFinished(GameResult::Draw)]);
+        assert_eq!((host.round, guest.round), (2, 2));
+        assert_eq!((host.score.wins, host.score.draws), (1, 1));
+        assert_eq!((guest.score.loses, guest.score.draws), (1, 1));
+
+        // Only the same profile can resume.
+        let resume = LanMessage::Resume {
+            version: PROTOCOL_VERSION,
+            profile: Uuid::new_v4(),
+        };
+        assert!(matches!(host.handle(resume, rulesets), Err(LanError::UnexpectedMessage(_))));
+        let resume = LanMessage::Resume {
+            version: PROTOCOL_VERSION,
+            profile: guest_profile,
+        };
+        assert_eq!(host.handle(resume, rulesets), Ok(Some(LanEvent::Resumed)));
+    }
+
+    #[test]
+    fn test_handshake_errors() {
+        let (mut host, mut guest) = new_sessions(MatchFormat::Endless);
+        guest.take_outgoing();
+        let rulesets = bundled_rulesets();
+        assert_eq!(
+            host.handle(
+                LanMessage::Hello {
+                    version: PROTOCOL_VERSION + 1,
+                    name: String::from("Guest"),
+                    profile: Uuid::new_v4(),
+                },
+                rulesets
+            ),
+            Err(LanError::VersionMismatch(PROTOCOL_VERSION + 1))
+        );
+
+        for message in host.take_outgoing() {
+            guest.handle(message, rulesets).unwrap();
+        }
+        let result = guest.handle(
+            LanMessage::Ruleset {
+                id: String::from("normal"),
+                fingerprint: String::from("other"),
+                match_format: MatchFormat::Endless,
+            },
+            rulesets,
+        );
+        assert!(matches!(result, Err(LanError::RulesetMismatch(_))));
+        assert!(matches!(guest.take_outgoing()[..], [LanMessage::RulesetRejected { .. }]));
+
+        assert!(matches!(
+            guest.handle(LanMessage::Commit { round: 0, hash: String::new() }, rulesets),
+            Err(LanError::UnexpectedMessage(_))
+        ));
+        assert_eq!(guest.handle(LanMessage::Bye, rulesets), Err(LanError::OpponentLeft));
+    }
+
+    fn pump(session: &mut LanSession, connection: &mut LanConnection, events: &mut Vec<LanEvent>) {
+        events.extend(poll(connection, session, bundled_rulesets()).unwrap());
+    }
+
+    #[test]
+    fn test_localhost() {
+        let listener = listen(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut guest_connection = LanConnection::connect(&format!("127.0.0.1:{}", port)).unwrap();
+        let mut host_connection = None;
+        let (mut host, mut guest) = new_sessions(MatchFormat::BestOf(1));
+        let mut host_events = Vec::new();
+        let mut guest_events = Vec::new();
+        let mut is_committed = false;
+        for _ in 0..500 {
+            if host_connection.is_none() {
+                host_connection = accept(&listener).unwrap();
+            }
+            if let Some(x) = host_connection.as_mut() {
+                pump(&mut host, x, &mut host_events);
+            }
+            pump(&mut guest, &mut guest_connection, &mut guest_events);
+            if host.is_ready && guest.is_ready && !is_committed {
+                is_committed = host.commit(GameMove(2), &mut StepRng::new(5, 1)) && guest.commit(GameMove(1), &mut StepRng::new(6, 1));
+            }
+            if guest_events.contains(&LanEvent::RoundFinished(GameResult::Lose)) && host_events.contains(&LanEvent::RoundFinished(GameResult::Win)) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(host.get_winner(), Some(GameResult::Win));
+        assert_eq!(guest.get_winner(), Some(GameResult::Lose));
+
+        guest.leave();
+        pump(&mut guest, &mut guest_connection, &mut guest_events);
+        drop(guest_connection);
+        let host_connection = host_connection.as_mut().unwrap();
+        let mut result = Ok(Vec::new());
+        for _ in 0..500 {
+            result = poll(host_connection, &mut host, bundled_rulesets());
+            if result.is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(result, Err(LanError::OpponentLeft));
+    }
+}
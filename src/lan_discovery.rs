@@ -0,0 +1,233 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::lan::{LanError, PROTOCOL_VERSION};
+use crate::match_format::MatchFormat;
+
+pub const DISCOVERY_PORT: u16 = 7879;
+pub const ANNOUNCE_INTERVAL: f32 = 1.0;
+// Lobby which was not announced for a few intervals is gone, hosts don't say goodbye.
+const LOBBY_EXPIRY: f64 = 4.0;
+const MAX_ANNOUNCEMENT_LENGTH: usize = 2048;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyAnnouncement {
+    pub version: u32,
+    pub host_name: String,
+    pub profile_name: String,
+    pub ruleset_id: String,
+    pub ruleset_name: String,
+    pub match_format: MatchFormat,
+    pub port: u16,
+    // Lobby stays announced while the match is played, so it can be watched.
+    #[serde(default)]
+    pub is_playing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lobby {
+    pub address: SocketAddr,
+    pub announcement: LobbyAnnouncement,
+    pub last_seen: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Lobbies(pub Vec<Lobby>);
+
+impl Lobbies {
+    // Lobby is identified by address, so a host that changes ruleset replaces its old entry. A host on this machine is
+    // heard both on its network address and on loopback, both copies are one lobby shown with the network address.
+    pub fn update(&mut self, ip: IpAddr, announcement: LobbyAnnouncement, now: f64) {
+        let address = SocketAddr::new(ip, announcement.port);
+        let is_same = |x: &Lobby| {
+            x.address == address
+                || (x.address.port() == address.port()
+                    && x.announcement.host_name == announcement.host_name
+                    && (x.address.ip().is_loopback() || ip.is_loopback()))
+        };
+        match self.0.iter_mut().find(|x| is_same(x)) {
+            Some(x) => {
+                if !ip.is_loopback() {
+                    x.address = address;
+                }
+                x.announcement = announcement;
+                x.last_seen = now;
+            }
+            None => self.0.push(Lobby {
+                address,
+                announcement,
+                last_seen: now,
+            }),
+        }
+    }
+
+    pub fn remove_expired(&mut self, now: f64) {
+        self.0.retain(|x| now - x.last_seen <= LOBBY_EXPIRY);
+    }
+}
+
+pub fn get_host_name() -> String {
+    fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+// Announcement goes to broadcast address and to loopback, so instances on the same machine see it too.
+#[derive(Debug)]
+pub struct LobbyAnnouncer {
+    socket: UdpSocket,
+    message: Vec<u8>,
+    port: u16,
+}
+
+impl LobbyAnnouncer {
+    pub fn new(announcement: &LobbyAnnouncement, port: u16) -> Result<Self, LanError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            message: serde_json::to_vec(announcement).map_err(|e| LanError::InvalidMessage(e.to_string()))?,
+            port,
+        })
+    }
+
+    pub fn announce(&self) {
+        for ip in [Ipv4Addr::BROADCAST, Ipv4Addr::LOCALHOST] {
+            if let Err(e) = self.socket.send_to(&self.message, (ip, self.port)) {
+                debug!("Unable to announce lobby to {}: {}", ip, e);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LobbyBrowser {
+    socket: UdpSocket,
+}
+
+impl LobbyBrowser {
+    // Only one instance on a machine can browse at a time, binding fails while the port is taken.
+    pub fn new(port: u16) -> Result<Self, LanError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket })
+    }
+
+    // Announcements of other protocol versions are skipped, they could not be joined anyway.
+    pub fn receive(&self) -> Vec<(IpAddr, LobbyAnnouncement)> {
+        let mut buffer = [0u8; MAX_ANNOUNCEMENT_LENGTH];
+        let mut announcements = Vec::new();
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, address)) => match serde_json::from_slice::<LobbyAnnouncement>(&buffer[..length]) {
+                    Ok(x) if x.version == PROTOCOL_VERSION => announcements.push((address.ip(), x)),
+                    Ok(_) => {}
+                    Err(e) => debug!("Invalid lobby announcement from {}: {}", address, e),
+                },
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    if e.kind() != ErrorKind::WouldBlock {
+                        debug!("Unable to receive lobby announcements: {}", e);
+                    }
+                    return announcements;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn announcement(ruleset_id: &str) -> LobbyAnnouncement {
+        LobbyAnnouncement {
+            version: PROTOCOL_VERSION,
+            host_name: String::from("desk"),
+            profile_name: String::from("Alice"),
+            ruleset_id: ruleset_id.to_string(),
+            ruleset_name: String::from("Normal"),
+            match_format: MatchFormat::BestOf(3),
+            port: 7878,
+            is_playing: false,
+        }
+    }
+
+    #[test]
+    fn test_lobbies() {
+        let mut lobbies = Lobbies::default();
+        let first = IpAddr::from([192, 168, 1, 2]);
+        let second = IpAddr::from([192, 168, 1, 3]);
+        lobbies.update(first, announcement("normal"), 0.0);
+        lobbies.update(second, announcement("normal"), 1.0);
+        lobbies.update(first, announcement("normal"), 3.0);
+        lobbies.update(first, announcement("rps_7"), 4.0);
+        assert_eq!(lobbies.0.len(), 2);
+        assert_eq!(lobbies.0[0].address, SocketAddr::new(first, 7878));
+
+        lobbies.remove_expired(5.0);
+        assert_eq!(lobbies.0.len(), 2);
+        lobbies.remove_expired(5.5);
+        assert_eq!(lobbies.0.len(), 1);
+        assert_eq!(lobbies.0[0].announcement.ruleset_id, "rps_7");
+        lobbies.remove_expired(9.0);
+        assert!(lobbies.0.is_empty());
+    }
+
+    #[test]
+    fn test_lobby_heard_on_loopback() {
+        let mut lobbies = Lobbies::default();
+        let loopback = IpAddr::from(Ipv4Addr::LOCALHOST);
+        let network = IpAddr::from([192, 168, 1, 2]);
+        lobbies.update(loopback, announcement("normal"), 0.0);
+        lobbies.update(network, announcement("normal"), 0.1);
+        lobbies.update(loopback, announcement("normal"), 1.0);
+        assert_eq!(lobbies.0.len(), 1);
+        assert_eq!(lobbies.0[0].address, SocketAddr::new(network, 7878));
+        assert_eq!(lobbies.0[0].last_seen, 1.0);
+
+        let other_host = LobbyAnnouncement {
+            host_name: String::from("laptop"),
+            ..announcement("normal")
+        };
+        lobbies.update(loopback, other_host, 2.0);
+        assert_eq!(lobbies.0.len(), 2);
+    }
+
+    #[test]
+    fn test_announce_on_localhost() {
+        let browser = LobbyBrowser::new(0).unwrap();
+        let port = browser.socket.local_addr().unwrap().port();
+        let announcer = LobbyAnnouncer::new(&announcement("normal"), port).unwrap();
+        let other_version = LobbyAnnouncement {
+            version: PROTOCOL_VERSION + 1,
+            ..announcement("normal")
+        };
+        LobbyAnnouncer::new(&other_version, port).unwrap().announce();
+        announcer.announce();
+
+        let mut received = Vec::new();
+        for _ in 0..500 {
+            received.extend(browser.receive());
+            if !received.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert!(!received.is_empty());
+        assert!(received.iter().all(|(_, x)| *x == announcement("normal")));
+    }
+}
@@ -0,0 +1,1534 @@
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use rand::rngs::OsRng;
+
+use crate::button::{spawn_button, spawn_buttons, Actions, HotKey};
+use crate::chat::{ChatContent, ChatHistory, Emote, MAX_CHAT_LENGTH};
+use crate::common::*;
+use crate::game::{get_game_move_name_text, spawn_game_move_buttons, switch_game_move, OnGameMoveName};
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+use crate::history::{get_timestamp, MatchOpponent, MatchRecord, MatchResults, PlayerOpponent};
+use crate::lan::{accept, listen, poll, LanConnection, LanError, LanEvent, LanMessage, LanRole, LanSession, DEFAULT_PORT, PROTOCOL_VERSION, RECONNECT_GRACE};
+use crate::lan_discovery::{get_host_name, Lobbies, LobbyAnnouncement, LobbyAnnouncer, LobbyBrowser, ANNOUNCE_INTERVAL, DISCOVERY_PORT};
+use crate::match_format::MatchFormat;
+use crate::menu::{on_key_press_event_trigger, OnKeyPressEvent};
+use crate::ruleset::Rulesets;
+use crate::spectator::{get_round_played, get_welcome, poll_spectator, SpectatorEvent, SpectatorSession, Spectators};
+
+const MAX_ADDRESS_LENGTH: usize = 40;
+const CHAT_OVERLAY_LINES: usize = 5;
+const EMOTE_KEYS: [KeyCode; 4] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+const LETTER_KEYS: [KeyCode; 26] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+];
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum LanState {
+    #[default]
+    NotInit,
+    Setup,
+    Connecting,
+    PlayerMove,
+    WaitingForOpponent,
+    RoundFinish,
+    Reconnecting,
+    Spectating,
+    Disconnected,
+}
+
+#[derive(Component)]
+pub struct OnLanScreen;
+
+#[derive(Component)]
+pub struct OnLanRulesetText;
+
+#[derive(Component)]
+pub struct OnLanAddressText;
+
+#[derive(Component)]
+pub struct OnLanStatusText;
+
+#[derive(Component)]
+pub struct OnLanLobbiesText;
+
+#[derive(Component)]
+pub struct OnLanSpectatorText(usize);
+
+#[derive(Component)]
+pub struct OnLanReconnectText;
+
+#[derive(Component)]
+pub struct OnLanChatOverlay;
+
+#[derive(Component)]
+pub struct OnLanChatText;
+
+#[derive(Component)]
+pub struct OnLanChatButtons;
+
+// Lines of lobbies shown as buttons, they are spawned again when a line changes.
+#[derive(Component, Default)]
+pub struct OnLanLobbyList(Vec<String>);
+
+#[derive(Component)]
+pub struct LobbyButton(usize);
+
+// Listener and announcer are kept while hosting, so spectators can join the match. Session (or spectator session when
+// watching) lives as long as the connection, or until the grace period ends when the connection was lost. Lobbies are
+// browsed on the setup screen.
+#[derive(Resource, Debug, Default)]
+pub struct LanLink {
+    pub listener: Option<TcpListener>,
+    pub connection: Option<LanConnection>,
+    pub session: Option<LanSession>,
+    pub address: String,
+    pub ruleset_index: usize,
+    pub error: Option<String>,
+    pub announcer: Option<LobbyAnnouncer>,
+    pub announcement: Option<LobbyAnnouncement>,
+    pub spectators: Spectators,
+    pub spectator: Option<SpectatorSession>,
+    pub browser: Option<LobbyBrowser>,
+    pub lobbies: Lobbies,
+    pub selected_lobby: Option<usize>,
+    pub discovery_timer: Timer,
+    pub grace_timer: Option<Timer>,
+    pub retry_timer: Timer,
+    // Connecting blocks, so guest joins and reconnects on another thread.
+    pub connecting: Option<JoinHandle<Result<LanConnection, LanError>>>,
+    pub chat: ChatHistory,
+    // Text being typed, other hotkeys are off until it is sent or cancelled.
+    pub chat_input: Option<String>,
+}
+
+impl LanLink {
+    fn session(&self) -> &LanSession {
+        self.session.as_ref().expect("LAN session is not set.")
+    }
+}
+
+pub struct LanGamePlugin;
+
+impl Plugin for LanGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<LanState>()
+            .init_resource::<LanLink>()
+            .add_systems(OnEnter(AppState::Lan), init_lan)
+            .add_systems(OnExit(AppState::Lan), leave_lan)
+            .add_systems(Update, (update_lan_link, update_lobby_discovery).run_if(in_state(AppState::Lan)))
+            .add_systems(OnEnter(AppState::Lan), setup_chat_overlay)
+            .add_systems(
+                Update,
+                ((on_key_press_event_trigger, update_lan_chat).chain(), update_chat_overlay).run_if(in_state(AppState::Lan)),
+            )
+            .add_systems(OnExit(AppState::Lan), despawn_screen::<OnLanChatOverlay>)
+            .add_systems(OnEnter(LanState::Setup), setup_setup_screen)
+            .add_systems(Update, (select_lobby, setup_action).chain().run_if(in_state(LanState::Setup)))
+            .add_systems(OnEnter(LanState::Connecting), setup_connecting_screen)
+            .add_systems(Update, update_join.run_if(in_state(LanState::Connecting)))
+            .add_systems(OnEnter(LanState::PlayerMove), setup_move_screen)
+            .add_systems(
+                Update,
+                (switch_game_move, confirm_lan_move).run_if(in_state(LanState::PlayerMove).and_then(is_chat_closed)),
+            )
+            .add_systems(OnEnter(LanState::WaitingForOpponent), setup_waiting_screen)
+            .add_systems(OnEnter(LanState::RoundFinish), setup_result_screen)
+            .add_systems(Update, confirm_result_action.run_if(in_state(LanState::RoundFinish).and_then(is_chat_closed)))
+            .add_systems(OnEnter(LanState::Reconnecting), setup_reconnecting_screen)
+            .add_systems(Update, update_reconnect.run_if(in_state(LanState::Reconnecting)))
+            .add_systems(OnEnter(LanState::Spectating), setup_spectator_screen)
+            .add_systems(Update, update_spectator_screen.run_if(in_state(LanState::Spectating)))
+            .add_systems(OnEnter(LanState::Disconnected), setup_disconnected_screen)
+            .add_systems(
+                Update,
+                back_action.run_if(
+                    in_state(LanState::Connecting)
+                        .or_else(in_state(LanState::Spectating))
+                        .or_else(in_state(LanState::Disconnected)),
+                ),
+            )
+            .add_systems(
+                Update,
+                leave_action.run_if(
+                    in_state(LanState::PlayerMove)
+                        .or_else(in_state(LanState::WaitingForOpponent))
+                        .or_else(in_state(LanState::RoundFinish))
+                        .or_else(in_state(LanState::Reconnecting))
+                        .and_then(is_chat_closed),
+                ),
+            );
+        for state in [
+            LanState::Setup,
+            LanState::Connecting,
+            LanState::PlayerMove,
+            LanState::WaitingForOpponent,
+            LanState::RoundFinish,
+            LanState::Reconnecting,
+            LanState::Spectating,
+            LanState::Disconnected,
+        ] {
+            app.add_systems(OnExit(state), despawn_screen::<OnLanScreen>);
+        }
+    }
+}
+
+fn get_address_char(key_code: KeyCode) -> Option<char> {
+    match key_code {
+        KeyCode::Key0 | KeyCode::Numpad0 => Some('0'),
+        KeyCode::Key1 | KeyCode::Numpad1 => Some('1'),
+        KeyCode::Key2 | KeyCode::Numpad2 => Some('2'),
+        KeyCode::Key3 | KeyCode::Numpad3 => Some('3'),
+        KeyCode::Key4 | KeyCode::Numpad4 => Some('4'),
+        KeyCode::Key5 | KeyCode::Numpad5 => Some('5'),
+        KeyCode::Key6 | KeyCode::Numpad6 => Some('6'),
+        KeyCode::Key7 | KeyCode::Numpad7 => Some('7'),
+        KeyCode::Key8 | KeyCode::Numpad8 => Some('8'),
+        KeyCode::Key9 | KeyCode::Numpad9 => Some('9'),
+        KeyCode::Period | KeyCode::NumpadDecimal => Some('.'),
+        KeyCode::Colon | KeyCode::Semicolon => Some(':'),
+        _ => None,
+    }
+}
+
+fn get_chat_char(key_code: KeyCode, is_shift: bool) -> Option<char> {
+    if let Some(x) = LETTER_KEYS.iter().position(|x| *x == key_code) {
+        let letter = (b'a' + x as u8) as char;
+        return Some(if is_shift { letter.to_ascii_uppercase() } else { letter });
+    }
+    match key_code {
+        KeyCode::Key1 if is_shift => Some('!'),
+        KeyCode::Slash if is_shift => Some('?'),
+        KeyCode::Space => Some(' '),
+        KeyCode::Comma => Some(','),
+        KeyCode::Apostrophe => Some('\''),
+        KeyCode::Minus => Some('-'),
+        x => get_address_char(x),
+    }
+}
+
+fn get_chat_text(link: &LanLink, game_settings: &GameSettings) -> String {
+    let Some(session) = link.session.as_ref().filter(|x| x.is_ready) else {
+        return String::new();
+    };
+    let mut lines: Vec<String> = link
+        .chat
+        .get_last(CHAT_OVERLAY_LINES)
+        .map(|x| format!("{}: {}", x.sender, x.content.get_text()))
+        .collect();
+    if session.opponent_profile.is_some_and(|x| game_settings.muted_profiles.contains(&x)) {
+        lines.push(format!("{} is muted", session.get_opponent_name()));
+    }
+    if let Some(x) = &link.chat_input {
+        lines.push(format!("> {}| (ENTER to send, ESC to cancel)", x));
+    }
+    lines.join("\n")
+}
+
+fn send_chat(link: &mut LanLink, content: ChatContent) -> bool {
+    let Some(session) = link.session.as_mut() else {
+        return false;
+    };
+    let is_sent = session.chat(content.clone());
+    if is_sent {
+        link.chat.push(&session.name, content);
+    }
+    is_sent
+}
+
+pub fn is_chat_closed(link: Res<LanLink>) -> bool {
+    link.chat_input.is_none()
+}
+
+fn get_ruleset_text(rulesets: &Rulesets, index: usize, match_format: MatchFormat) -> String {
+    match rulesets.0.get(index) {
+        Some(x) => format!("Host {} ({}), LEFT/RIGHT to change", x.name, match_format.get_friendly_name()),
+        None => String::from("No ruleset to host"),
+    }
+}
+
+fn get_lobbies_text(link: &LanLink) -> String {
+    match link.browser {
+        _ if !link.lobbies.0.is_empty() => String::new(),
+        Some(_) => String::from("Looking for lobbies..."),
+        None => format!("Lobbies cannot be found, port {} is used by another game", DISCOVERY_PORT),
+    }
+}
+
+fn get_lobby_lines(link: &LanLink, rulesets: &Rulesets) -> Vec<String> {
+    link.lobbies
+        .0
+        .iter()
+        .enumerate()
+        .map(|(index, x)| {
+            let announcement = &x.announcement;
+            format!(
+                "{} {} on {}: {} ({}){} - {}",
+                if link.selected_lobby == Some(index) { ">" } else { " " },
+                announcement.profile_name,
+                announcement.host_name,
+                announcement.ruleset_name,
+                announcement.match_format.get_friendly_name(),
+                if !rulesets.0.iter().any(|y| y.id == announcement.ruleset_id) {
+                    ", ruleset missing"
+                } else if announcement.is_playing {
+                    ", playing"
+                } else {
+                    ""
+                },
+                x.address
+            )
+        })
+        .collect()
+}
+
+fn get_score_text(session: &LanSession) -> String {
+    format!(
+        "{}: {}, {}: {}, draws: {}",
+        session.name,
+        session.score.wins,
+        session.get_opponent_name(),
+        session.score.loses,
+        session.score.draws
+    )
+}
+
+fn get_spectators_text(spectators: &Spectators) -> String {
+    match spectators.get_names() {
+        x if x.is_empty() => String::new(),
+        x => format!("Watched by {}", x.join(", ")),
+    }
+}
+
+fn get_spectator_texts(session: &SpectatorSession) -> [String; 4] {
+    let [first, second] = &session.players;
+    let (phrase, moves) = match session.last_round {
+        Some((first_move, second_move)) => (
+            GameMove::get_phrase(&session.game_type, &first_move, &second_move),
+            format!(
+                "{} ({}): {}, {} ({}): {}",
+                first,
+                first_move.get_name(&session.game_type),
+                first_move.beats_other(&session.game_type, &second_move).get_friendly_name(),
+                second,
+                second_move.get_name(&session.game_type),
+                second_move.beats_other(&session.game_type, &first_move).get_friendly_name()
+            ),
+        ),
+        None => (String::from("Players are choosing..."), String::new()),
+    };
+    let score = format!(
+        "{}: {}, {}: {}, draws: {}",
+        first, session.score.wins, second, session.score.loses, session.score.draws
+    );
+    let status = match session.match_format {
+        MatchFormat::Endless => format!("Round {}", session.round + 1),
+        x => x.get_status_text(session.get_winner(), first, second),
+    };
+    [phrase, moves, score, status]
+}
+
+fn get_reconnect_text(link: &LanLink) -> String {
+    let seconds = link.grace_timer.as_ref().map_or(0.0, |x| x.remaining_secs().ceil());
+    match link.session.as_ref() {
+        Some(x) if x.role == LanRole::Host => format!("Waiting {} s for {} to reconnect...", seconds, x.get_opponent_name()),
+        _ => format!("Reconnecting to {}, {} s left...", link.address, seconds),
+    }
+}
+
+// Screen of the round which was played when the connection was lost.
+fn get_resume_state(session: &LanSession) -> LanState {
+    if session.commitment.is_some() {
+        LanState::WaitingForOpponent
+    } else if session.last_round.is_some() && session.get_winner().is_some() {
+        LanState::RoundFinish
+    } else {
+        LanState::PlayerMove
+    }
+}
+
+// Both players asked for a rematch, whichever side agreed last. Spectators start watching the new match too and chat
+// history is kept only for the match.
+fn restart_match(spectators: &mut Spectators, chat: &mut ChatHistory, lan_state: &mut NextState<LanState>) {
+    spectators.broadcast(&LanMessage::MatchRestarted);
+    chat.clear();
+    lan_state.set(LanState::PlayerMove);
+}
+
+fn record_lan_match(match_results: &MatchResults, session: &LanSession, winner: Option<GameResult>, forfeit: bool) {
+    let record = MatchRecord {
+        timestamp: get_timestamp(),
+        profile: session.profile,
+        match_id: session.match_id,
+        game_type: session.game_type.ruleset().id.clone(),
+        opponent: MatchOpponent::Player(PlayerOpponent::Lan),
+        format: session.match_format,
+        wins: session.score.wins as u32,
+        loses: session.score.loses as u32,
+        draws: session.score.draws as u32,
+        winner,
+        forfeit,
+    };
+    if let Err(e) = match_results.append(&record) {
+        warn!("Unable to save match result: {}", e);
+    }
+}
+
+fn get_status_text(session: &LanSession) -> String {
+    let opponent_name = session.get_opponent_name();
+    if session.is_rematch_requested {
+        format!("Rematch requested, waiting for {}", opponent_name)
+    } else if session.is_rematch_offered {
+        format!("{} wants a rematch", opponent_name)
+    } else if session.opponent_hash.is_some() {
+        format!("{} has locked in", opponent_name)
+    } else {
+        format!("{} is choosing...", opponent_name)
+    }
+}
+
+pub fn init_lan(
+    mut link: ResMut<LanLink>,
+    mut lan_state: ResMut<NextState<LanState>>,
+    game_settings: Res<GameSettings>,
+    game_type: Res<GameType>,
+    rulesets: Res<Rulesets>,
+) {
+    let ruleset_index = match &*game_type {
+        GameType::Ruleset(x) => rulesets.0.iter().position(|y| y.id == x.id).unwrap_or(0),
+        GameType::None => 0,
+    };
+    *link = LanLink {
+        address: game_settings.lan_address.clone(),
+        ruleset_index,
+        browser: LobbyBrowser::new(DISCOVERY_PORT).ok(),
+        discovery_timer: Timer::from_seconds(ANNOUNCE_INTERVAL, TimerMode::Repeating),
+        retry_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        ..default()
+    };
+    lan_state.set(LanState::Setup);
+}
+
+// Opponent is told about leaving, the connection is closed when the link is reset.
+pub fn leave_lan(mut link: ResMut<LanLink>, mut lan_state: ResMut<NextState<LanState>>) {
+    let link = &mut *link;
+    if let (Some(connection), Some(session)) = (link.connection.as_mut(), link.session.as_mut()) {
+        session.leave();
+        for message in session.take_outgoing() {
+            if connection.send(&message).is_err() {
+                break;
+            }
+        }
+    }
+    if let (Some(connection), Some(spectator)) = (link.connection.as_mut(), link.spectator.as_mut()) {
+        spectator.leave();
+        for message in spectator.take_outgoing() {
+            let _ = connection.send(&message);
+        }
+    }
+    link.spectators.close();
+    *link = LanLink::default();
+    lan_state.set(LanState::NotInit);
+}
+
+pub fn setup_setup_screen(mut commands: Commands, game_font: Res<GameFont>, game_settings: Res<GameSettings>, rulesets: Res<Rulesets>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, String::from("LAN game"), TITLE_SIZE, TITLE_COLOR, font);
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                spawn_button(parent, "<", KeyCode::Left, font);
+                parent.spawn((
+                    TextBundle::from_section(
+                        get_ruleset_text(&rulesets, link.ruleset_index, game_settings.match_format),
+                        TextStyle {
+                            font_size: BUTTON_TEXT_SIZE,
+                            color: BUTTON_TITLE_COLOR,
+                            font: font.clone(),
+                        },
+                    ),
+                    OnLanRulesetText,
+                ));
+                spawn_button(parent, ">", KeyCode::Right, font);
+            });
+        spawn_text(parent, String::from("Address to join:"), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        parent.spawn((
+            TextBundle::from_section(
+                link.address.clone(),
+                TextStyle {
+                    font_size: BUTTON_TEXT_SIZE,
+                    color: BUTTON_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ),
+            OnLanAddressText,
+        ));
+        spawn_text(
+            parent,
+            String::from("Type digits, dots and colon, BACKSPACE to delete"),
+            16.0,
+            OVERVIEW_SUB_TITLE_COLOR,
+            font,
+        );
+        spawn_text(
+            parent,
+            String::from("Lobbies in network, UP/DOWN to choose:"),
+            16.0,
+            OVERVIEW_SUB_TITLE_COLOR,
+            font,
+        );
+        parent.spawn((
+            TextBundle::from_section(
+                get_lobbies_text(&link),
+                TextStyle {
+                    font_size: 16.0,
+                    color: BUTTON_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ),
+            OnLanLobbiesText,
+        ));
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLanLobbyList::default(),
+        ));
+        spawn_buttons(
+            parent,
+            &[
+                (&format!("(H)ost on port {}", DEFAULT_PORT), KeyCode::H),
+                ("ENTER to join", KeyCode::Return),
+                ("(W)atch", KeyCode::W),
+                ("(B)ack", KeyCode::B),
+            ],
+            font,
+        );
+    });
+}
+
+// Clicked lobby is selected, its ENTER joins it.
+pub fn select_lobby(query: Query<(&Interaction, &LobbyButton), Changed<Interaction>>, mut link: ResMut<LanLink>) {
+    for (interaction, lobby_button) in &query {
+        if *interaction == Interaction::Pressed {
+            link.selected_lobby = Some(lobby_button.0);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn setup_action(
+    actions: Res<Actions>,
+    mut query_ruleset: Query<&mut Text, (With<OnLanRulesetText>, Without<OnLanAddressText>)>,
+    mut query_address: Query<&mut Text, (With<OnLanAddressText>, Without<OnLanRulesetText>)>,
+    mut link: ResMut<LanLink>,
+    mut game_type: ResMut<GameType>,
+    game_settings: Res<GameSettings>,
+    mut lan_state: ResMut<NextState<LanState>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    rulesets: Res<Rulesets>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    let number_of_rulesets = rulesets.0.len();
+    if number_of_rulesets > 0 && actions.any_just_pressed([KeyCode::Left, KeyCode::Right]) {
+        link.ruleset_index = if actions.just_pressed(KeyCode::Left) {
+            (link.ruleset_index + number_of_rulesets - 1) % number_of_rulesets
+        } else {
+            (link.ruleset_index + 1) % number_of_rulesets
+        };
+        for mut text in &mut query_ruleset {
+            text.sections[0].value = get_ruleset_text(&rulesets, link.ruleset_index, game_settings.match_format);
+        }
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::H) {
+        let Some(ruleset) = rulesets.0.get(link.ruleset_index) else {
+            return;
+        };
+        let result = ruleset
+            .validate()
+            .map_err(|e| e.to_string())
+            .and_then(|_| listen(DEFAULT_PORT).map_err(|e| e.to_string()));
+        match result {
+            Ok(listener) => {
+                let announcement = LobbyAnnouncement {
+                    version: PROTOCOL_VERSION,
+                    host_name: get_host_name(),
+                    profile_name: game_settings.player_options.name.clone(),
+                    ruleset_id: ruleset.id.clone(),
+                    ruleset_name: ruleset.name.clone(),
+                    match_format: game_settings.match_format,
+                    port: DEFAULT_PORT,
+                    is_playing: false,
+                };
+                // Discovery port is freed, so a guest started on the same machine can find this lobby.
+                link.browser = None;
+                link.announcer = get_announcer(&announcement);
+                link.announcement = Some(announcement);
+                *game_type = GameType::Ruleset(ruleset.clone());
+                link.listener = Some(listener);
+                link.session = Some(LanSession::new_host(
+                    game_settings.player_options.name.clone(),
+                    game_settings.player_options.uuid,
+                    game_type.clone(),
+                    game_settings.match_format,
+                ));
+                lan_state.set(LanState::Connecting);
+            }
+            Err(e) => {
+                link.error = Some(e);
+                lan_state.set(LanState::Disconnected);
+            }
+        }
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if !link.lobbies.0.is_empty() && actions.any_just_pressed([KeyCode::Up, KeyCode::Down]) {
+        let last = link.lobbies.0.len() - 1;
+        link.selected_lobby = match link.selected_lobby {
+            None if actions.just_pressed(KeyCode::Down) => Some(0),
+            Some(0) if actions.just_pressed(KeyCode::Up) => None,
+            Some(x) if actions.just_pressed(KeyCode::Up) => Some(x - 1),
+            Some(x) => Some((x + 1).min(last)),
+            None => None,
+        };
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter, KeyCode::W]) {
+        if let Some(x) = link.selected_lobby.and_then(|x| link.lobbies.0.get(x)) {
+            link.address = x.address.to_string();
+        }
+        let name = game_settings.player_options.name.clone();
+        if actions.just_pressed(KeyCode::W) {
+            link.spectator = Some(SpectatorSession::new(name));
+        } else {
+            link.session = Some(LanSession::new_guest(name, game_settings.player_options.uuid));
+        }
+        let address = link.address.clone();
+        link.connecting = Some(thread::spawn(move || LanConnection::connect(&address)));
+        lan_state.set(LanState::Connecting);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::B) {
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else {
+        let mut is_changed = false;
+        for key_code in actions.get_just_pressed() {
+            if *key_code == KeyCode::Back {
+                is_changed |= link.address.pop().is_some();
+            } else if let Some(x) = get_address_char(*key_code).filter(|_| link.address.len() < MAX_ADDRESS_LENGTH) {
+                link.address.push(x);
+                is_changed = true;
+            }
+        }
+        if is_changed {
+            link.selected_lobby = None;
+            for mut text in &mut query_address {
+                text.sections[0].value = link.address.clone();
+            }
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        }
+    }
+}
+
+pub fn setup_connecting_screen(mut commands: Commands, game_font: Res<GameFont>, game_type: Res<GameType>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    let (title, text) = match link.session.as_ref().map(|x| x.role) {
+        Some(LanRole::Host) => (
+            format!("Hosting {}", game_type.ruleset().name),
+            format!("Waiting for opponent on port {}...", DEFAULT_PORT),
+        ),
+        Some(LanRole::Guest) => (format!("Joining {}", link.address), String::from("Agreeing on ruleset...")),
+        None => (format!("Watching {}", link.address), String::from("Waiting for match to start...")),
+    };
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, title, TITLE_SIZE, TITLE_COLOR, font);
+        spawn_text(parent, text, BUTTON_TEXT_SIZE, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_button(parent, "(B)ack", KeyCode::B, font);
+    });
+}
+
+// Guest or spectator waits on the Connecting screen until the connection is made.
+pub fn update_join(
+    mut link: ResMut<LanLink>,
+    mut lan_state: ResMut<NextState<LanState>>,
+    mut game_settings: ResMut<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    if !link.connecting.as_ref().is_some_and(|x| x.is_finished()) {
+        return;
+    }
+    let result = link.connecting.take().unwrap().join();
+    match result.unwrap_or_else(|_| Err(LanError::Io(String::from("Connecting failed.")))) {
+        Ok(connection) => {
+            link.browser = None;
+            game_settings.lan_address = link.address.clone();
+            game_settings.fetch();
+            link.connection = Some(connection);
+        }
+        Err(e) => {
+            link.error = Some(format!("Unable to join {}. {}", link.address, e));
+            link.session = None;
+            link.spectator = None;
+            lan_state.set(LanState::Disconnected);
+
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.lose);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_lan_link(
+    mut link: ResMut<LanLink>,
+    mut query: Query<&mut Text, With<OnLanStatusText>>,
+    mut game_type: ResMut<GameType>,
+    mut lan_state: ResMut<NextState<LanState>>,
+    rulesets: Res<Rulesets>,
+    game_settings: Res<GameSettings>,
+    match_results: Res<MatchResults>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    let link = &mut *link;
+    if let (Some(connection), Some(spectator)) = (link.connection.as_mut(), link.spectator.as_mut()) {
+        update_spectator_link(
+            connection,
+            spectator,
+            &mut game_type,
+            &mut lan_state,
+            &rulesets,
+            &game_settings,
+            &audio,
+            &game_sounds,
+        )
+        .unwrap_or_else(|e| {
+            link.error = Some(e);
+            link.connection = None;
+            link.spectator = None;
+        });
+        return;
+    }
+    if let Some(listener) = &link.listener {
+        loop {
+            match accept(listener) {
+                Ok(Some(x)) => link.spectators.add(x),
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Unable to accept LAN connection: {}", e);
+                    break;
+                }
+            }
+        }
+        let welcome = link.session.as_ref().filter(|x| x.is_ready).map(|x| {
+            let players = [x.name.clone(), x.get_opponent_name().to_string()];
+            get_welcome(&x.game_type, x.match_format, players, x.round, &x.score)
+        });
+        // Opponent who lost the connection may come back before the old connection timed out here.
+        let opponent_profile = link.session.as_ref().filter(|x| x.is_ready).and_then(|x| x.opponent_profile);
+        let is_player_wanted = |x: &LanMessage| match x {
+            LanMessage::Hello { .. } => link.connection.is_none() && opponent_profile.is_none(),
+            LanMessage::Resume { profile, .. } => opponent_profile == Some(*profile),
+            _ => false,
+        };
+        if let Some(x) = link.spectators.update(is_player_wanted, welcome.as_ref()) {
+            link.connection = Some(x);
+            if let Some(announcement) = link.announcement.as_mut().filter(|x| !x.is_playing) {
+                announcement.is_playing = true;
+                link.announcer = get_announcer(announcement);
+            }
+        }
+    }
+    let (Some(connection), Some(session)) = (link.connection.as_mut(), link.session.as_mut()) else {
+        return;
+    };
+
+    let events = match poll(connection, session, &rulesets) {
+        Ok(x) => x,
+        Err(e) if e.is_connection_lost() && session.is_ready => {
+            warn!("LAN connection lost: {}", e);
+            link.connection = None;
+            if link.grace_timer.is_none() {
+                link.grace_timer = Some(Timer::new(RECONNECT_GRACE, TimerMode::Once));
+            }
+            lan_state.set(LanState::Reconnecting);
+
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            return;
+        }
+        Err(e) => {
+            warn!("LAN game ended: {}", e);
+            // Dedicated server held the seat of the opponent who lost the connection, they did not come back in time.
+            let sound = if e == LanError::OpponentForfeited {
+                record_lan_match(&match_results, session, Some(GameResult::Win), true);
+                link.error = Some(format!("{} did not come back, match is forfeited.", session.get_opponent_name()));
+                &game_sounds.win
+            } else {
+                link.error = Some(e.to_string());
+                &game_sounds.lose
+            };
+            link.connection = None;
+            link.session = None;
+            link.spectators.close();
+            link.listener = None;
+            link.announcer = None;
+            lan_state.set(LanState::Disconnected);
+
+            play_sound(&audio, game_settings.is_sound_on, sound);
+            return;
+        }
+    };
+    for event in events {
+        match event {
+            LanEvent::Connected => {
+                *game_type = session.game_type.clone();
+                lan_state.set(LanState::PlayerMove);
+
+                play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+            LanEvent::OpponentCommitted | LanEvent::RematchRequested => {
+                for mut text in &mut query {
+                    text.sections[0].value = get_status_text(session);
+                }
+            }
+            LanEvent::RoundFinished(result) => {
+                if let Some(x) = session.last_round {
+                    link.spectators
+                        .broadcast(&get_round_played(&session.game_type, session.round - 1, x, &session.score));
+                }
+                if let Some(x) = session.get_winner() {
+                    record_lan_match(&match_results, session, Some(x), false);
+                }
+                lan_state.set(LanState::RoundFinish);
+                let result_sound = match result {
+                    GameResult::Win => &game_sounds.win,
+                    GameResult::Lose => &game_sounds.lose,
+                    GameResult::Draw => &game_sounds.drawn,
+                };
+                play_sound(&audio, game_settings.is_sound_on, result_sound);
+            }
+            LanEvent::MatchRestarted => {
+                restart_match(&mut link.spectators, &mut link.chat, &mut lan_state);
+
+                play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+            LanEvent::Resumed => {
+                link.grace_timer = None;
+                lan_state.set(get_resume_state(session));
+
+                play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+            LanEvent::Chat(content) => {
+                if session.opponent_profile.is_some_and(|x| game_settings.muted_profiles.contains(&x)) {
+                    continue;
+                }
+                link.chat.push(session.get_opponent_name(), content);
+
+                play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+        }
+    }
+}
+
+pub fn get_announcer(announcement: &LobbyAnnouncement) -> Option<LobbyAnnouncer> {
+    let announcer = LobbyAnnouncer::new(announcement, DISCOVERY_PORT)
+        .map_err(|e| warn!("Lobby cannot be announced: {}", e))
+        .ok();
+    if let Some(x) = &announcer {
+        x.announce();
+    }
+    announcer
+}
+
+// Returns the error text when watching ended.
+#[allow(clippy::too_many_arguments)]
+fn update_spectator_link(
+    connection: &mut LanConnection,
+    spectator: &mut SpectatorSession,
+    game_type: &mut GameType,
+    lan_state: &mut NextState<LanState>,
+    rulesets: &Rulesets,
+    game_settings: &GameSettings,
+    audio: &Res<Audio>,
+    game_sounds: &GameSounds,
+) -> Result<(), String> {
+    let events = poll_spectator(connection, spectator, rulesets).map_err(|e| {
+        lan_state.set(LanState::Disconnected);
+        e.to_string()
+    })?;
+    for event in events {
+        match event {
+            SpectatorEvent::Welcomed => {
+                *game_type = spectator.game_type.clone();
+                lan_state.set(LanState::Spectating);
+
+                play_sound(audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+            SpectatorEvent::RoundPlayed | SpectatorEvent::MatchRestarted => {
+                play_sound(audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Lobby is announced while hosting. Lobbies are browsed only on the setup screen, binding the discovery
+// port is retried there, because another instance on the same machine may hold it.
+#[allow(clippy::too_many_arguments)]
+pub fn update_lobby_discovery(
+    mut commands: Commands,
+    mut link: ResMut<LanLink>,
+    mut query: Query<&mut Text, With<OnLanLobbiesText>>,
+    mut query_list: Query<(Entity, &mut OnLanLobbyList)>,
+    lan_state: Res<State<LanState>>,
+    rulesets: Res<Rulesets>,
+    game_font: Res<GameFont>,
+    time: Res<Time>,
+) {
+    let is_setup = *lan_state.get() == LanState::Setup;
+    if link.discovery_timer.tick(time.delta()).just_finished() {
+        if let Some(x) = &link.announcer {
+            x.announce();
+        }
+        if is_setup && link.browser.is_none() {
+            link.browser = LobbyBrowser::new(DISCOVERY_PORT).ok();
+        }
+    }
+    if !is_setup {
+        return;
+    }
+
+    let now = time.elapsed_seconds_f64();
+    let link = &mut *link;
+    if let Some(browser) = &link.browser {
+        for (ip, announcement) in browser.receive() {
+            link.lobbies.update(ip, announcement, now);
+        }
+    }
+    link.lobbies.remove_expired(now);
+    if link.selected_lobby.is_some_and(|x| x >= link.lobbies.0.len()) {
+        link.selected_lobby = None;
+    }
+    let text_value = get_lobbies_text(link);
+    for mut text in &mut query {
+        if text.sections[0].value != text_value {
+            text.sections[0].value = text_value.clone();
+        }
+    }
+    let lines = get_lobby_lines(link, &rulesets);
+    for (entity, mut list) in &mut query_list {
+        if list.0 == lines {
+            continue;
+        }
+        commands.entity(entity).despawn_descendants().with_children(|parent| {
+            for (index, line) in lines.iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..default()
+                        },
+                        LobbyButton(index),
+                        HotKey(KeyCode::Return),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            line.clone(),
+                            TextStyle {
+                                font_size: 16.0,
+                                color: BUTTON_TITLE_COLOR,
+                                font: game_font.0.clone(),
+                            },
+                        ));
+                    });
+            }
+        });
+        list.0 = lines.clone();
+    }
+}
+
+pub fn setup_chat_overlay(mut commands: Commands, game_font: Res<GameFont>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            OnLanChatOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: OVERVIEW_SUB_TITLE_COLOR,
+                        font: game_font.0.clone(),
+                    },
+                ),
+                OnLanChatText,
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    OnLanChatButtons,
+                ))
+                .with_children(|parent| {
+                    let emotes: Vec<String> = Emote::ALL.iter().enumerate().map(|(i, x)| format!("{} {}", i + 1, x.get_text())).collect();
+                    for (text, key_code) in emotes.iter().zip(EMOTE_KEYS) {
+                        spawn_button(parent, text, key_code, &game_font.0);
+                    }
+                    spawn_button(parent, "(T)alk", KeyCode::T, &game_font.0);
+                    spawn_button(parent, "(M)ute", KeyCode::M, &game_font.0);
+                });
+        });
+}
+
+pub fn update_chat_overlay(
+    link: Res<LanLink>,
+    game_settings: Res<GameSettings>,
+    mut query: Query<&mut Text, With<OnLanChatText>>,
+    mut query_buttons: Query<&mut Visibility, With<OnLanChatButtons>>,
+) {
+    let text_value = get_chat_text(&link, &game_settings);
+    for mut text in &mut query {
+        if text.sections[0].value != text_value {
+            text.sections[0].value = text_value.clone();
+        }
+    }
+    // Emotes can be sent while connected and not typing.
+    let visibility = if link.connection.is_some() && link.chat_input.is_none() && link.session.as_ref().is_some_and(|x| x.is_ready) {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut x in &mut query_buttons {
+        if *x != visibility {
+            *x = visibility;
+        }
+    }
+}
+
+// Emotes (1 to 4), typed chat (T) and mute (M) work on every screen of the match. Actions used by chat are cleared, so
+// screens do not react to them too.
+pub fn update_lan_chat(
+    mut on_key_press_events: EventReader<OnKeyPressEvent>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut actions: ResMut<Actions>,
+    mut link: ResMut<LanLink>,
+    mut game_settings: ResMut<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    let link = &mut *link;
+    let is_chat_open = link.connection.is_some() && link.session.as_ref().is_some_and(|x| x.is_ready);
+    if !is_chat_open {
+        on_key_press_events.clear();
+        link.chat_input = None;
+        return;
+    }
+    let is_shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut is_handled = false;
+    for event in on_key_press_events.iter() {
+        let key_code = event.key_code;
+        match link.chat_input.as_mut() {
+            Some(_) if key_code == KeyCode::Escape => link.chat_input = None,
+            Some(x) if [KeyCode::Return, KeyCode::NumpadEnter].contains(&key_code) => {
+                let content = ChatContent::Text(std::mem::take(x)).sanitize();
+                link.chat_input = None;
+                if let Some(x) = content {
+                    send_chat(link, x);
+                }
+            }
+            Some(x) if key_code == KeyCode::Back => {
+                x.pop();
+            }
+            Some(x) => {
+                if let Some(c) = get_chat_char(key_code, is_shift).filter(|_| x.chars().count() < MAX_CHAT_LENGTH) {
+                    x.push(c);
+                }
+            }
+            None if key_code == KeyCode::T => link.chat_input = Some(String::new()),
+            None if key_code == KeyCode::M => {
+                let Some(profile) = link.session.as_ref().and_then(|x| x.opponent_profile) else {
+                    continue;
+                };
+                if game_settings.muted_profiles.contains(&profile) {
+                    game_settings.muted_profiles.retain(|x| *x != profile);
+                } else {
+                    game_settings.muted_profiles.push(profile);
+                }
+                game_settings.fetch();
+            }
+            None => {
+                let Some(x) = EMOTE_KEYS.iter().position(|x| *x == key_code) else {
+                    continue;
+                };
+                send_chat(link, ChatContent::Emote(Emote::ALL[x]));
+            }
+        }
+        is_handled = true;
+    }
+    if is_handled || link.chat_input.is_some() {
+        actions.clear();
+    }
+    if is_handled {
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+fn spawn_status_text(parent: &mut ChildBuilder, session: &LanSession, font: &Handle<Font>) {
+    parent.spawn((
+        TextBundle::from_section(
+            get_status_text(session),
+            TextStyle {
+                font_size: 16.0,
+                color: OVERVIEW_TITLE_COLOR,
+                font: font.clone(),
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::all(Val::Px(10.0)),
+            ..default()
+        }),
+        OnLanStatusText,
+    ));
+}
+
+pub fn setup_move_screen(
+    mut commands: Commands,
+    mut selected_option: ResMut<SelectedOption>,
+    game_font: Res<GameFont>,
+    asset_server: Res<AssetServer>,
+    game_type: Res<GameType>,
+    link: Res<LanLink>,
+) {
+    let font = &game_font.0;
+    let session = link.session();
+    selected_option.set_value(1);
+
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(
+            parent,
+            format!("{} vs {}", session.name, session.get_opponent_name()),
+            TITLE_SIZE,
+            TITLE_COLOR,
+            font,
+        );
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                spawn_game_move_buttons(parent, &game_type, &asset_server, font);
+            });
+        parent.spawn((
+            TextBundle::from_section(
+                get_game_move_name_text(&game_type, 1),
+                TextStyle {
+                    font_size: BUTTON_TEXT_SMALL_SIZE,
+                    color: BUTTON_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ),
+            OnGameMoveName,
+        ));
+        spawn_status_text(parent, session, font);
+        spawn_text(parent, get_score_text(session), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_text(parent, get_spectators_text(&link.spectators), 12.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_buttons(parent, &[("ENTER to lock in", KeyCode::Return), ("(L)eave", KeyCode::L)], font);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn confirm_lan_move(
+    actions: Res<Actions>,
+    selected_option: Res<SelectedOption>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut link: ResMut<LanLink>,
+    mut lan_state: ResMut<NextState<LanState>>,
+) {
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        let (Some(game_move), Some(session)) = (GameMove::from_i32(&game_type, selected_option.get_value()), link.session.as_mut()) else {
+            return;
+        };
+        if session.commit(game_move, &mut OsRng) {
+            lan_state.set(LanState::WaitingForOpponent);
+
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        }
+    }
+}
+
+pub fn setup_waiting_screen(mut commands: Commands, game_font: Res<GameFont>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    let session = link.session();
+    let hash = session.commitment.as_ref().map(|x| x.hash.clone()).unwrap_or_default();
+
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, String::from("Move locked in"), TITLE_SIZE, TITLE_COLOR, font);
+        spawn_status_text(parent, session, font);
+        spawn_text(parent, format!("Sent commitment: {}", hash), 12.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_text(parent, get_score_text(session), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_button(parent, "(L)eave", KeyCode::L, font);
+    });
+}
+
+pub fn setup_result_screen(mut commands: Commands, game_font: Res<GameFont>, game_type: Res<GameType>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    let session = link.session();
+    let (own_move, opponent_move) = session.last_round.expect("Last LAN round is not set.");
+    let winner = session.get_winner();
+
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(
+            parent,
+            GameMove::get_phrase(&game_type, &own_move, &opponent_move),
+            TITLE_SIZE,
+            TITLE_COLOR,
+            font,
+        );
+        for (name, game_move, other_move) in [
+            (session.name.as_str(), own_move, opponent_move),
+            (session.get_opponent_name(), opponent_move, own_move),
+        ] {
+            spawn_text(
+                parent,
+                format!(
+                    "{} ({}): {}",
+                    name,
+                    game_move.get_name(&game_type),
+                    game_move.beats_other(&game_type, &other_move).get_friendly_name()
+                ),
+                24.0,
+                OVERVIEW_SUB_TITLE_COLOR,
+                font,
+            );
+        }
+        spawn_text(
+            parent,
+            format!("Move of {} matches its commitment", session.get_opponent_name()),
+            12.0,
+            OVERVIEW_SUB_TITLE_COLOR,
+            font,
+        );
+        spawn_text(parent, get_score_text(session), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        if session.match_format != MatchFormat::Endless {
+            spawn_text(
+                parent,
+                session.match_format.get_status_text(winner, &session.name, session.get_opponent_name()),
+                16.0,
+                OVERVIEW_TITLE_COLOR,
+                font,
+            );
+        }
+        spawn_status_text(parent, session, font);
+        if winner.is_some() {
+            spawn_buttons(parent, &[("(R)ematch", KeyCode::R), ("(L)eave", KeyCode::L)], font);
+        } else {
+            spawn_buttons(parent, &[("(C)ontinue", KeyCode::C), ("(L)eave", KeyCode::L)], font);
+        }
+    });
+}
+
+pub fn confirm_result_action(
+    actions: Res<Actions>,
+    mut query: Query<&mut Text, With<OnLanStatusText>>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut link: ResMut<LanLink>,
+    mut lan_state: ResMut<NextState<LanState>>,
+) {
+    let link = &mut *link;
+    let Some(session) = link.session.as_mut() else {
+        return;
+    };
+    if actions.just_pressed(KeyCode::C) && session.get_winner().is_none() {
+        lan_state.set(LanState::PlayerMove);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::R) && session.get_winner().is_some() {
+        if let Some(LanEvent::MatchRestarted) = session.request_rematch() {
+            restart_match(&mut link.spectators, &mut link.chat, &mut lan_state);
+        }
+        for mut text in &mut query {
+            text.sections[0].value = get_status_text(session);
+        }
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+pub fn setup_reconnecting_screen(mut commands: Commands, game_font: Res<GameFont>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    let session = link.session();
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, String::from("Connection lost"), TITLE_SIZE, TITLE_COLOR, font);
+        parent.spawn((
+            TextBundle::from_section(
+                get_reconnect_text(&link),
+                TextStyle {
+                    font_size: BUTTON_TEXT_SIZE,
+                    color: OVERVIEW_SUB_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ),
+            OnLanReconnectText,
+        ));
+        spawn_text(parent, get_score_text(session), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_text(
+            parent,
+            String::from("Match is forfeited when time runs out"),
+            BUTTON_TEXT_SMALL_SIZE,
+            BUTTON_TITLE_COLOR,
+            font,
+        );
+        spawn_button(parent, "(L)eave", KeyCode::L, font);
+    });
+}
+
+// Host waits for the guest to come back, guest tries to connect again every second. When the grace period ends, the
+// player who stayed wins by forfeit.
+#[allow(clippy::too_many_arguments)]
+pub fn update_reconnect(
+    mut link: ResMut<LanLink>,
+    mut query: Query<&mut Text, With<OnLanReconnectText>>,
+    mut lan_state: ResMut<NextState<LanState>>,
+    match_results: Res<MatchResults>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    time: Res<Time>,
+) {
+    let link = &mut *link;
+    let (Some(grace_timer), Some(session)) = (link.grace_timer.as_mut(), link.session.as_mut()) else {
+        return;
+    };
+    if grace_timer.tick(time.delta()).finished() {
+        let (winner, error, sound) = match session.role {
+            LanRole::Host => (
+                GameResult::Win,
+                format!("{} did not come back, match is forfeited.", session.get_opponent_name()),
+                &game_sounds.win,
+            ),
+            LanRole::Guest => (
+                GameResult::Lose,
+                format!("Unable to reconnect to {}, match is forfeited.", link.address),
+                &game_sounds.lose,
+            ),
+        };
+        record_lan_match(&match_results, session, Some(winner), true);
+        link.error = Some(error);
+        link.connection = None;
+        link.session = None;
+        link.grace_timer = None;
+        link.connecting = None;
+        link.spectators.close();
+        link.listener = None;
+        link.announcer = None;
+        lan_state.set(LanState::Disconnected);
+
+        play_sound(&audio, game_settings.is_sound_on, sound);
+        return;
+    }
+
+    if session.role == LanRole::Guest && link.connection.is_none() {
+        match link.connecting.take() {
+            Some(x) if x.is_finished() => {
+                if let Ok(Ok(connection)) = x.join() {
+                    session.rejoin();
+                    link.connection = Some(connection);
+                }
+            }
+            Some(x) => link.connecting = Some(x),
+            None if link.retry_timer.tick(time.delta()).just_finished() => {
+                let address = link.address.clone();
+                link.connecting = Some(thread::spawn(move || LanConnection::connect(&address)));
+            }
+            None => {}
+        }
+    }
+    let text_value = get_reconnect_text(link);
+    for mut text in &mut query {
+        if text.sections[0].value != text_value {
+            text.sections[0].value = text_value.clone();
+        }
+    }
+}
+
+pub fn setup_spectator_screen(mut commands: Commands, game_font: Res<GameFont>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    let Some(spectator) = &link.spectator else {
+        return;
+    };
+    let [first, second] = &spectator.players;
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, format!("{} vs {}", first, second), TITLE_SIZE, TITLE_COLOR, font);
+        for (index, (value, (font_size, color))) in get_spectator_texts(spectator)
+            .into_iter()
+            .zip([
+                (TITLE_SIZE, TITLE_COLOR),
+                (24.0, OVERVIEW_SUB_TITLE_COLOR),
+                (16.0, OVERVIEW_SUB_TITLE_COLOR),
+                (16.0, OVERVIEW_TITLE_COLOR),
+            ])
+            .enumerate()
+        {
+            parent.spawn((
+                TextBundle::from_section(
+                    value,
+                    TextStyle {
+                        font_size,
+                        color,
+                        font: font.clone(),
+                    },
+                ),
+                OnLanSpectatorText(index),
+            ));
+        }
+        spawn_button(parent, "(B)ack", KeyCode::B, font);
+    });
+}
+
+pub fn update_spectator_screen(link: Res<LanLink>, mut query: Query<(&mut Text, &OnLanSpectatorText)>) {
+    let Some(spectator) = link.spectator.as_ref().filter(|_| link.is_changed()) else {
+        return;
+    };
+    let texts = get_spectator_texts(spectator);
+    for (mut text, index) in &mut query {
+        if text.sections[0].value != texts[index.0] {
+            text.sections[0].value = texts[index.0].clone();
+        }
+    }
+}
+
+pub fn setup_disconnected_screen(mut commands: Commands, game_font: Res<GameFont>, link: Res<LanLink>) {
+    let font = &game_font.0;
+    spawn_panel(&mut commands, OnLanScreen, |parent| {
+        spawn_text(parent, String::from("Disconnected"), TITLE_SIZE, TITLE_COLOR, font);
+        spawn_text(parent, link.error.clone().unwrap_or_default(), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_button(parent, "(B)ack", KeyCode::B, font);
+    });
+}
+
+pub fn back_action(
+    actions: Res<Actions>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if actions.just_pressed(KeyCode::B) {
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+pub fn leave_action(
+    actions: Res<Actions>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if actions.just_pressed(KeyCode::L) {
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_address_char() {
+        let address: String = [KeyCode::Key1, KeyCode::Numpad9, KeyCode::Period, KeyCode::A, KeyCode::Semicolon, KeyCode::Key7]
+            .into_iter()
+            .filter_map(get_address_char)
+            .collect();
+        assert_eq!(address, "19.:7");
+    }
+
+    #[test]
+    fn test_get_chat_char() {
+        let text: String = [
+            (KeyCode::G, true),
+            (KeyCode::G, false),
+            (KeyCode::Space, false),
+            (KeyCode::Key1, true),
+            (KeyCode::Key1, false),
+        ]
+        .into_iter()
+        .filter_map(|(key_code, is_shift)| get_chat_char(key_code, is_shift))
+        .collect();
+        assert_eq!(text, "Gg !1");
+        assert_eq!(get_chat_char(KeyCode::Escape, false), None);
+    }
+}
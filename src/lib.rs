@@ -0,0 +1,14 @@
+pub mod chat;
+pub mod commitment;
+pub mod game_move;
+pub mod game_result;
+pub mod game_type;
+pub mod lan;
+pub mod lan_discovery;
+pub mod match_format;
+pub mod ruleset;
+pub mod ruleset_analysis;
+pub mod server;
+pub mod spectator;
+#[cfg(test)]
+mod test_support;
@@ -4,28 +4,84 @@ use bevy::prelude::*;
 use bevy::window::WindowMode;
 use bevy_kira_audio::AudioPlugin;
 
+use crate::button::ButtonPlugin;
 use crate::closing::ClosingPlugin;
+use crate::commitment::{get_message, verify, ComputerCommitment};
 use crate::common::*;
 use crate::credits::CreditsPlugin;
 use crate::game::GamePlugin;
 use crate::game_overview::GameOverviewPlugin;
-use crate::game_settings::GameSettings;
+use crate::game_rng::{get_seed_argument, GameRng};
+use crate::game_settings::{GameSettings, DEFAULT_TARGET_WIN_RATE};
 use crate::game_type::GameType;
+use crate::history::{MatchHistory, MatchResults};
+use crate::hot_seat::HotSeatPlugin;
+use crate::lan_game::LanGamePlugin;
+use crate::match_setup::MatchSetupPlugin;
 use crate::menu::MenuPlugin;
+use crate::opponent::{ComputerOpponent, Opponent};
+use crate::rating::Ratings;
+use crate::round_timer::RoundCountdown;
+use crate::ruleset::Rulesets;
+use crate::ruleset_info::RulesetInfoPlugin;
+use crate::split_keyboard::SplitKeyboardPlugin;
+use crate::statistics::StatisticsPlugin;
 
+// Game logic and networking are shared with the server binary through the library crate.
+pub(crate) use rock_paper_scissors::{
+    chat, commitment, game_move, game_result, game_type, lan, lan_discovery, match_format, ruleset, ruleset_analysis, spectator,
+};
+
+mod adaptive;
+mod button;
 mod closing;
 mod common;
 mod credits;
 mod game;
-mod game_move;
 mod game_overview;
-mod game_result;
+mod game_rng;
 mod game_settings;
-mod game_type;
+mod history;
+mod history_statistics;
+mod hot_seat;
+mod iocaine;
+mod lan_game;
+mod match_setup;
 mod menu;
+mod nash;
+mod opponent;
 mod player_options;
+mod predictability;
+mod rating;
+mod round_timer;
+mod ruleset_info;
+mod split_keyboard;
+mod statistics;
+mod strategy;
+#[cfg(test)]
+mod test_support;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(x) = args.iter().position(|x| x == "--verify") {
+        match &args[x + 1..] {
+            [move_name, nonce, hash, ..] => {
+                let is_valid = verify(move_name, nonce, hash);
+                println!(
+                    "SHA-256({}) {} {}",
+                    get_message(move_name, nonce),
+                    if is_valid { "matches" } else { "does not match" },
+                    hash
+                );
+                std::process::exit(if is_valid { 0 } else { 1 });
+            }
+            _ => {
+                println!("Usage: --verify <move name> <nonce> <hash>");
+                std::process::exit(2);
+            }
+        }
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -46,14 +102,36 @@ fn main() {
         .add_state::<AppState>()
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(GameType::None)
+        .insert_resource(Rulesets::init())
         .insert_resource(SelectedOption::init())
         .insert_resource(GameSettings::init())
         .insert_resource(GameStatistics::init())
+        .insert_resource(LocalMatch::default())
+        .insert_resource(MatchHistory::init())
+        .insert_resource(MatchResults::init())
+        .insert_resource(Ratings::init())
+        .insert_resource(ComputerCommitment::default())
+        .insert_resource(RoundCountdown::default())
+        .insert_resource(GameRng::new(get_seed_argument(args.into_iter())))
+        .insert_resource(ComputerOpponent::new(Opponent::default(), DEFAULT_TARGET_WIN_RATE))
         .add_systems(
             Startup,
             (setup_camera, setup_game_sounds, setup_game_images, setup_game_font, setup_game_settings),
         )
-        .add_plugins((MenuPlugin, ClosingPlugin, GamePlugin, GameOverviewPlugin, CreditsPlugin))
+        .add_plugins((
+            ButtonPlugin,
+            MenuPlugin,
+            ClosingPlugin,
+            GamePlugin,
+            MatchSetupPlugin,
+            HotSeatPlugin,
+            SplitKeyboardPlugin,
+            LanGamePlugin,
+            GameOverviewPlugin,
+            RulesetInfoPlugin,
+            StatisticsPlugin,
+            CreditsPlugin,
+        ))
         .run();
 }
 
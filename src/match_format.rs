@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_result::GameResult;
+
+// Draws never count towards the match, so best of N is decided by wins and losses only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MatchFormat {
+    #[default]
+    Endless,
+    BestOf(u32),
+    FirstTo(u32),
+    WinByTwo(u32),
+}
+
+impl MatchFormat {
+    pub const ALL: [MatchFormat; 9] = [
+        MatchFormat::Endless,
+        MatchFormat::BestOf(3),
+        MatchFormat::BestOf(5),
+        MatchFormat::BestOf(7),
+        MatchFormat::FirstTo(3),
+        MatchFormat::FirstTo(5),
+        MatchFormat::FirstTo(10),
+        MatchFormat::WinByTwo(3),
+        MatchFormat::WinByTwo(5),
+    ];
+
+    pub fn get_friendly_name(&self) -> String {
+        match self {
+            MatchFormat::Endless => String::from("Endless"),
+            MatchFormat::BestOf(x) => format!("Best of {}", x),
+            MatchFormat::FirstTo(x) => format!("First to {}", x),
+            MatchFormat::WinByTwo(x) => format!("First to {}, win by two", x),
+        }
+    }
+
+    // Command line form: endless, best-of-N, first-to-N or win-by-two-N.
+    pub fn from_argument(value: &str) -> Option<MatchFormat> {
+        if value == "endless" {
+            return Some(MatchFormat::Endless);
+        }
+        let (name, number) = value.rsplit_once('-')?;
+        let number = number.parse().ok().filter(|x| *x > 0)?;
+        match name {
+            "best-of" => Some(MatchFormat::BestOf(number)),
+            "first-to" => Some(MatchFormat::FirstTo(number)),
+            "win-by-two" => Some(MatchFormat::WinByTwo(number)),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> MatchFormat {
+        let index = MatchFormat::ALL.iter().position(|x| x == self).map_or(0, |x| x + 1);
+        MatchFormat::ALL[index % MatchFormat::ALL.len()]
+    }
+
+    // Win when the player won the match, Lose when the computer did and None while the match goes on.
+    pub fn get_winner(&self, wins: u32, loses: u32) -> Option<GameResult> {
+        let is_winning = |x: u32, y: u32| match self {
+            MatchFormat::Endless => false,
+            MatchFormat::BestOf(n) => x > n / 2,
+            MatchFormat::FirstTo(n) => x >= *n,
+            MatchFormat::WinByTwo(n) => x >= *n && x >= y + 2,
+        };
+        if is_winning(wins, loses) {
+            Some(GameResult::Win)
+        } else if is_winning(loses, wins) {
+            Some(GameResult::Lose)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_status_text(&self, winner: Option<GameResult>, player_name: &str, opponent_name: &str) -> String {
+        match winner {
+            Some(GameResult::Win) => format!("{}: {} wins the match", self.get_friendly_name(), player_name),
+            Some(GameResult::Lose) => format!("{}: {} wins the match", self.get_friendly_name(), opponent_name),
+            _ => format!("{}: no winner yet", self.get_friendly_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_winner() {
+        assert_eq!(MatchFormat::Endless.get_winner(100, 0), None);
+        assert_eq!(MatchFormat::BestOf(5).get_winner(2, 2), None);
+        assert_eq!(MatchFormat::BestOf(5).get_winner(3, 1), Some(GameResult::Win));
+        assert_eq!(MatchFormat::BestOf(4).get_winner(2, 2), None);
+        assert_eq!(MatchFormat::BestOf(4).get_winner(1, 3), Some(GameResult::Lose));
+        assert_eq!(MatchFormat::FirstTo(3).get_winner(2, 2), None);
+        assert_eq!(MatchFormat::FirstTo(3).get_winner(2, 3), Some(GameResult::Lose));
+        assert_eq!(MatchFormat::WinByTwo(3).get_winner(3, 2), None);
+        assert_eq!(MatchFormat::WinByTwo(3).get_winner(5, 4), None);
+        assert_eq!(MatchFormat::WinByTwo(3).get_winner(6, 4), Some(GameResult::Win));
+        assert_eq!(MatchFormat::WinByTwo(3).get_winner(1, 3), Some(GameResult::Lose));
+    }
+
+    #[test]
+    fn test_from_argument() {
+        assert_eq!(MatchFormat::from_argument("endless"), Some(MatchFormat::Endless));
+        assert_eq!(MatchFormat::from_argument("best-of-3"), Some(MatchFormat::BestOf(3)));
+        assert_eq!(MatchFormat::from_argument("first-to-10"), Some(MatchFormat::FirstTo(10)));
+        assert_eq!(MatchFormat::from_argument("win-by-two-5"), Some(MatchFormat::WinByTwo(5)));
+        assert_eq!(MatchFormat::from_argument("best-of-0"), None);
+        assert_eq!(MatchFormat::from_argument("best-of"), None);
+        assert_eq!(MatchFormat::from_argument("last-to-3"), None);
+    }
+
+    #[test]
+    fn test_next() {
+        assert_eq!(MatchFormat::Endless.next(), MatchFormat::BestOf(3));
+        assert_eq!(MatchFormat::WinByTwo(5).next(), MatchFormat::Endless);
+        assert_eq!(MatchFormat::BestOf(9).next(), MatchFormat::Endless);
+    }
+}
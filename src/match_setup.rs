@@ -0,0 +1,269 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::button::{spawn_buttons, Actions, OptionButton};
+use crate::common::*;
+use crate::game_rng::GameRng;
+use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+use crate::opponent::{ComputerOpponent, Opponent};
+
+#[derive(Component)]
+pub struct OnMatchSetupScreen;
+
+pub struct MatchSetupPlugin;
+
+impl Plugin for MatchSetupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::MatchSetup), setup_match_setup_screen)
+            .add_systems(Update, (switch_opponent, confirm_button_action).run_if(in_state(AppState::MatchSetup)))
+            .add_systems(OnExit(AppState::MatchSetup), despawn_screen::<OnMatchSetupScreen>);
+    }
+}
+
+pub fn setup_match_setup_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_images: Res<GameImages>,
+    game_settings: Res<GameSettings>,
+    game_type: Res<GameType>,
+    mut selected_option: ResMut<SelectedOption>,
+) {
+    selected_option.set_value(game_settings.opponent.get_option_value());
+
+    let button_style = Style {
+        flex_direction: FlexDirection::Row,
+        width: Val::Px(600.0),
+        height: Val::Px(40.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Start,
+        justify_items: JustifyItems::Start,
+        align_items: AlignItems::Start,
+        ..default()
+    };
+
+    let button_icon_style = Style {
+        width: Val::Px(24.0),
+        height: Val::Px(24.0),
+        ..default()
+    };
+
+    let font = &game_font.0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Start,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnMatchSetupScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                (TextBundle::from_section(
+                    game_settings.player_options.name.clone(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Right))
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: MENU_BACKGROUND_COLOR.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    game_type.get_friendly_name(),
+                                    TextStyle {
+                                        font_size: TITLE_SIZE,
+                                        color: TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    ..default()
+                                }),
+                            );
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    "Choose opponent",
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: BUTTON_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            for opponent in Opponent::ALL {
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: button_style.clone(),
+                                            background_color: Color::NONE.into(),
+                                            ..default()
+                                        },
+                                        OptionButton(opponent.get_option_value()),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            ImageBundle {
+                                                style: button_icon_style.clone(),
+                                                image: UiImage::new(game_images.joystick.clone()),
+                                                visibility: if opponent.get_option_value() == selected_option.value {
+                                                    Visibility::Visible
+                                                } else {
+                                                    Visibility::Hidden
+                                                },
+                                                ..default()
+                                            },
+                                            opponent,
+                                        ));
+                                        parent.spawn(
+                                            TextBundle::from_section(
+                                                opponent.get_friendly_name(),
+                                                TextStyle {
+                                                    font_size: BUTTON_TEXT_SIZE,
+                                                    color: BUTTON_TITLE_COLOR,
+                                                    font: font.clone(),
+                                                },
+                                            )
+                                            .with_style(Style {
+                                                margin: UiRect::left(Val::Px(10.0)),
+                                                ..default()
+                                            }),
+                                        );
+                                    });
+                            }
+
+                            spawn_buttons(
+                                parent,
+                                &[
+                                    ("ENTER to start", KeyCode::Return),
+                                    ("(H)ot seat", KeyCode::H),
+                                    ("(S)plit keyboard", KeyCode::S),
+                                    ("(B)ack", KeyCode::B),
+                                ],
+                                font,
+                            );
+                        });
+                });
+        });
+}
+
+pub fn switch_opponent(
+    actions: Res<Actions>,
+    mut query: Query<(&mut Visibility, &Opponent)>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+    mut selected_option: ResMut<SelectedOption>,
+    game_settings: Res<GameSettings>,
+) {
+    let mut up_or_down = false;
+    if actions.just_pressed(KeyCode::Up) {
+        up_or_down = true;
+
+        if selected_option.value > 1 {
+            selected_option.value -= 1;
+        }
+    } else if actions.just_pressed(KeyCode::Down) {
+        up_or_down = true;
+
+        if selected_option.value < Opponent::ALL.len() as i32 {
+            selected_option.value += 1;
+        }
+    }
+    if selected_option.is_changed() {
+        for (mut visibility, opponent) in &mut query {
+            if opponent.get_option_value() == selected_option.value {
+                *visibility = Visibility::Visible;
+            } else {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+    if up_or_down {
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn confirm_button_action(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut selected_option: ResMut<SelectedOption>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_settings: ResMut<GameSettings>,
+    mut game_rng: ResMut<GameRng>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        if let Some(opponent) = Opponent::from_option_value(selected_option.value) {
+            game_settings.opponent = opponent;
+            game_settings.fetch();
+            commands.insert_resource(ComputerOpponent::new(opponent, game_settings.target_win_rate));
+            game_rng.start_match(game_settings.seed);
+            info!("Match seed: {}", game_rng.seed);
+
+            selected_option.set_value(1);
+            app_state.set(AppState::Playing);
+
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        }
+    } else if actions.just_pressed(KeyCode::H) {
+        selected_option.set_value(1);
+        app_state.set(AppState::HotSeat);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::S) {
+        selected_option.set_value(1);
+        app_state.set(AppState::SplitKeyboard);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    } else if actions.just_pressed(KeyCode::B) {
+        selected_option.set_value(1);
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
@@ -2,9 +2,14 @@ use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 use std::cmp::min;
 
+use crate::button::{spawn_button, Actions, HotKey, OptionButton};
 use crate::common::*;
+use crate::game_rng::GameRng;
 use crate::game_settings::GameSettings;
 use crate::game_type::GameType;
+use crate::player_options::PlayerOptions;
+use crate::round_timer::next_time_limit;
+use crate::ruleset::Rulesets;
 
 #[derive(Component)]
 pub struct OnStartMenuScreen;
@@ -18,7 +23,7 @@ pub struct OnChangeNameScreen;
 #[derive(Component)]
 pub struct OnChangeName;
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SaveCancelAction {
     Save,
     Cancel,
@@ -28,31 +33,139 @@ pub enum SaveCancelAction {
 pub struct BlinkingTimer(Timer);
 
 #[derive(Component)]
-pub struct OnGameSound;
+pub struct OnSettingText(SettingAction);
 
 #[derive(Component)]
 pub enum MenuAction {
-    Normal,
-    SpockLizard,
-    FireWater,
+    Play(usize),
+    Lan,
     Settings,
+    Statistics,
     Credits,
     Exit,
 }
 
-#[derive(Component)]
+impl MenuAction {
+    pub fn get_option_value(&self, number_of_rulesets: usize) -> i32 {
+        let number_of_rulesets = number_of_rulesets as i32;
+        match self {
+            MenuAction::Play(x) => *x as i32 + 1,
+            MenuAction::Lan => number_of_rulesets + 1,
+            MenuAction::Settings => number_of_rulesets + 2,
+            MenuAction::Statistics => number_of_rulesets + 3,
+            MenuAction::Credits => number_of_rulesets + 4,
+            MenuAction::Exit => number_of_rulesets + 5,
+        }
+    }
+
+    pub fn from_option_value(value: i32, number_of_rulesets: usize) -> Option<MenuAction> {
+        let number_of_rulesets = number_of_rulesets as i32;
+        if value >= 1 && value <= number_of_rulesets {
+            Some(MenuAction::Play(value as usize - 1))
+        } else if value == number_of_rulesets + 1 {
+            Some(MenuAction::Lan)
+        } else if value == number_of_rulesets + 2 {
+            Some(MenuAction::Settings)
+        } else if value == number_of_rulesets + 3 {
+            Some(MenuAction::Statistics)
+        } else if value == number_of_rulesets + 4 {
+            Some(MenuAction::Credits)
+        } else if value == number_of_rulesets + 5 {
+            Some(MenuAction::Exit)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SettingAction {
     Sound,
+    MatchFormat,
+    RoundTimer,
+    TimeoutAction,
+    TargetWinRate,
+    Seed,
+    Watchable,
     ChangeName,
+    ChangeSecondName,
     Back,
 }
 
+impl SettingAction {
+    pub const ALL: [SettingAction; 10] = [
+        SettingAction::Sound,
+        SettingAction::MatchFormat,
+        SettingAction::RoundTimer,
+        SettingAction::TimeoutAction,
+        SettingAction::TargetWinRate,
+        SettingAction::Seed,
+        SettingAction::Watchable,
+        SettingAction::ChangeName,
+        SettingAction::ChangeSecondName,
+        SettingAction::Back,
+    ];
+
+    pub fn get_option_value(&self) -> i32 {
+        SettingAction::ALL.iter().position(|x| x == self).unwrap() as i32 + 1
+    }
+
+    pub fn from_option_value(value: i32) -> Option<SettingAction> {
+        if value >= 1 {
+            SettingAction::ALL.get(value as usize - 1).copied()
+        } else {
+            None
+        }
+    }
+
+    pub fn get_text(&self, game_settings: &GameSettings) -> String {
+        match self {
+            SettingAction::Sound => "Sound (".to_string() + if game_settings.is_sound_on { "On" } else { "Off" } + ")",
+            SettingAction::MatchFormat => format!("Match ({})", game_settings.match_format.get_friendly_name()),
+            SettingAction::RoundTimer => match game_settings.round_time_limit {
+                Some(x) => format!("Round timer ({}s)", x),
+                None => String::from("Round timer (Off)"),
+            },
+            SettingAction::TimeoutAction => {
+                format!("On timeout ({})", game_settings.timeout_action.get_friendly_name())
+            }
+            SettingAction::TargetWinRate => format!("Adaptive target win rate ({}%)", game_settings.target_win_rate),
+            SettingAction::Seed => match game_settings.seed {
+                Some(x) => format!("Seed ({})", x),
+                None => String::from("Seed (random)"),
+            },
+            SettingAction::Watchable => "Spectators vs computer (".to_string() + if game_settings.is_watchable { "On" } else { "Off" } + ")",
+            SettingAction::ChangeName => String::from("Change name"),
+            SettingAction::ChangeSecondName => String::from("Change player 2 name (hot seat)"),
+            SettingAction::Back => String::from("Back"),
+        }
+    }
+}
+
+// Player whose name is changed on change name screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditedPlayer {
+    #[default]
+    First,
+    Second,
+}
+
+impl EditedPlayer {
+    fn get_player_options<'a>(&self, game_settings: &'a mut GameSettings) -> &'a mut PlayerOptions {
+        match self {
+            EditedPlayer::First => &mut game_settings.player_options,
+            EditedPlayer::Second => &mut game_settings.second_player_options,
+        }
+    }
+}
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<MenuState>()
             .add_event::<OnKeyPressEvent>()
+            .init_resource::<EditedPlayer>()
             .add_systems(OnEnter(AppState::Menu), init_setup_menu)
             .add_systems(OnEnter(MenuState::StartMenu), setup_start_menu)
             .add_systems(
@@ -69,7 +182,13 @@ impl Plugin for MenuPlugin {
             .add_systems(OnEnter(MenuState::ChangeName), setup_change_name_screen)
             .add_systems(
                 Update,
-                (on_key_press_event_trigger, on_key_press_event_listener, on_blinking_text_indicator).run_if(in_state(MenuState::ChangeName)),
+                (
+                    on_key_press_event_trigger,
+                    select_save_cancel_action.before(on_key_press_event_listener),
+                    on_key_press_event_listener,
+                    on_blinking_text_indicator,
+                )
+                    .run_if(in_state(MenuState::ChangeName)),
             )
             .add_systems(OnExit(MenuState::ChangeName), despawn_screen::<OnChangeNameScreen>);
     }
@@ -79,7 +198,14 @@ pub fn init_setup_menu(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::StartMenu);
 }
 
-pub fn setup_start_menu(mut commands: Commands, game_font: Res<GameFont>, game_images: Res<GameImages>, game_settings: Res<GameSettings>) {
+pub fn setup_start_menu(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_images: Res<GameImages>,
+    game_settings: Res<GameSettings>,
+    rulesets: Res<Rulesets>,
+    selected_option: Res<SelectedOption>,
+) {
     let button_style = Style {
         flex_direction: FlexDirection::Row,
         width: Val::Px(400.0),
@@ -169,83 +295,98 @@ pub fn setup_start_menu(mut commands: Commands, game_font: Res<GameFont>, game_i
                                     ..default()
                                 }),
                             );
-                            spawn_start_menu_button(
-                                parent,
-                                StartMenuButtonOptions {
-                                visibility: Visibility::Visible,
-                                text: "Normal",
-                                button_style: &button_style,
-                                icon_style: &button_icon_style,
-                                icon: &game_images.joystick,
-                                menu_action: MenuAction::Normal,
-                                font,
-                                }
-                            );
+                            for (index, ruleset) in rulesets.0.iter().enumerate() {
+                                let text = if ruleset.validate().is_ok() {
+                                    ruleset.name.clone()
+                                } else {
+                                    ruleset.name.clone() + " (invalid)"
+                                };
+                                spawn_start_menu_button(
+                                    parent,
+                                    StartMenuButtonOptions {
+                                        text: &text,
+                                        button_style: &button_style,
+                                        icon_style: &button_icon_style,
+                                        icon: &game_images.joystick,
+                                        menu_action: MenuAction::Play(index),
+                                        selected_value: selected_option.value,
+                                        number_of_rulesets: rulesets.0.len(),
+                                        font,
+                                    },
+                                );
+                            }
 
                             spawn_start_menu_button(
                                 parent,
                                 StartMenuButtonOptions {
-                                    visibility: Visibility::Hidden,
-                                    text: "Spock lizard",
+                                    text: "LAN game",
                                     button_style: &button_style,
                                     icon_style: &button_icon_style,
                                     icon: &game_images.joystick,
-                                    menu_action: MenuAction::SpockLizard,
+                                    menu_action: MenuAction::Lan,
+                                    selected_value: selected_option.value,
+                                    number_of_rulesets: rulesets.0.len(),
                                     font,
-                                }
+                                },
                             );
 
                             spawn_start_menu_button(
                                 parent,
                                 StartMenuButtonOptions {
-                                    visibility: Visibility::Hidden,
-                                    text: "Fire water",
+                                    text: "Settings",
                                     button_style: &button_style,
                                     icon_style: &button_icon_style,
                                     icon: &game_images.joystick,
-                                    menu_action: MenuAction::FireWater,
+                                    menu_action: MenuAction::Settings,
+                                    selected_value: selected_option.value,
+                                    number_of_rulesets: rulesets.0.len(),
                                     font,
-                                }
+                                },
                             );
 
                             spawn_start_menu_button(
                                 parent,
                                 StartMenuButtonOptions {
-                                visibility: Visibility::Hidden,
-                                    text: "Settings",
+                                    text: "Statistics",
                                     button_style: &button_style,
                                     icon_style: &button_icon_style,
                                     icon: &game_images.joystick,
-                                    menu_action: MenuAction::Settings,
+                                    menu_action: MenuAction::Statistics,
+                                    selected_value: selected_option.value,
+                                    number_of_rulesets: rulesets.0.len(),
                                     font,
-                                }
+                                },
                             );
 
                             spawn_start_menu_button(
                                 parent,
                                 StartMenuButtonOptions {
-                                    visibility: Visibility::Hidden,
                                     text: "Credits",
                                     button_style: &button_style,
                                     icon_style: &button_icon_style,
                                     icon: &game_images.joystick,
                                     menu_action: MenuAction::Credits,
+                                    selected_value: selected_option.value,
+                                    number_of_rulesets: rulesets.0.len(),
                                     font,
-                                }
+                                },
                             );
 
                             spawn_start_menu_button(
                                 parent,
                                 StartMenuButtonOptions {
-                                    visibility: Visibility::Hidden,
                                     text: "Exit",
                                     button_style: &button_style,
                                     icon_style: &button_icon_style,
                                     icon: &game_images.joystick,
                                     menu_action: MenuAction::Exit,
+                                    selected_value: selected_option.value,
+                                    number_of_rulesets: rulesets.0.len(),
                                     font,
-                                }
+                                },
                             );
+
+                            spawn_button(parent, "(I)nfo about selected game", KeyCode::I, font);
                         });
                 });
         });
@@ -254,7 +395,7 @@ pub fn setup_start_menu(mut commands: Commands, game_font: Res<GameFont>, game_i
 pub fn setup_setting_menu(mut commands: Commands, game_font: Res<GameFont>, game_images: Res<GameImages>, game_settings: Res<GameSettings>) {
     let button_style = Style {
         flex_direction: FlexDirection::Row,
-        width: Val::Px(300.0),
+        width: Val::Px(700.0),
         height: Val::Px(40.0),
         margin: UiRect::all(Val::Px(10.0)),
         justify_content: JustifyContent::Start,
@@ -342,107 +483,27 @@ pub fn setup_setting_menu(mut commands: Commands, game_font: Res<GameFont>, game
                                 }),
                             );
 
-                            parent
-                                .spawn(NodeBundle {
-                                    style: button_style.clone(),
-                                    ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        ImageBundle {
-                                            style: button_icon_style.clone(),
-                                            image: UiImage::new(game_images.joystick.clone()),
-                                            visibility: Visibility::Visible,
-                                            ..default()
-                                        },
-                                        SettingAction::Sound,
-                                    ));
-
-                                    parent.spawn((
-                                        TextBundle::from_section(
-                                            "Sound (".to_string() + if game_settings.is_sound_on { "On" } else { "Off" } + ")",
-                                            TextStyle {
-                                                font_size: BUTTON_TEXT_SIZE,
-                                                color: BUTTON_TITLE_COLOR,
-                                                font: font.clone(),
-                                            },
-                                        )
-                                        .with_style(Style {
-                                            margin: UiRect::left(Val::Px(10.0)),
-                                            ..default()
-                                        }),
-                                        OnGameSound,
-                                    ));
-                                });
-
-                            parent
-                                .spawn(NodeBundle {
-                                    style: button_style.clone(),
-                                    ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        ImageBundle {
-                                            style: button_icon_style.clone(),
-                                            image: UiImage::new(game_images.joystick.clone()),
-                                            visibility: Visibility::Hidden,
-                                            ..default()
-                                        },
-                                        SettingAction::ChangeName,
-                                    ));
-
-                                    parent.spawn(
-                                        TextBundle::from_section(
-                                            "Change name",
-                                            TextStyle {
-                                                font_size: BUTTON_TEXT_SIZE,
-                                                color: BUTTON_TITLE_COLOR,
-                                                font: font.clone(),
-                                            },
-                                        )
-                                        .with_style(Style {
-                                            margin: UiRect::left(Val::Px(10.0)),
-                                            ..default()
-                                        }),
-                                    );
-                                });
-
-                            parent
-                                .spawn(NodeBundle {
-                                    style: button_style.clone(),
-                                    ..default()
-                                })
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        ImageBundle {
-                                            style: button_icon_style.clone(),
-                                            image: UiImage::new(game_images.joystick.clone()),
-                                            visibility: Visibility::Hidden,
-                                            ..default()
-                                        },
-                                        SettingAction::Back,
-                                    ));
-                                    parent.spawn(
-                                        TextBundle::from_section(
-                                            "Back",
-                                            TextStyle {
-                                                font_size: BUTTON_TEXT_SIZE,
-                                                color: BUTTON_TITLE_COLOR,
-                                                font: font.clone(),
-                                            },
-                                        )
-                                        .with_style(Style {
-                                            margin: UiRect::left(Val::Px(10.0)),
-                                            ..default()
-                                        }),
-                                    );
-                                });
+                            for setting_action in SettingAction::ALL {
+                                spawn_setting_menu_button(
+                                    parent,
+                                    SettingMenuButtonOptions {
+                                        text: setting_action.get_text(&game_settings),
+                                        button_style: &button_style,
+                                        icon_style: &button_icon_style,
+                                        icon: &game_images.joystick,
+                                        setting_action,
+                                        selected_value: 1,
+                                        font,
+                                    },
+                                );
+                            }
                         });
                 });
         });
 }
 
-pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>, game_settings: Res<GameSettings>) {
+pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>, mut game_settings: ResMut<GameSettings>, edited_player: Res<EditedPlayer>) {
+    let name = edited_player.get_player_options(&mut game_settings).name.clone();
     let button_style = Style {
         flex_direction: FlexDirection::Row,
         width: Val::Percent(90.0),
@@ -507,7 +568,7 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
                         .with_children(|parent| {
                             parent.spawn((
                                 TextBundle::from_section(
-                                    game_settings.player_options.name.clone(),
+                                    name,
                                     TextStyle {
                                         font_size: BUTTON_TEXT_SIZE,
                                         color: BUTTON_TITLE_COLOR,
@@ -535,7 +596,7 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
                         .with_children(|parent| {
                             parent
                                 .spawn((
-                                    NodeBundle {
+                                    ButtonBundle {
                                         style: Style {
                                             width: Val::Px(250.0),
                                             height: Val::Px(50.0),
@@ -546,9 +607,11 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
                                             ..default()
                                         },
                                         border_color: GAME_NO_SELECTED_BORDER_COLOR.into(),
+                                        background_color: Color::NONE.into(),
                                         ..default()
                                     },
                                     SaveCancelAction::Cancel,
+                                    HotKey(KeyCode::Return),
                                 ))
                                 .with_children(|parent| {
                                     parent.spawn(
@@ -568,7 +631,7 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
                                 });
                             parent
                                 .spawn((
-                                    NodeBundle {
+                                    ButtonBundle {
                                         style: Style {
                                             width: Val::Px(250.0),
                                             height: Val::Px(50.0),
@@ -579,9 +642,11 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
                                             ..default()
                                         },
                                         border_color: GAME_SELECTED_BORDER_COLOR.into(),
+                                        background_color: Color::NONE.into(),
                                         ..default()
                                     },
                                     SaveCancelAction::Save,
+                                    HotKey(KeyCode::Return),
                                 ))
                                 .with_children(|parent| {
                                     parent.spawn(
@@ -607,30 +672,36 @@ pub fn setup_change_name_screen(mut commands: Commands, game_font: Res<GameFont>
 }
 
 struct StartMenuButtonOptions<'a> {
-    visibility: Visibility,
     text: &'a str,
     button_style: &'a Style,
     icon_style: &'a Style,
     icon: &'a Handle<Image>,
     menu_action: MenuAction,
+    selected_value: i32,
+    number_of_rulesets: usize,
     font: &'a Handle<Font>,
 }
 
-fn spawn_start_menu_button(
-    parent: &mut ChildBuilder,
-    options: StartMenuButtonOptions
-) {
+fn spawn_start_menu_button(parent: &mut ChildBuilder, options: StartMenuButtonOptions) {
     parent
-        .spawn(NodeBundle {
-            style: options.button_style.clone(),
-            ..default()
-        })
+        .spawn((
+            ButtonBundle {
+                style: options.button_style.clone(),
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            OptionButton(options.menu_action.get_option_value(options.number_of_rulesets)),
+        ))
         .with_children(|parent| {
             parent.spawn((
                 ImageBundle {
                     style: options.icon_style.clone(),
                     image: UiImage::new(options.icon.clone()),
-                    visibility: options.visibility,
+                    visibility: if options.menu_action.get_option_value(options.number_of_rulesets) == options.selected_value {
+                        Visibility::Visible
+                    } else {
+                        Visibility::Hidden
+                    },
                     ..default()
                 },
                 options.menu_action,
@@ -652,87 +723,99 @@ fn spawn_start_menu_button(
         });
 }
 
+struct SettingMenuButtonOptions<'a> {
+    text: String,
+    button_style: &'a Style,
+    icon_style: &'a Style,
+    icon: &'a Handle<Image>,
+    setting_action: SettingAction,
+    selected_value: i32,
+    font: &'a Handle<Font>,
+}
+
+fn spawn_setting_menu_button(parent: &mut ChildBuilder, options: SettingMenuButtonOptions) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: options.button_style.clone(),
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            OptionButton(options.setting_action.get_option_value()),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: options.icon_style.clone(),
+                    image: UiImage::new(options.icon.clone()),
+                    visibility: if options.setting_action.get_option_value() == options.selected_value {
+                        Visibility::Visible
+                    } else {
+                        Visibility::Hidden
+                    },
+                    ..default()
+                },
+                options.setting_action,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    options.text,
+                    TextStyle {
+                        font_size: BUTTON_TEXT_SIZE,
+                        color: BUTTON_TITLE_COLOR,
+                        font: options.font.clone(),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::left(Val::Px(10.0)),
+                    ..default()
+                }),
+                OnSettingText(options.setting_action),
+            ));
+        });
+}
+
 pub fn switch_start_menu_action(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut game_type: ResMut<GameType>,
+    actions: Res<Actions>,
     mut query: Query<(&mut Visibility, &MenuAction), With<MenuAction>>,
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
     mut selected_option: ResMut<SelectedOption>,
     game_settings: Res<GameSettings>,
+    rulesets: Res<Rulesets>,
 ) {
+    let number_of_rulesets = rulesets.0.len();
     let mut up_or_down = false;
-    if keyboard_input.just_pressed(KeyCode::Up) {
+    if actions.just_pressed(KeyCode::Up) {
         up_or_down = true;
 
         if selected_option.value > 1 {
             selected_option.value -= 1;
         }
-
-        *game_type = selected_option.value.into();
-    } else if keyboard_input.just_pressed(KeyCode::Down) {
+    } else if actions.just_pressed(KeyCode::Down) {
         up_or_down = true;
 
-        if selected_option.value < 6 {
+        if selected_option.value < MenuAction::Exit.get_option_value(number_of_rulesets) {
             selected_option.value += 1;
         }
-
-        *game_type = selected_option.value.into();
     }
-    if up_or_down {
+    // Clicked item is selected too.
+    if selected_option.is_changed() {
         for (mut visibility, menu_action) in &mut query {
-            match menu_action {
-                MenuAction::Normal => {
-                    if *game_type == GameType::Normal {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                MenuAction::SpockLizard => {
-                    if *game_type == GameType::SpockLizard {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                MenuAction::FireWater => {
-                    if *game_type == GameType::FireWater {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                MenuAction::Settings => {
-                    if selected_option.value == 4 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                MenuAction::Credits => {
-                    if selected_option.value == 5 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                MenuAction::Exit => {
-                    if selected_option.value == 6 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
+            if menu_action.get_option_value(number_of_rulesets) == selected_option.value {
+                *visibility = Visibility::Visible;
+            } else {
+                *visibility = Visibility::Hidden;
             }
         }
-
+    }
+    if up_or_down {
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
     }
 }
 
 pub fn switch_settings_menu_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut query: Query<(&mut Visibility, &SettingAction), With<SettingAction>>,
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
@@ -740,78 +823,105 @@ pub fn switch_settings_menu_action(
     game_settings: Res<GameSettings>,
 ) {
     let mut up_or_down = false;
-    if keyboard_input.just_pressed(KeyCode::Up) {
+    if actions.just_pressed(KeyCode::Up) {
         up_or_down = true;
 
         if selected_option.value > 1 {
             selected_option.value -= 1;
         }
-    } else if keyboard_input.just_pressed(KeyCode::Down) {
+    } else if actions.just_pressed(KeyCode::Down) {
         up_or_down = true;
 
-        if selected_option.value < 3 {
+        if selected_option.value < SettingAction::ALL.len() as i32 {
             selected_option.value += 1;
         }
     }
-    if up_or_down {
+    if selected_option.is_changed() {
         for (mut visibility, setting_action) in &mut query {
-            match setting_action {
-                SettingAction::Sound => {
-                    if selected_option.value == 1 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                SettingAction::ChangeName => {
-                    if selected_option.value == 2 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
-                SettingAction::Back => {
-                    if selected_option.value == 3 {
-                        *visibility = Visibility::Visible;
-                    } else {
-                        *visibility = Visibility::Hidden;
-                    }
-                }
+            if setting_action.get_option_value() == selected_option.value {
+                *visibility = Visibility::Visible;
+            } else {
+                *visibility = Visibility::Hidden;
             }
         }
+    }
+    if up_or_down {
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_settings_menu_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut selected_option: ResMut<SelectedOption>,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_settings: ResMut<GameSettings>,
-    mut query: Query<&mut Text, With<OnGameSound>>,
+    mut query: Query<(&mut Text, &OnSettingText)>,
+    mut edited_player: ResMut<EditedPlayer>,
+    game_rng: Res<GameRng>,
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
 ) {
-    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+    if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
         debug!("Menu from Settings to Start menu.");
-        if selected_option.value == 1 {
-            game_settings.is_sound_on = !game_settings.is_sound_on;
-            let mut text = query.single_mut();
-            text.sections[0].value = "Sound (".to_string() + if game_settings.is_sound_on { "On" } else { "Off" } + ")";
-            game_settings.fetch();
-        } else if selected_option.value == 2 {
-            selected_option.set_value(1);
-            menu_state.set(MenuState::ChangeName);
-        } else if selected_option.value == 3 {
-            selected_option.set_value(1);
-            menu_state.set(MenuState::StartMenu);
+        match SettingAction::from_option_value(selected_option.value) {
+            Some(SettingAction::Sound) => {
+                game_settings.is_sound_on = !game_settings.is_sound_on;
+                game_settings.fetch();
+            }
+            Some(SettingAction::MatchFormat) => {
+                game_settings.match_format = game_settings.match_format.next();
+                game_settings.fetch();
+            }
+            Some(SettingAction::RoundTimer) => {
+                game_settings.round_time_limit = next_time_limit(game_settings.round_time_limit);
+                game_settings.fetch();
+            }
+            Some(SettingAction::TimeoutAction) => {
+                game_settings.timeout_action = game_settings.timeout_action.next();
+                game_settings.fetch();
+            }
+            Some(SettingAction::TargetWinRate) => {
+                game_settings.target_win_rate = game_settings.next_target_win_rate();
+                game_settings.fetch();
+            }
+            Some(SettingAction::Seed) => {
+                game_settings.seed = match game_settings.seed {
+                    Some(_) => None,
+                    None => Some(game_rng.seed),
+                };
+                game_settings.fetch();
+            }
+            Some(SettingAction::Watchable) => {
+                game_settings.is_watchable = !game_settings.is_watchable;
+                game_settings.fetch();
+            }
+            Some(SettingAction::ChangeName) => {
+                *edited_player = EditedPlayer::First;
+                selected_option.set_value(1);
+                menu_state.set(MenuState::ChangeName);
+            }
+            Some(SettingAction::ChangeSecondName) => {
+                *edited_player = EditedPlayer::Second;
+                selected_option.set_value(1);
+                menu_state.set(MenuState::ChangeName);
+            }
+            Some(SettingAction::Back) => {
+                selected_option.set_value(1);
+                menu_state.set(MenuState::StartMenu);
+            }
+            None => {}
+        }
+        for (mut text, setting_text) in &mut query {
+            text.sections[0].value = setting_text.0.get_text(&game_settings);
         }
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn confirm_start_menu_action(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Actions>,
     mut selected_option: ResMut<SelectedOption>,
     mut app_state: ResMut<NextState<AppState>>,
     mut menu_state: ResMut<NextState<MenuState>>,
@@ -819,33 +929,54 @@ pub fn confirm_start_menu_action(
     audio: Res<Audio>,
     game_sounds: Res<GameSounds>,
     game_settings: ResMut<GameSettings>,
+    rulesets: Res<Rulesets>,
 ) {
-    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
-        if selected_option.value == 1 {
-            selected_option.set_value(1);
-            *game_type = GameType::Normal;
-            menu_state.set(MenuState::NotInit);
-            app_state.set(AppState::Playing);
-        } else if selected_option.value == 2 {
-            selected_option.set_value(1);
-            *game_type = GameType::SpockLizard;
-            menu_state.set(MenuState::NotInit);
-            app_state.set(AppState::Playing);
-        } else if selected_option.value == 3 {
-            selected_option.set_value(1);
-            *game_type = GameType::FireWater;
+    if actions.just_pressed(KeyCode::I) {
+        if let Some(MenuAction::Play(x)) = MenuAction::from_option_value(selected_option.value, rulesets.0.len()) {
+            *game_type = GameType::Ruleset(rulesets.0[x].clone());
             menu_state.set(MenuState::NotInit);
-            app_state.set(AppState::Playing);
-        } else if selected_option.value == 4 {
-            selected_option.set_value(1);
-            menu_state.set(MenuState::SettingsMenu);
-        } else if selected_option.value == 5 {
-            selected_option.set_value(1);
-            menu_state.set(MenuState::NotInit);
-            app_state.set(AppState::Credits);
-        } else if selected_option.value == 6 {
-            menu_state.set(MenuState::NotInit);
-            app_state.set(AppState::Closing);
+            app_state.set(AppState::RulesetInfo);
+
+            play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        }
+    } else if actions.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        match MenuAction::from_option_value(selected_option.value, rulesets.0.len()) {
+            Some(MenuAction::Play(x)) => {
+                *game_type = GameType::Ruleset(rulesets.0[x].clone());
+                menu_state.set(MenuState::NotInit);
+                if let Err(e) = rulesets.0[x].validate() {
+                    warn!("Ruleset {} cannot be played: {}", rulesets.0[x].id, e);
+                    app_state.set(AppState::RulesetInfo);
+
+                    play_sound(&audio, game_settings.is_sound_on, &game_sounds.lose);
+                    return;
+                }
+                app_state.set(AppState::MatchSetup);
+            }
+            Some(MenuAction::Lan) => {
+                selected_option.set_value(1);
+                menu_state.set(MenuState::NotInit);
+                app_state.set(AppState::Lan);
+            }
+            Some(MenuAction::Settings) => {
+                selected_option.set_value(1);
+                menu_state.set(MenuState::SettingsMenu);
+            }
+            Some(MenuAction::Statistics) => {
+                selected_option.set_value(1);
+                menu_state.set(MenuState::NotInit);
+                app_state.set(AppState::Statistics);
+            }
+            Some(MenuAction::Credits) => {
+                selected_option.set_value(1);
+                menu_state.set(MenuState::NotInit);
+                app_state.set(AppState::Credits);
+            }
+            Some(MenuAction::Exit) => {
+                menu_state.set(MenuState::NotInit);
+                app_state.set(AppState::Closing);
+            }
+            None => {}
         }
 
         play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
@@ -857,8 +988,8 @@ pub struct OnKeyPressEvent {
     pub key_code: KeyCode,
 }
 
-pub fn on_key_press_event_trigger(keyboard_input: Res<Input<KeyCode>>, mut on_key_press_events: EventWriter<OnKeyPressEvent>) {
-    for just_pressed in keyboard_input.get_just_pressed() {
+pub fn on_key_press_event_trigger(actions: Res<Actions>, mut on_key_press_events: EventWriter<OnKeyPressEvent>) {
+    for just_pressed in actions.get_just_pressed() {
         debug!("{:?}", just_pressed);
         on_key_press_events.send(OnKeyPressEvent { key_code: *just_pressed });
     }
@@ -875,6 +1006,26 @@ pub fn on_blinking_text_indicator(mut query: Query<&mut Text, With<OnChangeName>
     }
 }
 
+// Clicked Save or Cancel is selected before its ENTER is handled.
+pub fn select_save_cancel_action(
+    query_interaction: Query<(&Interaction, &SaveCancelAction), Changed<Interaction>>,
+    mut query_border: Query<(&mut BorderColor, &SaveCancelAction)>,
+) {
+    for (interaction, clicked_action) in &query_interaction {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        for (mut border_color, action) in &mut query_border {
+            *border_color = if action == clicked_action {
+                GAME_SELECTED_BORDER_COLOR.into()
+            } else {
+                GAME_NO_SELECTED_BORDER_COLOR.into()
+            };
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn on_key_press_event_listener(
     mut on_key_press_events: EventReader<OnKeyPressEvent>,
     mut query: Query<&mut Text, With<OnChangeName>>,
@@ -883,13 +1034,14 @@ pub fn on_key_press_event_listener(
     game_sounds: Res<GameSounds>,
     mut game_settings: ResMut<GameSettings>,
     mut menu_state: ResMut<NextState<MenuState>>,
+    edited_player: Res<EditedPlayer>,
 ) {
     for event in on_key_press_events.iter() {
         debug!("{:?}", event);
 
-        if vec![KeyCode::Left, KeyCode::Right, KeyCode::Space, KeyCode::Return].contains(&event.key_code) {
+        if [KeyCode::Left, KeyCode::Right, KeyCode::Space, KeyCode::Return].contains(&event.key_code) {
             for (mut border_color, action) in &mut query_border {
-                if vec![KeyCode::Left, KeyCode::Right].contains(&event.key_code) {
+                if [KeyCode::Left, KeyCode::Right].contains(&event.key_code) {
                     if border_color.0 == GAME_SELECTED_BORDER_COLOR {
                         *border_color = GAME_NO_SELECTED_BORDER_COLOR.into();
                     } else {
@@ -899,7 +1051,7 @@ pub fn on_key_press_event_listener(
                     if let SaveCancelAction::Save = action {
                         if border_color.0 == GAME_SELECTED_BORDER_COLOR {
                             let text = query.single();
-                            game_settings.player_options.name = text.sections[0].value.replace('|', "").clone();
+                            edited_player.get_player_options(&mut game_settings).name = text.sections[0].value.replace('|', "").clone();
                             game_settings.fetch();
                         }
                     }
@@ -0,0 +1,147 @@
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_type::GameType;
+
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equilibrium {
+    pub row_strategy: Vec<f64>,
+    pub column_strategy: Vec<f64>,
+    pub value: f64,
+}
+
+impl Equilibrium {
+    pub fn for_game_type(game_type: &GameType) -> Self {
+        Equilibrium::solve(&payoff_matrix(game_type))
+    }
+
+    // Solves the zero-sum matrix game with the simplex method. Payoffs are shifted to be positive, so the column player's
+    // problem becomes: maximize sum(y) subject to A * y <= 1, y >= 0. The row player's strategy is read from the dual
+    // values of the slack variables in the final tableau.
+    pub fn solve(matrix: &[Vec<f64>]) -> Self {
+        let rows = matrix.len();
+        let columns = matrix[0].len();
+        let min = matrix.iter().flatten().fold(f64::INFINITY, |x, y| x.min(*y));
+        let shift = 1.0 - min;
+
+        let width = columns + rows + 1;
+        let mut tableau = vec![vec![0.0; width]; rows + 1];
+        for (row, values) in matrix.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                tableau[row][column] = value + shift;
+            }
+            tableau[row][columns + row] = 1.0;
+            tableau[row][width - 1] = 1.0;
+        }
+        tableau[rows][..columns].fill(-1.0);
+        let mut basis: Vec<usize> = (columns..columns + rows).collect();
+
+        // Bland's rule: the lowest entering and leaving indices, so degenerate games like RPS cannot cycle.
+        while let Some(entering) = (0..width - 1).find(|x| tableau[rows][*x] < -EPSILON) {
+            let leaving = (0..rows).filter(|x| tableau[*x][entering] > EPSILON).min_by(|x, y| {
+                let x_ratio = tableau[*x][width - 1] / tableau[*x][entering];
+                let y_ratio = tableau[*y][width - 1] / tableau[*y][entering];
+                if (x_ratio - y_ratio).abs() < EPSILON {
+                    basis[*x].cmp(&basis[*y])
+                } else {
+                    x_ratio.total_cmp(&y_ratio)
+                }
+            });
+            let Some(leaving) = leaving else {
+                panic!("Matrix game is unbounded.");
+            };
+
+            let pivot = tableau[leaving][entering];
+            for value in tableau[leaving].iter_mut() {
+                *value /= pivot;
+            }
+            let pivot_row = tableau[leaving].clone();
+            for (row, values) in tableau.iter_mut().enumerate() {
+                let factor = values[entering];
+                if row != leaving && factor.abs() > EPSILON {
+                    for (value, pivot_value) in values.iter_mut().zip(&pivot_row) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+            basis[leaving] = entering;
+        }
+
+        let mut column_strategy = vec![0.0; columns];
+        for (row, variable) in basis.iter().enumerate() {
+            if *variable < columns {
+                column_strategy[*variable] = tableau[row][width - 1];
+            }
+        }
+        let total = tableau[rows][width - 1];
+        let row_strategy = (0..rows).map(|x| tableau[rows][columns + x] / total).collect();
+        let column_strategy = column_strategy.iter().map(|x| x / total).collect();
+
+        Self {
+            row_strategy,
+            column_strategy,
+            value: 1.0 / total - shift,
+        }
+    }
+}
+
+pub fn payoff_matrix(game_type: &GameType) -> Vec<Vec<f64>> {
+    let number_of_moves = game_type.ruleset().moves.len();
+    (0..number_of_moves)
+        .map(|first| {
+            (0..number_of_moves)
+                .map(|second| match GameMove(first).beats_other(game_type, &GameMove(second)) {
+                    GameResult::Win => 1.0,
+                    GameResult::Lose => -1.0,
+                    GameResult::Draw => 0.0,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    fn assert_strategy(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (x, y) in actual.iter().zip(expected) {
+            assert!((x - y).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_balanced_rulesets_are_uniform() {
+        for (id, number_of_moves) in [("normal", 3), ("spock_lizard", 5), ("rps_7", 7), ("rps_101", 101)] {
+            let equilibrium = Equilibrium::for_game_type(&bundled_game_type(id));
+            let uniform = vec![1.0 / number_of_moves as f64; number_of_moves];
+            assert_strategy(&equilibrium.row_strategy, &uniform);
+            assert_strategy(&equilibrium.column_strategy, &uniform);
+            assert!(equilibrium.value.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_fire_water() {
+        let game_type = bundled_game_type("fire_water");
+        let equilibrium = Equilibrium::for_game_type(&game_type);
+        let third = 1.0 / 3.0;
+        // Rock, Paper, Scissors, Fire, Water: dominated Rock and Scissors are never played.
+        assert_strategy(&equilibrium.row_strategy, &[0.0, third, 0.0, third, third]);
+        assert_strategy(&equilibrium.column_strategy, &[0.0, third, 0.0, third, third]);
+        assert!(equilibrium.value.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_asymmetric_game() {
+        // Matching pennies with a bonus for heads/heads.
+        let equilibrium = Equilibrium::solve(&[vec![3.0, -1.0], vec![-1.0, 1.0]]);
+        assert_strategy(&equilibrium.row_strategy, &[1.0 / 3.0, 2.0 / 3.0]);
+        assert_strategy(&equilibrium.column_strategy, &[1.0 / 3.0, 2.0 / 3.0]);
+        assert!((equilibrium.value - 1.0 / 3.0).abs() < 1e-6);
+    }
+}
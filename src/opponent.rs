@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::adaptive::AdaptiveStrategy;
+use crate::common::Round;
+use crate::game_move::GameMove;
+use crate::game_type::GameType;
+use crate::iocaine::IocaineStrategy;
+use crate::strategy::*;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Opponent {
+    #[default]
+    Random,
+    Optimal,
+    Frequency,
+    Markov(usize),
+    BeatLast,
+    Iocaine,
+    Adaptive,
+}
+
+impl Opponent {
+    pub const ALL: [Opponent; 9] = [
+        Opponent::Random,
+        Opponent::Optimal,
+        Opponent::Frequency,
+        Opponent::Markov(1),
+        Opponent::Markov(2),
+        Opponent::Markov(3),
+        Opponent::BeatLast,
+        Opponent::Iocaine,
+        Opponent::Adaptive,
+    ];
+
+    pub fn get_friendly_name(&self) -> String {
+        match self {
+            Opponent::Random => String::from("Random"),
+            Opponent::Optimal => String::from("Optimal"),
+            Opponent::Frequency => String::from("Frequency counter"),
+            Opponent::Markov(x) => format!("Markov chain (order {})", x),
+            Opponent::BeatLast => String::from("Beat last move"),
+            Opponent::Iocaine => String::from("Iocaine Powder (hard)"),
+            Opponent::Adaptive => String::from("Adaptive"),
+        }
+    }
+
+    pub fn get_option_value(&self) -> i32 {
+        Opponent::ALL.iter().position(|x| x == self).map(|x| x as i32 + 1).unwrap_or(1)
+    }
+
+    pub fn from_option_value(value: i32) -> Option<Opponent> {
+        if value >= 1 {
+            Opponent::ALL.get(value as usize - 1).copied()
+        } else {
+            None
+        }
+    }
+
+    pub fn create_strategy(&self, target_win_rate: u32) -> Box<dyn Strategy> {
+        match self {
+            Opponent::Random => Box::new(UniformStrategy),
+            Opponent::Optimal => Box::<OptimalStrategy>::default(),
+            Opponent::Frequency => Box::<FrequencyStrategy>::default(),
+            Opponent::Markov(x) => Box::new(MarkovStrategy::new(*x)),
+            Opponent::BeatLast => Box::<BeatLastStrategy>::default(),
+            Opponent::Iocaine => Box::<IocaineStrategy>::default(),
+            Opponent::Adaptive => Box::new(AdaptiveStrategy::new(target_win_rate as f64 / 100.0)),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ComputerOpponent {
+    pub opponent: Opponent,
+    strategy: Box<dyn Strategy>,
+}
+
+impl ComputerOpponent {
+    pub fn new(opponent: Opponent, target_win_rate: u32) -> Self {
+        Self {
+            opponent,
+            strategy: opponent.create_strategy(target_win_rate),
+        }
+    }
+
+    pub fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        self.strategy.get_move(history, game_type, rng)
+    }
+
+    pub fn reset(&mut self) {
+        self.strategy.reset();
+    }
+
+    pub fn get_difficulty(&self) -> Option<usize> {
+        self.strategy.get_difficulty()
+    }
+
+    pub fn get_explanation(&self) -> Option<&Explanation> {
+        self.strategy.get_explanation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_value() {
+        for opponent in Opponent::ALL {
+            assert_eq!(Opponent::from_option_value(opponent.get_option_value()), Some(opponent));
+        }
+        assert_eq!(Opponent::from_option_value(0), None);
+        assert_eq!(Opponent::from_option_value(Opponent::ALL.len() as i32 + 1), None);
+        assert_eq!(Opponent::Markov(7).get_option_value(), 1);
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use crate::common::Round;
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_type::GameType;
+
+const MIN_ROUNDS: usize = 10;
+const MIN_HABIT_OCCURRENCES: usize = 4;
+const HABIT_MARGIN: f64 = 0.3;
+const TIP_THRESHOLD: f64 = 0.85;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entropy {
+    pub bits: f64,
+    // Entropy compared to the highest entropy possible with the same number of moves and rounds, 1.0 is fully random.
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Habit {
+    SwitchToWinnerAfterLoss,
+    StayAfterWin,
+    Repeat,
+    BeatOwnLast,
+    CopyComputer,
+}
+
+impl Habit {
+    pub const ALL: [Habit; 5] = [
+        Habit::SwitchToWinnerAfterLoss,
+        Habit::StayAfterWin,
+        Habit::Repeat,
+        Habit::BeatOwnLast,
+        Habit::CopyComputer,
+    ];
+
+    pub fn get_description(&self) -> &str {
+        match self {
+            Habit::SwitchToWinnerAfterLoss => "After a loss you switch to a move that beats what beat you",
+            Habit::StayAfterWin => "After a win you play the same move again",
+            Habit::Repeat => "You repeat your last move",
+            Habit::BeatOwnLast => "You play a move that beats your last move",
+            Habit::CopyComputer => "You copy the computer's last move",
+        }
+    }
+
+    fn applies(&self, game_type: &GameType, previous: &Round) -> bool {
+        let result = previous.player_move.beats_other(game_type, &previous.computer_move);
+        match self {
+            Habit::SwitchToWinnerAfterLoss => result == GameResult::Lose,
+            Habit::StayAfterWin => result == GameResult::Win,
+            Habit::Repeat | Habit::BeatOwnLast | Habit::CopyComputer => true,
+        }
+    }
+
+    fn matches(&self, game_type: &GameType, previous: &Round, player_move: &GameMove) -> bool {
+        match self {
+            Habit::SwitchToWinnerAfterLoss => player_move.beats_other(game_type, &previous.computer_move) == GameResult::Win,
+            Habit::StayAfterWin | Habit::Repeat => *player_move == previous.player_move,
+            Habit::BeatOwnLast => player_move.beats_other(game_type, &previous.player_move) == GameResult::Win,
+            Habit::CopyComputer => *player_move == previous.computer_move,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HabitScore {
+    pub habit: Habit,
+    pub occurrences: usize,
+    pub matches: usize,
+    // How many matches a player picking moves at random would have.
+    pub expected: f64,
+}
+
+impl HabitScore {
+    // Share of occurrences matched above what random moves would match.
+    fn get_excess(&self) -> f64 {
+        (self.matches as f64 - self.expected) / self.occurrences as f64
+    }
+
+    pub fn is_detected(&self) -> bool {
+        self.occurrences >= MIN_HABIT_OCCURRENCES && self.get_excess() >= HABIT_MARGIN
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predictability {
+    pub rounds: usize,
+    pub entropy: Option<Entropy>,
+    pub entropy_given_move: Option<Entropy>,
+    pub entropy_given_result: Option<Entropy>,
+    pub habits: Vec<HabitScore>,
+    most_frequent: Option<(GameMove, usize)>,
+}
+
+fn get_entropy(counts: &[usize]) -> f64 {
+    let total: usize = counts.iter().sum();
+    counts
+        .iter()
+        .filter(|x| **x > 0)
+        .map(|x| {
+            let p = *x as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// Entropy of moves within every context, weighted by how often the context happened.
+fn get_conditional_entropy(samples: &[(usize, GameMove)], number_of_moves: usize) -> Option<Entropy> {
+    let mut contexts: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (context, game_move) in samples {
+        contexts.entry(*context).or_insert_with(|| vec![0; number_of_moves])[game_move.0] += 1;
+    }
+    let mut bits = 0.0;
+    let mut max_bits = 0.0;
+    for counts in contexts.values() {
+        let total: usize = counts.iter().sum();
+        let weight = total as f64 / samples.len() as f64;
+        bits += weight * get_entropy(counts);
+        max_bits += weight * (total.min(number_of_moves) as f64).log2();
+    }
+    if max_bits <= 0.0 {
+        return None;
+    }
+    Some(Entropy { bits, ratio: bits / max_bits })
+}
+
+fn get_result_index(result: GameResult) -> usize {
+    match result {
+        GameResult::Win => 0,
+        GameResult::Lose => 1,
+        GameResult::Draw => 2,
+    }
+}
+
+impl Predictability {
+    pub fn new(history: &[Round], game_type: &GameType) -> Self {
+        let number_of_moves = game_type.max_number_of_moves() as usize;
+        let moves: Vec<(usize, GameMove)> = history.iter().map(|x| (0, x.player_move)).collect();
+        let given_move: Vec<(usize, GameMove)> = history.windows(2).map(|x| (x[0].player_move.0, x[1].player_move)).collect();
+        let given_result: Vec<(usize, GameMove)> = history
+            .windows(2)
+            .map(|x| {
+                let result = x[0].player_move.beats_other(game_type, &x[0].computer_move);
+                (get_result_index(result), x[1].player_move)
+            })
+            .collect();
+
+        let habits = Habit::ALL
+            .iter()
+            .map(|habit| {
+                let mut score = HabitScore {
+                    habit: *habit,
+                    occurrences: 0,
+                    matches: 0,
+                    expected: 0.0,
+                };
+                for x in history.windows(2).filter(|x| habit.applies(game_type, &x[0])) {
+                    score.occurrences += 1;
+                    if habit.matches(game_type, &x[0], &x[1].player_move) {
+                        score.matches += 1;
+                    }
+                    let matching_moves = (0..number_of_moves).filter(|m| habit.matches(game_type, &x[0], &GameMove(*m))).count();
+                    score.expected += matching_moves as f64 / number_of_moves as f64;
+                }
+                score
+            })
+            .collect();
+
+        let mut counts = vec![0; number_of_moves];
+        for x in history {
+            counts[x.player_move.0] += 1;
+        }
+        // Ties go to the lowest move, so that the result does not depend on iteration order.
+        let most_frequent = counts
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, x)| **x)
+            .filter(|(_, x)| **x > 0)
+            .map(|(i, x)| (GameMove(i), *x));
+
+        Self {
+            rounds: history.len(),
+            entropy: get_conditional_entropy(&moves, number_of_moves),
+            entropy_given_move: get_conditional_entropy(&given_move, number_of_moves),
+            entropy_given_result: get_conditional_entropy(&given_result, number_of_moves),
+            habits,
+            most_frequent,
+        }
+    }
+
+    // 0 is fully random, 100 means the next move can always be guessed from the lowest of the three entropies.
+    pub fn get_score(&self) -> Option<u32> {
+        if self.rounds < MIN_ROUNDS {
+            return None;
+        }
+        [self.entropy, self.entropy_given_move, self.entropy_given_result]
+            .iter()
+            .flatten()
+            .map(|x| x.ratio)
+            .min_by(|x, y| x.total_cmp(y))
+            .map(|x| ((1.0 - x.clamp(0.0, 1.0)) * 100.0).round() as u32)
+    }
+
+    pub fn get_detected_habits(&self) -> Vec<&HabitScore> {
+        let mut habits: Vec<&HabitScore> = self.habits.iter().filter(|x| x.is_detected()).collect();
+        habits.sort_by(|x, y| y.get_excess().total_cmp(&x.get_excess()));
+        habits
+    }
+
+    pub fn get_tips(&self, game_type: &GameType) -> Vec<String> {
+        if self.rounds < MIN_ROUNDS {
+            return vec![format!("Play at least {} rounds to measure how predictable you are", MIN_ROUNDS)];
+        }
+        let is_low = |x: Option<Entropy>| x.is_some_and(|x| x.ratio < TIP_THRESHOLD);
+        let mut tips = Vec::new();
+        if let (true, Some((game_move, count))) = (is_low(self.entropy), self.most_frequent) {
+            tips.push(format!(
+                "You play {} in {:.0}% of rounds, mix your moves more evenly",
+                game_move.get_name(game_type),
+                count as f64 / self.rounds as f64 * 100.0
+            ));
+        }
+        if is_low(self.entropy_given_move) {
+            tips.push(String::from("Your move depends on your previous move, avoid fixed sequences"));
+        }
+        if is_low(self.entropy_given_result) {
+            tips.push(String::from(
+                "Your move depends on the last result, don't react to wins and losses the same way",
+            ));
+        }
+        for x in self.get_detected_habits() {
+            tips.push(format!("{} ({} of {} times)", x.habit.get_description(), x.matches, x.occurrences));
+        }
+        if tips.is_empty() {
+            tips.push(String::from("No clear pattern found, keep mixing your moves"));
+        }
+        tips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    fn rounds(moves: &[(usize, usize)]) -> Vec<Round> {
+        moves
+            .iter()
+            .map(|(x, y)| Round {
+                player_move: GameMove(*x),
+                computer_move: GameMove(*y),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_entropy() {
+        assert_eq!(get_entropy(&[4, 0, 0]), 0.0);
+        assert_eq!(get_entropy(&[2, 2]), 1.0);
+        assert_eq!(get_entropy(&[1, 1, 1, 1]), 2.0);
+    }
+
+    #[test]
+    fn test_too_few_rounds() {
+        let game_type = bundled_game_type("normal");
+        let predictability = Predictability::new(&rounds(&[(0, 1), (0, 2)]), &game_type);
+        assert_eq!(predictability.get_score(), None);
+        assert_eq!(predictability.get_tips(&game_type).len(), 1);
+    }
+
+    #[test]
+    fn test_same_move() {
+        let game_type = bundled_game_type("normal");
+        let history = rounds(&[(0, 0), (0, 1), (0, 2)].repeat(4));
+        let predictability = Predictability::new(&history, &game_type);
+        assert_eq!(predictability.entropy.unwrap().bits, 0.0);
+        assert_eq!(predictability.get_score(), Some(100));
+        assert!(predictability.get_tips(&game_type)[0].contains("100%"));
+        assert!(predictability.get_detected_habits().iter().any(|x| x.habit == Habit::Repeat));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let game_type = bundled_game_type("normal");
+        let history = rounds(&[(0, 0), (1, 0), (2, 0)].repeat(5));
+        let predictability = Predictability::new(&history, &game_type);
+        assert!(predictability.entropy.unwrap().ratio > 0.99);
+        assert_eq!(predictability.entropy_given_move.unwrap().bits, 0.0);
+        assert_eq!(predictability.get_score(), Some(100));
+        assert_eq!(predictability.get_detected_habits()[0].habit, Habit::BeatOwnLast);
+    }
+
+    #[test]
+    fn test_switch_to_winner_after_loss() {
+        let game_type = bundled_game_type("normal");
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut history: Vec<Round> = Vec::new();
+        for _ in 0..60 {
+            let computer_move = GameMove(rng.gen_range(0..3));
+            let player_move = match history.last() {
+                Some(x) if x.player_move.beats_other(&game_type, &x.computer_move) == GameResult::Lose => GameMove(
+                    (0..3)
+                        .find(|m| GameMove(*m).beats_other(&game_type, &x.computer_move) == GameResult::Win)
+                        .unwrap(),
+                ),
+                _ => GameMove(rng.gen_range(0..3)),
+            };
+            history.push(Round { player_move, computer_move });
+        }
+        let predictability = Predictability::new(&history, &game_type);
+        let habit = predictability.habits.iter().find(|x| x.habit == Habit::SwitchToWinnerAfterLoss).unwrap();
+        assert_eq!(habit.matches, habit.occurrences);
+        assert!(habit.is_detected());
+    }
+
+    #[test]
+    fn test_random_player() {
+        let game_type = bundled_game_type("normal");
+        let mut rng = StdRng::seed_from_u64(7);
+        let history: Vec<Round> = (0..600)
+            .map(|_| Round {
+                player_move: GameMove(rng.gen_range(0..3)),
+                computer_move: GameMove(rng.gen_range(0..3)),
+            })
+            .collect();
+        let predictability = Predictability::new(&history, &game_type);
+        assert!(predictability.get_score().unwrap() < 10);
+        assert!(predictability.get_detected_habits().is_empty());
+        assert_eq!(
+            predictability.get_tips(&game_type),
+            vec![String::from("No clear pattern found, keep mixing your moves")]
+        );
+    }
+}
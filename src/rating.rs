@@ -0,0 +1,286 @@
+use std::f64::consts::PI;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game_result::GameResult;
+use crate::game_type::GameType;
+use crate::opponent::Opponent;
+
+const RATINGS_FILE_PATH: &str = "./RATINGS";
+const GLICKO_SCALE: f64 = 173.7178;
+const DEFAULT_RATING: f64 = 1500.0;
+const DEFAULT_DEVIATION: f64 = 350.0;
+const DEFAULT_VOLATILITY: f64 = 0.06;
+const TAU: f64 = 0.5;
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+// Opponent strategies don't learn between matches, so they are rated as fixed players of average strength.
+const OPPONENT_RATING: Glicko2 = Glicko2 {
+    rating: DEFAULT_RATING,
+    deviation: 30.0,
+    volatility: DEFAULT_VOLATILITY,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Glicko2 {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+impl Glicko2 {
+    // One rating period with the given games, score is 1.0 for a win, 0.5 for a draw and 0.0 for a loss. Follows the
+    // steps of Glickman's "Example of the Glicko-2 system".
+    pub fn update(&self, games: &[(Glicko2, f64)]) -> Glicko2 {
+        let mu = (self.rating - DEFAULT_RATING) / GLICKO_SCALE;
+        let phi = self.deviation / GLICKO_SCALE;
+        if games.is_empty() {
+            return Glicko2 {
+                deviation: (phi * phi + self.volatility * self.volatility).sqrt() * GLICKO_SCALE,
+                ..*self
+            };
+        }
+
+        let mut v_inverse = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in games {
+            let opponent_mu = (opponent.rating - DEFAULT_RATING) / GLICKO_SCALE;
+            let opponent_g = g(opponent.deviation / GLICKO_SCALE);
+            let expected = 1.0 / (1.0 + (-opponent_g * (mu - opponent_mu)).exp());
+            v_inverse += opponent_g * opponent_g * expected * (1.0 - expected);
+            improvement += opponent_g * (score - expected);
+        }
+        let v = 1.0 / v_inverse;
+        let delta = v * improvement;
+
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2)) - (x - a) / (TAU * TAU)
+        };
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > CONVERGENCE_TOLERANCE {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+        let volatility = (big_a / 2.0).exp();
+
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+        Glicko2 {
+            rating: new_mu * GLICKO_SCALE + DEFAULT_RATING,
+            deviation: new_phi * GLICKO_SCALE,
+            volatility,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RatingPoint {
+    pub timestamp: u64,
+    pub match_id: Uuid,
+    pub rating: Glicko2,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingEntry {
+    pub profile: Uuid,
+    pub opponent: Opponent,
+    pub game_type: String,
+    pub rating: Glicko2,
+    pub history: Vec<RatingPoint>,
+}
+
+impl RatingEntry {
+    pub fn get_change(&self) -> Option<f64> {
+        match self.history.len() {
+            0 => None,
+            1 => Some(self.rating.rating - DEFAULT_RATING),
+            x => Some(self.rating.rating - self.history[x - 2].rating.rating),
+        }
+    }
+}
+
+// Every match is one rating period, its rounds are the games played in it.
+#[derive(Resource, Debug)]
+pub struct Ratings {
+    path: PathBuf,
+    pub entries: Vec<RatingEntry>,
+}
+
+impl Ratings {
+    pub fn init() -> Self {
+        Ratings::load(Path::new(RATINGS_FILE_PATH))
+    }
+
+    pub fn load(path: &Path) -> Self {
+        let entries = match fs::read_to_string(path) {
+            Ok(x) => serde_json::from_str(&x).unwrap_or_else(|e| {
+                warn!("Unable to read ratings: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            path: path.to_path_buf(),
+            entries,
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut file = File::create(&self.path)?;
+        file.write_all(serde_json::to_string(&self.entries)?.as_bytes())
+    }
+
+    pub fn get(&self, profile: Uuid, opponent: Opponent, game_type: &str) -> Option<&RatingEntry> {
+        self.entries
+            .iter()
+            .find(|x| x.profile == profile && x.opponent == opponent && x.game_type == game_type)
+    }
+
+    pub fn get_profile_entries(&self, profile: Uuid) -> Vec<&RatingEntry> {
+        self.entries.iter().filter(|x| x.profile == profile).collect()
+    }
+
+    // Returns false when the match was already rated or has no rounds.
+    pub fn update(&mut self, profile: Uuid, opponent: Opponent, game_type: &GameType, match_id: Uuid, results: &[GameResult], timestamp: u64) -> bool {
+        if results.is_empty() {
+            return false;
+        }
+        let id = &game_type.ruleset().id;
+        let index = match self
+            .entries
+            .iter()
+            .position(|x| x.profile == profile && x.opponent == opponent && x.game_type == *id)
+        {
+            Some(x) => x,
+            None => {
+                self.entries.push(RatingEntry {
+                    profile,
+                    opponent,
+                    game_type: id.clone(),
+                    rating: Glicko2::default(),
+                    history: Vec::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+        let entry = &mut self.entries[index];
+        if entry.history.iter().any(|x| x.match_id == match_id) {
+            return false;
+        }
+
+        let games: Vec<(Glicko2, f64)> = results
+            .iter()
+            .map(|x| {
+                let score = match x {
+                    GameResult::Win => 1.0,
+                    GameResult::Draw => 0.5,
+                    GameResult::Lose => 0.0,
+                };
+                (OPPONENT_RATING, score)
+            })
+            .collect();
+        entry.rating = entry.rating.update(&games);
+        entry.history.push(RatingPoint {
+            timestamp,
+            match_id,
+            rating: entry.rating,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    #[test]
+    fn test_glickman_example() {
+        let player = Glicko2 {
+            rating: 1500.0,
+            deviation: 200.0,
+            volatility: 0.06,
+        };
+        let opponent = |rating, deviation| Glicko2 {
+            rating,
+            deviation,
+            volatility: 0.06,
+        };
+        let updated = player.update(&[(opponent(1400.0, 30.0), 1.0), (opponent(1550.0, 100.0), 0.0), (opponent(1700.0, 300.0), 0.0)]);
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001, "{:?}", updated);
+    }
+
+    #[test]
+    fn test_no_games_increases_deviation() {
+        let player = Glicko2 { deviation: 50.0, ..default() };
+        let updated = player.update(&[]);
+        assert_eq!(updated.rating, player.rating);
+        assert!(updated.deviation > player.deviation);
+    }
+
+    #[test]
+    fn test_update_and_persist() {
+        let game_type = bundled_game_type("normal");
+        let path = std::env::temp_dir().join(format!("rps-ratings-{}", Uuid::new_v4()));
+        let mut ratings = Ratings::load(&path);
+        let profile = Uuid::new_v4();
+        let match_id = Uuid::new_v4();
+        let wins = vec![GameResult::Win; 5];
+
+        assert!(!ratings.update(profile, Opponent::Iocaine, &game_type, match_id, &[], 0));
+        assert!(ratings.update(profile, Opponent::Iocaine, &game_type, match_id, &wins, 10));
+        assert!(!ratings.update(profile, Opponent::Iocaine, &game_type, match_id, &wins, 10));
+        let entry = ratings.get(profile, Opponent::Iocaine, "normal").unwrap();
+        assert!(entry.rating.rating > DEFAULT_RATING);
+        assert!(entry.rating.deviation < DEFAULT_DEVIATION);
+        assert_eq!(entry.get_change(), Some(entry.rating.rating - DEFAULT_RATING));
+        assert!(ratings.get(profile, Opponent::Random, "normal").is_none());
+
+        ratings.save().unwrap();
+        assert_eq!(Ratings::load(&path).entries, ratings.entries);
+        fs::remove_file(&path).unwrap();
+    }
+}
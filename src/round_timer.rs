@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const TIME_LIMITS: [Option<u32>; 4] = [None, Some(3), Some(5), Some(10)];
+const CHANT: [&str; 4] = ["rock...", "paper...", "scissors...", "shoot!"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeoutAction {
+    #[default]
+    RandomMove,
+    Forfeit,
+}
+
+impl TimeoutAction {
+    pub fn get_friendly_name(&self) -> &str {
+        match self {
+            TimeoutAction::RandomMove => "random move",
+            TimeoutAction::Forfeit => "forfeit",
+        }
+    }
+
+    pub fn next(&self) -> TimeoutAction {
+        match self {
+            TimeoutAction::RandomMove => TimeoutAction::Forfeit,
+            TimeoutAction::Forfeit => TimeoutAction::RandomMove,
+        }
+    }
+}
+
+// Timer of current round, None when rounds have no time limit or the round is over.
+#[derive(Resource, Debug, Default)]
+pub struct RoundCountdown(pub Option<Timer>);
+
+pub fn next_time_limit(time_limit: Option<u32>) -> Option<u32> {
+    let index = TIME_LIMITS.iter().position(|x| *x == time_limit).map_or(0, |x| x + 1);
+    TIME_LIMITS[index % TIME_LIMITS.len()]
+}
+
+// Time limit is split into four equal beats, the last one is "shoot!".
+pub fn get_countdown_text(timer: &Timer) -> String {
+    let beat = ((timer.percent() * CHANT.len() as f32) as usize).min(CHANT.len() - 1);
+    format!("{} {:.1}s", CHANT[beat], timer.remaining_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_next_time_limit() {
+        assert_eq!(next_time_limit(None), Some(3));
+        assert_eq!(next_time_limit(Some(10)), None);
+        assert_eq!(next_time_limit(Some(7)), None);
+    }
+
+    #[test]
+    fn test_get_countdown_text() {
+        let mut timer = Timer::from_seconds(4.0, TimerMode::Once);
+        assert_eq!(get_countdown_text(&timer), "rock... 4.0s");
+        timer.tick(Duration::from_millis(1500));
+        assert_eq!(get_countdown_text(&timer), "paper... 2.5s");
+        timer.tick(Duration::from_millis(1000));
+        assert_eq!(get_countdown_text(&timer), "scissors... 1.5s");
+        timer.tick(Duration::from_millis(1400));
+        assert_eq!(get_countdown_text(&timer), "shoot! 0.1s");
+        timer.tick(Duration::from_millis(1000));
+        assert_eq!(get_countdown_text(&timer), "shoot! 0.0s");
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::FileAssetIo;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::game_result::GameResult;
+use crate::ruleset_analysis::RulesetAnalysis;
+
+const RULESETS_ASSET_DIR: &str = "rulesets";
+const RULESET_FILE_EXTENSION: &str = "ron";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesetError {
+    Io(String),
+    Parse(String),
+    UnknownMove(String),
+    DuplicateMove(String),
+    NoMoves,
+    MissingOutcome(String, String),
+    ContradictingRules(String, String),
+    SelfBeatingMove(String),
+}
+
+impl Display for RulesetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesetError::Io(x) => write!(f, "Unable to read ruleset: {}", x),
+            RulesetError::Parse(x) => write!(f, "Unable to parse ruleset: {}", x),
+            RulesetError::UnknownMove(x) => write!(f, "Rule references unknown move: {}", x),
+            RulesetError::DuplicateMove(x) => write!(f, "Move is defined more than once: {}", x),
+            RulesetError::NoMoves => write!(f, "Ruleset has no moves."),
+            RulesetError::MissingOutcome(x, y) => write!(f, "No outcome is defined for {} against {}.", x, y),
+            RulesetError::ContradictingRules(x, y) => write!(f, "{} and {} both beat each other.", x, y),
+            RulesetError::SelfBeatingMove(x) => write!(f, "{} beats itself.", x),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesetMove {
+    pub name: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub winner: usize,
+    pub loser: usize,
+    pub verb: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RuleDefinition {
+    winner: String,
+    verb: String,
+    loser: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CycleDefinition {
+    verb: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RulesetDefinition {
+    id: String,
+    name: String,
+    friendly_name: String,
+    moves: Vec<RulesetMove>,
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+    #[serde(default)]
+    cycle: Option<CycleDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    pub id: String,
+    pub name: String,
+    pub friendly_name: String,
+    pub moves: Vec<RulesetMove>,
+    pub rules: Vec<Rule>,
+    rule_table: Vec<Option<usize>>,
+}
+
+impl Ruleset {
+    pub fn from_ron_str(content: &str) -> Result<Self, RulesetError> {
+        let definition: RulesetDefinition = ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(content)
+            .map_err(|e| RulesetError::Parse(e.to_string()))?;
+        if definition.moves.is_empty() {
+            return Err(RulesetError::NoMoves);
+        }
+        for (index, game_move) in definition.moves.iter().enumerate() {
+            if definition.moves[..index].iter().any(|x| x.name == game_move.name) {
+                return Err(RulesetError::DuplicateMove(game_move.name.clone()));
+            }
+        }
+
+        let mut ruleset = Self {
+            id: definition.id,
+            name: definition.name,
+            friendly_name: definition.friendly_name,
+            moves: definition.moves,
+            rules: Vec::new(),
+            rule_table: Vec::new(),
+        };
+        for rule in definition.rules {
+            ruleset.rules.push(Rule {
+                winner: ruleset.find_move(&rule.winner).ok_or(RulesetError::UnknownMove(rule.winner))?,
+                loser: ruleset.find_move(&rule.loser).ok_or(RulesetError::UnknownMove(rule.loser))?,
+                verb: rule.verb,
+            });
+        }
+        ruleset.build_rule_table();
+        if let Some(cycle) = definition.cycle {
+            ruleset.add_cycle_rules(&cycle.verb);
+        }
+
+        Ok(ruleset)
+    }
+
+    // Every move beats the (n - 1) / 2 moves that follow it in the list, wrapping around, like in RPS-7 or RPS-101.
+    // Pairs already covered by an explicit rule keep it.
+    fn add_cycle_rules(&mut self, verb: &str) {
+        let number_of_moves = self.moves.len();
+        for winner in 0..number_of_moves {
+            for distance in 1..=(number_of_moves - 1) / 2 {
+                let loser = (winner + distance) % number_of_moves;
+                if self.find_rule(winner, loser).is_none() && self.find_rule(loser, winner).is_none() {
+                    self.rule_table[winner * number_of_moves + loser] = Some(self.rules.len());
+                    self.rules.push(Rule {
+                        winner,
+                        loser,
+                        verb: verb.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn build_rule_table(&mut self) {
+        let number_of_moves = self.moves.len();
+        self.rule_table = vec![None; number_of_moves * number_of_moves];
+        for (index, rule) in self.rules.iter().enumerate() {
+            self.rule_table[rule.winner * number_of_moves + rule.loser].get_or_insert(index);
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, RulesetError> {
+        let content = fs::read_to_string(path).map_err(|e| RulesetError::Io(format!("{}: {}", path.display(), e)))?;
+        Ruleset::from_ron_str(&content)
+    }
+
+    pub fn find_move(&self, name: &str) -> Option<usize> {
+        self.moves.iter().position(|x| x.name == name)
+    }
+
+    pub fn find_rule(&self, winner: usize, loser: usize) -> Option<&Rule> {
+        self.rule_table[winner * self.moves.len() + loser].map(|x| &self.rules[x])
+    }
+
+    pub fn outcome(&self, first: usize, second: usize) -> GameResult {
+        if first == second {
+            GameResult::Draw
+        } else if self.find_rule(first, second).is_some() {
+            GameResult::Win
+        } else if self.find_rule(second, first).is_some() {
+            GameResult::Lose
+        } else {
+            GameResult::Draw
+        }
+    }
+
+    pub fn payoff(&self, first: usize, second: usize) -> i32 {
+        match self.outcome(first, second) {
+            GameResult::Win => 1,
+            GameResult::Lose => -1,
+            GameResult::Draw => 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), RulesetError> {
+        match RulesetAnalysis::new(self).get_error(self) {
+            Some(x) => Err(x),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get_phrase(&self, first: usize, second: usize) -> String {
+        self.find_rule(first, second)
+            .or_else(|| self.find_rule(second, first))
+            .map(|x| format!("{} {} {}.", self.moves[x.winner].name, x.verb, self.moves[x.loser].name))
+            .unwrap_or_default()
+    }
+}
+
+// Rulesets are next to the icons in the asset folder, found the same way as the asset server does, so the game can be
+// started from any directory.
+pub fn get_rulesets_dir() -> PathBuf {
+    FileAssetIo::get_base_path().join(AssetPlugin::default().asset_folder).join(RULESETS_ASSET_DIR)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Rulesets(pub Vec<Ruleset>);
+
+impl Rulesets {
+    pub fn init() -> Self {
+        Rulesets::load_dir(&get_rulesets_dir())
+    }
+
+    pub fn load_dir(dir: &Path) -> Self {
+        let mut paths = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|x| x.ok().map(|x| x.path()))
+                .filter(|x| x.extension().is_some_and(|x| x == RULESET_FILE_EXTENSION))
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                warn!("Unable to read rulesets directory {}: {}", dir.display(), e);
+                Vec::new()
+            }
+        };
+        paths.sort();
+
+        Self(
+            paths
+                .iter()
+                .filter_map(|path| match Ruleset::from_file(path) {
+                    Ok(x) => {
+                        if let Err(e) = x.validate() {
+                            warn!("Ruleset {} cannot be played: {}", path.display(), e);
+                        }
+                        Some(x)
+                    }
+                    Err(e) => {
+                        warn!("Skipping ruleset {}: {}", path.display(), e);
+                        None
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{bundled_game_type, bundled_rulesets};
+
+    use super::*;
+
+    fn bundled(id: &str) -> Ruleset {
+        bundled_game_type(id).ruleset().clone()
+    }
+
+    #[test]
+    fn test_load_bundled_rulesets() {
+        let rulesets = bundled_rulesets();
+        let ids: Vec<&str> = rulesets.0.iter().map(|x| x.id.as_str()).collect();
+        assert_eq!(ids, vec!["normal", "spock_lizard", "fire_water", "rps_7", "rps_15", "rps_101"]);
+        assert_eq!(bundled("normal").moves.len(), 3);
+        assert_eq!(bundled("spock_lizard").moves.len(), 5);
+        assert_eq!(bundled("fire_water").moves.len(), 5);
+        assert_eq!(bundled("rps_7").moves.len(), 7);
+        assert_eq!(bundled("rps_15").moves.len(), 15);
+        assert_eq!(bundled("rps_101").moves.len(), 101);
+    }
+
+    #[test]
+    fn test_cycle_rules() {
+        let ruleset = bundled("rps_101");
+        assert_eq!(ruleset.rules.len(), 101 * 50);
+        for first in 0..ruleset.moves.len() {
+            let wins = (0..ruleset.moves.len()).filter(|x| ruleset.outcome(first, *x) == GameResult::Win).count();
+            assert_eq!(wins, 50);
+        }
+        let dynamite = ruleset.find_move("Dynamite").unwrap();
+        let helicopter = ruleset.find_move("Helicopter").unwrap();
+        assert_eq!(ruleset.outcome(helicopter, dynamite), GameResult::Win);
+
+        let content = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A"), (name: "B"), (name: "C")], rules: [(winner: "A", verb: "smashes", loser: "C")], cycle: (verb: "beats"))"#;
+        let ruleset = Ruleset::from_ron_str(content).unwrap();
+        assert_eq!(ruleset.rules.len(), 3);
+        assert_eq!(ruleset.outcome(0, 1), GameResult::Win);
+        assert_eq!(ruleset.outcome(1, 2), GameResult::Win);
+        assert_eq!(ruleset.outcome(0, 2), GameResult::Win);
+        assert_eq!(ruleset.get_phrase(2, 0), "A smashes C.");
+        assert_eq!(ruleset.get_phrase(1, 0), "A beats B.");
+    }
+
+    #[test]
+    fn test_outcome() {
+        let ruleset = bundled("fire_water");
+        let water = ruleset.find_move("Water").unwrap();
+        let fire = ruleset.find_move("Fire").unwrap();
+        assert_eq!(ruleset.outcome(water, fire), GameResult::Win);
+        assert_eq!(ruleset.outcome(fire, water), GameResult::Lose);
+        assert_eq!(ruleset.outcome(fire, fire), GameResult::Draw);
+    }
+
+    #[test]
+    fn test_get_phrase() {
+        let ruleset = bundled("fire_water");
+        let water = ruleset.find_move("Water").unwrap();
+        let rock = ruleset.find_move("Rock").unwrap();
+        assert_eq!(ruleset.get_phrase(rock, water), "Water erodes Rock.");
+        assert_eq!(ruleset.get_phrase(water, rock), "Water erodes Rock.");
+        assert_eq!(ruleset.get_phrase(rock, rock), "");
+    }
+
+    #[test]
+    fn test_from_ron_str_errors() {
+        let unknown = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A", icon: "a.png")], rules: [(winner: "A", verb: "beats", loser: "B")])"#;
+        assert_eq!(Ruleset::from_ron_str(unknown), Err(RulesetError::UnknownMove(String::from("B"))));
+
+        let duplicate = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A", icon: "a.png"), (name: "A", icon: "a.png")], rules: [])"#;
+        assert_eq!(Ruleset::from_ron_str(duplicate), Err(RulesetError::DuplicateMove(String::from("A"))));
+
+        let empty = r#"(id: "x", name: "x", friendly_name: "x", moves: [], rules: [])"#;
+        assert_eq!(Ruleset::from_ron_str(empty), Err(RulesetError::NoMoves));
+
+        assert!(matches!(Ruleset::from_ron_str("("), Err(RulesetError::Parse(_))));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::game_result::GameResult;
+use crate::ruleset::{Ruleset, RulesetError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MoveDegree {
+    pub wins: usize,
+    pub loses: usize,
+    pub draws: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominatedMove {
+    pub dominated: usize,
+    pub dominating: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulesetAnalysis {
+    pub degrees: Vec<MoveDegree>,
+    pub dominated_moves: Vec<DominatedMove>,
+    pub missing_pairs: Vec<(usize, usize)>,
+    pub contradicting_pairs: Vec<(usize, usize)>,
+    pub self_beating_moves: Vec<usize>,
+}
+
+impl RulesetAnalysis {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        let number_of_moves = ruleset.moves.len();
+        let mut degrees = vec![MoveDegree::default(); number_of_moves];
+        let mut missing_pairs = Vec::new();
+        let mut contradicting_pairs = Vec::new();
+        let mut self_beating_moves = Vec::new();
+
+        for (first, degree) in degrees.iter_mut().enumerate() {
+            if ruleset.find_rule(first, first).is_some() {
+                self_beating_moves.push(first);
+            }
+            for second in 0..number_of_moves {
+                match ruleset.outcome(first, second) {
+                    GameResult::Win => degree.wins += 1,
+                    GameResult::Lose => degree.loses += 1,
+                    GameResult::Draw => degree.draws += 1,
+                }
+                if first < second {
+                    let first_wins = ruleset.find_rule(first, second).is_some();
+                    let second_wins = ruleset.find_rule(second, first).is_some();
+                    if first_wins && second_wins {
+                        contradicting_pairs.push((first, second));
+                    } else if !first_wins && !second_wins {
+                        missing_pairs.push((first, second));
+                    }
+                }
+            }
+        }
+
+        let mut dominated_moves = Vec::new();
+        for dominated in 0..number_of_moves {
+            for dominating in 0..number_of_moves {
+                if dominated != dominating && RulesetAnalysis::is_dominated_by(ruleset, dominated, dominating) {
+                    dominated_moves.push(DominatedMove { dominated, dominating });
+                }
+            }
+        }
+
+        Self {
+            degrees,
+            dominated_moves,
+            missing_pairs,
+            contradicting_pairs,
+            self_beating_moves,
+        }
+    }
+
+    // Against every opponent move the dominating move does at least as well, and against some of them strictly better.
+    fn is_dominated_by(ruleset: &Ruleset, dominated: usize, dominating: usize) -> bool {
+        let mut is_strictly_better = false;
+        for other in 0..ruleset.moves.len() {
+            let dominated_payoff = ruleset.payoff(dominated, other);
+            let dominating_payoff = ruleset.payoff(dominating, other);
+            if dominating_payoff < dominated_payoff {
+                return false;
+            }
+            is_strictly_better |= dominating_payoff > dominated_payoff;
+        }
+        is_strictly_better
+    }
+
+    pub fn is_tournament(&self) -> bool {
+        self.missing_pairs.is_empty() && self.contradicting_pairs.is_empty() && self.self_beating_moves.is_empty()
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.is_tournament() && self.degrees.iter().all(|x| x.wins == self.degrees[0].wins && x.loses == self.degrees[0].loses)
+    }
+
+    pub fn get_error(&self, ruleset: &Ruleset) -> Option<RulesetError> {
+        let name = |x: usize| ruleset.moves[x].name.clone();
+        if let Some(x) = self.self_beating_moves.first() {
+            Some(RulesetError::SelfBeatingMove(name(*x)))
+        } else if let Some((first, second)) = self.contradicting_pairs.first() {
+            Some(RulesetError::ContradictingRules(name(*first), name(*second)))
+        } else {
+            self.missing_pairs
+                .first()
+                .map(|(first, second)| RulesetError::MissingOutcome(name(*first), name(*second)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    #[test]
+    fn test_balanced_rulesets() {
+        for id in ["normal", "spock_lizard", "rps_7", "rps_15", "rps_101"] {
+            let ruleset = bundled_game_type(id).ruleset().clone();
+            let analysis = RulesetAnalysis::new(&ruleset);
+            assert!(analysis.is_tournament(), "{}", id);
+            assert!(analysis.is_balanced(), "{}", id);
+            assert!(analysis.dominated_moves.is_empty(), "{}", id);
+            assert_eq!(analysis.get_error(&ruleset), None);
+        }
+    }
+
+    #[test]
+    fn test_fire_water_is_unbalanced() {
+        let ruleset = bundled_game_type("fire_water").ruleset().clone();
+        let analysis = RulesetAnalysis::new(&ruleset);
+        let id = |x: &str| ruleset.find_move(x).unwrap();
+
+        assert!(analysis.is_tournament());
+        assert!(!analysis.is_balanced());
+        assert_eq!(analysis.degrees[id("Water")], MoveDegree { wins: 3, loses: 1, draws: 1 });
+        assert_eq!(analysis.degrees[id("Scissors")], MoveDegree { wins: 1, loses: 3, draws: 1 });
+        assert_eq!(
+            analysis.dominated_moves,
+            vec![
+                DominatedMove {
+                    dominated: id("Rock"),
+                    dominating: id("Water"),
+                },
+                DominatedMove {
+                    dominated: id("Scissors"),
+                    dominating: id("Fire"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_ruleset() {
+        let content = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A"), (name: "B"), (name: "C")], rules: [(winner: "A", verb: "beats", loser: "B"), (winner: "B", verb: "beats", loser: "C")])"#;
+        let ruleset = Ruleset::from_ron_str(content).unwrap();
+        let analysis = RulesetAnalysis::new(&ruleset);
+        assert!(!analysis.is_tournament());
+        assert_eq!(analysis.missing_pairs, vec![(0, 2)]);
+        assert_eq!(analysis.degrees[0], MoveDegree { wins: 1, loses: 0, draws: 2 });
+        assert_eq!(
+            analysis.get_error(&ruleset),
+            Some(RulesetError::MissingOutcome(String::from("A"), String::from("C")))
+        );
+        assert_eq!(ruleset.validate(), Err(RulesetError::MissingOutcome(String::from("A"), String::from("C"))));
+
+        let content = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A"), (name: "B")], rules: [(winner: "A", verb: "beats", loser: "B"), (winner: "B", verb: "beats", loser: "A")])"#;
+        let ruleset = Ruleset::from_ron_str(content).unwrap();
+        assert_eq!(ruleset.validate(), Err(RulesetError::ContradictingRules(String::from("A"), String::from("B"))));
+
+        let content = r#"(id: "x", name: "x", friendly_name: "x", moves: [(name: "A")], rules: [(winner: "A", verb: "beats", loser: "A")])"#;
+        let ruleset = Ruleset::from_ron_str(content).unwrap();
+        assert_eq!(ruleset.validate(), Err(RulesetError::SelfBeatingMove(String::from("A"))));
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::button::{Actions, HotKey};
+use crate::common::*;
+use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+use crate::nash::Equilibrium;
+use crate::ruleset::Ruleset;
+use crate::ruleset_analysis::{MoveDegree, RulesetAnalysis};
+
+const MAX_LISTED_ITEMS: usize = 6;
+
+#[derive(Component)]
+pub struct OnRulesetInfoScreen;
+
+pub struct RulesetInfoPlugin;
+
+impl Plugin for RulesetInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::RulesetInfo), setup_ruleset_info_screen)
+            .add_systems(Update, confirm_button_action.run_if(in_state(AppState::RulesetInfo)))
+            .add_systems(OnExit(AppState::RulesetInfo), despawn_screen::<OnRulesetInfoScreen>);
+    }
+}
+
+fn get_listed_text(items: Vec<String>) -> String {
+    if items.is_empty() {
+        String::from("none")
+    } else if items.len() > MAX_LISTED_ITEMS {
+        format!("{}, ... ({} in total)", items[..MAX_LISTED_ITEMS].join(", "), items.len())
+    } else {
+        items.join(", ")
+    }
+}
+
+fn get_degree_lines(ruleset: &Ruleset, analysis: &RulesetAnalysis) -> Vec<String> {
+    let mut groups: Vec<(MoveDegree, Vec<String>)> = Vec::new();
+    for (index, degree) in analysis.degrees.iter().enumerate() {
+        let name = ruleset.moves[index].name.clone();
+        match groups.iter_mut().find(|(x, _)| x == degree) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((*degree, vec![name])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(degree, names)| {
+            format!(
+                "{}: wins {}, loses {}, draws {}",
+                get_listed_text(names),
+                degree.wins,
+                degree.loses,
+                degree.draws
+            )
+        })
+        .collect()
+}
+
+fn get_equilibrium_lines(ruleset: &Ruleset, equilibrium: &Equilibrium) -> Vec<String> {
+    let mut groups: Vec<(i32, Vec<String>)> = Vec::new();
+    for (index, probability) in equilibrium.column_strategy.iter().enumerate() {
+        let percent = (probability * 100.0).round() as i32;
+        let name = ruleset.moves[index].name.clone();
+        match groups.iter_mut().find(|(x, _)| *x == percent) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((percent, vec![name])),
+        }
+    }
+    groups.sort_by(|(x, _), (y, _)| y.cmp(x));
+
+    groups
+        .into_iter()
+        .map(|(percent, names)| format!("{}: {}%", get_listed_text(names), percent))
+        .collect()
+}
+
+pub fn setup_ruleset_info_screen(mut commands: Commands, game_font: Res<GameFont>, game_settings: Res<GameSettings>, game_type: Res<GameType>) {
+    let font = &game_font.0;
+    let ruleset = game_type.ruleset();
+    let analysis = RulesetAnalysis::new(ruleset);
+    let name = |x: usize| ruleset.moves[x].name.clone();
+
+    let header_style = TextStyle {
+        font: font.clone(),
+        font_size: 18.0,
+        color: OVERVIEW_TITLE_COLOR,
+    };
+
+    let body_style = TextStyle {
+        font: font.clone(),
+        font_size: 14.0,
+        color: OVERVIEW_SUB_TITLE_COLOR,
+    };
+
+    let header_margin = UiRect::new(Val::Px(10.0), Val::Px(10.0), Val::Px(10.0), Val::Px(10.0));
+    let body_margin = UiRect::new(Val::Px(10.0), Val::Px(10.0), Val::Px(5.0), Val::Px(5.0));
+
+    let mut sections: Vec<(&str, Vec<String>)> = vec![
+        ("Moves", get_degree_lines(ruleset, &analysis)),
+        (
+            "Summary",
+            vec![
+                format!("Every pair has one winner: {}", if analysis.is_tournament() { "yes" } else { "no" }),
+                format!("Balanced: {}", if analysis.is_balanced() { "yes" } else { "no" }),
+                match ruleset.validate() {
+                    Ok(_) => String::from("Ruleset can be played."),
+                    Err(e) => format!("Ruleset cannot be played. {}", e),
+                },
+            ],
+        ),
+        (
+            "Dominated moves",
+            vec![get_listed_text(
+                analysis
+                    .dominated_moves
+                    .iter()
+                    .map(|x| format!("{} by {}", name(x.dominated), name(x.dominating)))
+                    .collect(),
+            )],
+        ),
+    ];
+    if ruleset.validate().is_ok() {
+        sections.push(("Optimal strategy", get_equilibrium_lines(ruleset, &Equilibrium::for_game_type(&game_type))));
+    }
+    if !analysis.missing_pairs.is_empty() {
+        sections.push((
+            "Missing pairings",
+            vec![get_listed_text(
+                analysis.missing_pairs.iter().map(|(x, y)| format!("{} - {}", name(*x), name(*y))).collect(),
+            )],
+        ));
+    }
+    if !analysis.contradicting_pairs.is_empty() {
+        sections.push((
+            "Contradicting rules",
+            vec![get_listed_text(
+                analysis
+                    .contradicting_pairs
+                    .iter()
+                    .map(|(x, y)| format!("{} - {}", name(*x), name(*y)))
+                    .collect(),
+            )],
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Start,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnRulesetInfoScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                (TextBundle::from_section(
+                    game_settings.player_options.name.clone(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Right))
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: MENU_BACKGROUND_COLOR.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    game_type.get_friendly_name(),
+                                    TextStyle {
+                                        font_size: 40.0,
+                                        color: TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::new(Val::Px(20.0), Val::Px(20.0), Val::Px(30.0), Val::Px(30.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            for (header, lines) in sections {
+                                parent.spawn(TextBundle::from_section(header, header_style.clone()).with_style(Style {
+                                    margin: header_margin,
+                                    ..default()
+                                }));
+
+                                for line in lines {
+                                    parent.spawn(TextBundle::from_section(line, body_style.clone()).with_style(Style {
+                                        margin: body_margin,
+                                        ..default()
+                                    }));
+                                }
+                            }
+
+                            parent
+                                .spawn((NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        align_items: AlignItems::End,
+                                        justify_content: JustifyContent::End,
+                                        align_content: AlignContent::End,
+                                        width: Val::Percent(100.0),
+                                        margin: UiRect::top(Val::Px(20.0)),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },))
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(250.0),
+                                                    height: Val::Px(50.0),
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    border: UiRect::all(Val::Px(5.0)),
+                                                    justify_content: JustifyContent::Center,
+                                                    align_items: AlignItems::Center,
+                                                    ..default()
+                                                },
+                                                border_color: Color::WHITE.into(),
+                                                background_color: Color::NONE.into(),
+                                                ..default()
+                                            },
+                                            HotKey(KeyCode::B),
+                                        ))
+                                        .with_children(|parent| {
+                                            parent.spawn(
+                                                TextBundle::from_section(
+                                                    "(B)ack",
+                                                    TextStyle {
+                                                        font_size: BUTTON_TEXT_SIZE,
+                                                        color: BUTTON_TITLE_COLOR,
+                                                        font: font.clone(),
+                                                    },
+                                                )
+                                                .with_style(Style {
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    ..default()
+                                                }),
+                                            );
+                                        });
+                                });
+                        });
+                });
+        });
+}
+
+pub fn confirm_button_action(
+    actions: Res<Actions>,
+    mut app_state: ResMut<NextState<AppState>>,
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    game_sounds: Res<GameSounds>,
+) {
+    if actions.just_pressed(KeyCode::B) {
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
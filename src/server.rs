@@ -0,0 +1,958 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commitment::verify;
+use crate::game_move::GameMove;
+use crate::game_result::{GameResult, ResultCounts};
+use crate::game_type::GameType;
+use crate::lan::{accept, get_ruleset_fingerprint, listen, LanConnection, LanError, LanMessage, DEFAULT_PORT, PROTOCOL_VERSION, RECONNECT_GRACE};
+use crate::match_format::MatchFormat;
+use crate::ruleset::{get_rulesets_dir, Rulesets};
+use crate::spectator::{get_round_played, get_welcome, Spectators};
+
+pub const SERVER_NAME: &str = "rps-server";
+const DEFAULT_LOG_PATH: &str = "./rps-server.log";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub ruleset_id: String,
+    pub match_format: MatchFormat,
+    pub log_path: PathBuf,
+    pub rulesets_path: PathBuf,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            ruleset_id: String::from("normal"),
+            match_format: MatchFormat::BestOf(3),
+            log_path: PathBuf::from(DEFAULT_LOG_PATH),
+            rulesets_path: get_rulesets_dir(),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub const USAGE: &'static str =
+        "Usage: rps-server [--port PORT] [--ruleset ID] [--format endless|best-of-N|first-to-N|win-by-two-N] [--log PATH] [--rulesets DIR]";
+
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = ServerConfig::default();
+        let mut args = args;
+        while let Some(name) = args.next() {
+            let value = args.next().ok_or_else(|| format!("Missing value of {}", name))?;
+            match name.as_str() {
+                "--port" => config.port = value.parse().map_err(|_| format!("Invalid port: {}", value))?,
+                "--ruleset" => config.ruleset_id = value,
+                "--format" => config.match_format = MatchFormat::from_argument(&value).ok_or_else(|| format!("Invalid match format: {}", value))?,
+                "--log" => config.log_path = PathBuf::from(value),
+                "--rulesets" => config.rulesets_path = PathBuf::from(value),
+                _ => return Err(format!("Unknown argument: {}", name)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+// One JSON object per line, like MATCH_HISTORY. Scores and winners are from the first player's point of view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum ServerLogEntry {
+    MatchStarted {
+        timestamp: u64,
+        match_id: Uuid,
+        ruleset: String,
+        match_format: MatchFormat,
+        players: [String; 2],
+    },
+    RoundPlayed {
+        timestamp: u64,
+        match_id: Uuid,
+        round: u32,
+        moves: [String; 2],
+        winner: Option<String>,
+    },
+    MatchFinished {
+        timestamp: u64,
+        match_id: Uuid,
+        wins: [usize; 2],
+        draws: usize,
+        winner: Option<String>,
+        reason: String,
+    },
+}
+
+pub fn load_server_log(path: &Path) -> Vec<ServerLogEntry> {
+    fs::read_to_string(path)
+        .map(|x| x.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+fn append_server_log(path: &Path, entry: &ServerLogEntry) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)
+}
+
+#[derive(Debug)]
+struct WaitingClient {
+    connection: LanConnection,
+    hello: Option<(String, Uuid)>,
+    spectator_name: Option<String>,
+    resume: Option<Uuid>,
+}
+
+impl WaitingClient {
+    fn is_introduced(&self) -> bool {
+        self.hello.is_some() || self.spectator_name.is_some() || self.resume.is_some()
+    }
+}
+
+#[derive(Debug)]
+struct Seat {
+    // Connection is lost when None, the seat is held until the player resumes or the grace period ends.
+    connection: Option<LanConnection>,
+    dropped_at: Option<u64>,
+    name: String,
+    profile: Uuid,
+    is_accepted: bool,
+    hash: Option<String>,
+    reveal: Option<(GameMove, LanMessage)>,
+    is_rematch_requested: bool,
+    // Commits and reveals of the last two rounds which were passed to the opponent, sent again when the opponent resumes.
+    round_messages: Vec<LanMessage>,
+}
+
+impl Seat {
+    fn new(connection: LanConnection, name: String, profile: Uuid) -> Self {
+        Self {
+            connection: Some(connection),
+            dropped_at: None,
+            name,
+            profile,
+            is_accepted: false,
+            hash: None,
+            reveal: None,
+            is_rematch_requested: false,
+            round_messages: Vec::new(),
+        }
+    }
+}
+
+// Server plays the host for both clients: it proposes the ruleset, relays commits and checks every reveal before it
+// is passed on, so the logged result is decided here and not by the clients.
+#[derive(Debug)]
+struct ServerMatch {
+    id: Uuid,
+    seats: [Seat; 2],
+    round: u32,
+    score: ResultCounts,
+    is_started: bool,
+    is_finished: bool,
+    spectators: Spectators,
+}
+
+impl ServerMatch {
+    fn get_winner_name(&self, result: GameResult) -> Option<String> {
+        match result {
+            GameResult::Win => Some(self.seats[0].name.clone()),
+            GameResult::Lose => Some(self.seats[1].name.clone()),
+            GameResult::Draw => None,
+        }
+    }
+
+    fn get_started_entry(&self, game_type: &GameType, match_format: MatchFormat, timestamp: u64) -> ServerLogEntry {
+        ServerLogEntry::MatchStarted {
+            timestamp,
+            match_id: self.id,
+            ruleset: game_type.ruleset().id.clone(),
+            match_format,
+            players: [self.seats[0].name.clone(), self.seats[1].name.clone()],
+        }
+    }
+
+    // Winner is given from the first seat's point of view.
+    fn get_finished_entry(&self, winner: Option<GameResult>, reason: String, timestamp: u64) -> ServerLogEntry {
+        ServerLogEntry::MatchFinished {
+            timestamp,
+            match_id: self.id,
+            wins: [self.score.wins, self.score.loses],
+            draws: self.score.draws,
+            winner: winner.and_then(|x| self.get_winner_name(x)),
+            reason,
+        }
+    }
+
+    fn get_welcome(&self, game_type: &GameType, match_format: MatchFormat) -> LanMessage {
+        let players = [self.seats[0].name.clone(), self.seats[1].name.clone()];
+        get_welcome(game_type, match_format, players, self.round, &self.score)
+    }
+
+    // Messages for a player who lost the connection are dropped, they get the round messages again on resume.
+    fn send(&mut self, seat: usize, message: &LanMessage) -> Result<(), String> {
+        match self.seats[seat].connection.as_mut().map(|x| x.send(message)) {
+            Some(Err(e)) => self.drop_seat(seat, e),
+            _ => Ok(()),
+        }
+    }
+
+    // Lost connection of a running match holds the seat, any other error ends the match.
+    fn drop_seat(&mut self, seat: usize, error: LanError) -> Result<(), String> {
+        if error.is_connection_lost() && self.is_started && !self.is_finished {
+            self.seats[seat].connection = None;
+            return Ok(());
+        }
+        match error {
+            LanError::Closed => Err(format!("{} disconnected", self.seats[seat].name)),
+            e => Err(format!("{} disconnected: {}", self.seats[seat].name, e)),
+        }
+    }
+
+    // New connection takes the seat over, both players resume like a guest with its host and rematch has to be asked
+    // for again.
+    fn resume(&mut self, seat: usize, connection: LanConnection) -> Result<(), String> {
+        self.seats[seat].connection = Some(connection);
+        self.seats[seat].dropped_at = None;
+        for x in self.seats.iter_mut() {
+            x.is_rematch_requested = false;
+        }
+        self.send(seat, &LanMessage::Resumed)?;
+        for message in self.seats[1 - seat].round_messages.clone() {
+            self.send(seat, &message)?;
+        }
+        self.send(1 - seat, &LanMessage::Resumed)
+    }
+
+    // Fails with the reason when the grace period of a lost connection ended, player who stayed wins by forfeit.
+    fn check_forfeit(&mut self, timestamp: u64, entries: &mut Vec<ServerLogEntry>) -> Result<(), String> {
+        for seat in 0..2 {
+            let Seat {
+                connection, dropped_at, name, ..
+            } = &mut self.seats[seat];
+            if connection.is_some() {
+                continue;
+            }
+            if timestamp < *dropped_at.get_or_insert(timestamp) + RECONNECT_GRACE.as_secs() {
+                continue;
+            }
+            let reason = format!("{} did not come back, match is forfeited", name);
+            let winner = [GameResult::Lose, GameResult::Win][seat];
+            let winner = Some(winner).filter(|_| self.seats[1 - seat].connection.is_some());
+            entries.push(self.get_finished_entry(winner, reason.clone(), timestamp));
+            self.is_finished = true;
+            self.send(1 - seat, &LanMessage::OpponentForfeited)?;
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    // Error ends the match, its text is the reason written to the log.
+    fn handle(
+        &mut self,
+        seat: usize,
+        message: LanMessage,
+        game_type: &GameType,
+        match_format: MatchFormat,
+        timestamp: u64,
+        entries: &mut Vec<ServerLogEntry>,
+    ) -> Result<(), String> {
+        let other = 1 - seat;
+        match message {
+            LanMessage::RulesetAccepted if !self.seats[seat].is_accepted => {
+                self.seats[seat].is_accepted = true;
+                if self.seats[other].is_accepted {
+                    self.is_started = true;
+                    entries.push(self.get_started_entry(game_type, match_format, timestamp));
+                    // Client can commit as soon as it accepted, its commit waits until the opponent accepted too.
+                    for x in 0..2 {
+                        if let Some(hash) = self.seats[x].hash.clone() {
+                            self.send(1 - x, &LanMessage::Commit { round: self.round, hash })?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            LanMessage::RulesetRejected { reason } => Err(format!("{} rejected ruleset: {}", self.seats[seat].name, reason)),
+            // Round messages sent again after a resume are skipped.
+            LanMessage::Commit { round, hash } if round < self.round || self.seats[seat].hash.as_ref() == Some(&hash) => Ok(()),
+            LanMessage::Reveal { round, .. } if round < self.round => Ok(()),
+            ref x @ LanMessage::Reveal { .. } if self.seats[seat].reveal.as_ref().is_some_and(|(_, y)| y == x) => Ok(()),
+            LanMessage::Commit { round, hash }
+                if self.seats[seat].is_accepted && !self.is_finished && round == self.round && self.seats[seat].hash.is_none() =>
+            {
+                self.seats[seat].hash = Some(hash.clone());
+                self.seats[seat].round_messages.push(LanMessage::Commit { round, hash: hash.clone() });
+                if self.is_started {
+                    self.send(other, &LanMessage::Commit { round, hash })?;
+                }
+                Ok(())
+            }
+            LanMessage::Reveal { round, game_move, nonce }
+                if self.is_started && !self.is_finished && round == self.round && self.seats[seat].reveal.is_none() && self.seats[other].hash.is_some() =>
+            {
+                let hash = self.seats[seat].hash.as_deref().unwrap_or_default();
+                let index = game_type
+                    .ruleset()
+                    .find_move(&game_move)
+                    .filter(|_| verify(&game_move, &nonce, hash))
+                    .ok_or_else(|| format!("{} revealed move which does not match commitment", self.seats[seat].name))?;
+                self.seats[seat].reveal = Some((GameMove(index), LanMessage::Reveal { round, game_move, nonce }));
+                if self.seats[other].reveal.is_some() {
+                    self.finish_round(game_type, match_format, timestamp, entries)?;
+                }
+                Ok(())
+            }
+            // Rematch is played only after the match was decided, then no commit can be on the way. Rounds keep counting, so
+            // messages of the previous match are skipped as old ones.
+            LanMessage::Rematch if self.is_finished && !self.seats[seat].is_rematch_requested => {
+                self.seats[seat].is_rematch_requested = true;
+                self.send(other, &LanMessage::Rematch)?;
+                if self.seats[other].is_rematch_requested {
+                    self.id = Uuid::new_v4();
+                    self.score = ResultCounts::default();
+                    self.is_finished = false;
+                    for x in self.seats.iter_mut() {
+                        x.hash = None;
+                        x.reveal = None;
+                        x.is_rematch_requested = false;
+                        x.round_messages.clear();
+                    }
+                    entries.push(self.get_started_entry(game_type, match_format, timestamp));
+                    self.spectators.broadcast(&LanMessage::MatchRestarted);
+                }
+                Ok(())
+            }
+            // Chat sent before both players accepted the ruleset is dropped.
+            LanMessage::Chat { content } => {
+                if let Some(content) = content.sanitize().filter(|_| self.is_started) {
+                    self.send(other, &LanMessage::Chat { content })?;
+                }
+                Ok(())
+            }
+            LanMessage::Ping => Ok(()),
+            LanMessage::Bye => Err(format!("{} left", self.seats[seat].name)),
+            x => Err(format!("Unexpected message from {}: {:?}", self.seats[seat].name, x)),
+        }
+    }
+
+    fn finish_round(&mut self, game_type: &GameType, match_format: MatchFormat, timestamp: u64, entries: &mut Vec<ServerLogEntry>) -> Result<(), String> {
+        let (first_move, first_reveal) = self.seats[0].reveal.take().unwrap();
+        let (second_move, second_reveal) = self.seats[1].reveal.take().unwrap();
+        self.send(0, &second_reveal)?;
+        self.send(1, &first_reveal)?;
+        self.seats[0].round_messages.push(first_reveal);
+        self.seats[1].round_messages.push(second_reveal);
+        let result = first_move.beats_other(game_type, &second_move);
+        self.score.add(result);
+        self.spectators
+            .broadcast(&get_round_played(game_type, self.round, (first_move, second_move), &self.score));
+        entries.push(ServerLogEntry::RoundPlayed {
+            timestamp,
+            match_id: self.id,
+            round: self.round,
+            moves: [first_move.get_name(game_type).to_string(), second_move.get_name(game_type).to_string()],
+            winner: self.get_winner_name(result),
+        });
+        self.round += 1;
+        let round = self.round;
+        for x in self.seats.iter_mut() {
+            x.hash = None;
+            x.round_messages.retain(|x| match x {
+                LanMessage::Commit { round: x, .. } | LanMessage::Reveal { round: x, .. } => x + 1 >= round,
+                _ => false,
+            });
+        }
+        let winner = match_format.get_winner(self.score.wins as u32, self.score.loses as u32);
+        if winner.is_some() {
+            self.is_finished = true;
+            entries.push(self.get_finished_entry(winner, String::from("Match decided"), timestamp));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    game_type: GameType,
+    match_format: MatchFormat,
+    log_path: PathBuf,
+    waiting: Vec<WaitingClient>,
+    matches: Vec<ServerMatch>,
+}
+
+impl Server {
+    pub fn new(config: &ServerConfig) -> Result<Self, String> {
+        let ruleset = Rulesets::load_dir(&config.rulesets_path)
+            .0
+            .into_iter()
+            .find(|x| x.id == config.ruleset_id)
+            .ok_or_else(|| format!("Ruleset {} not found in {}", config.ruleset_id, config.rulesets_path.display()))?;
+        ruleset.validate().map_err(|e| format!("Ruleset {} is invalid: {}", ruleset.id, e))?;
+        let listener = listen(config.port).map_err(|e| format!("Unable to listen on port {}: {}", config.port, e))?;
+        Ok(Self {
+            listener,
+            game_type: GameType::Ruleset(ruleset),
+            match_format: config.match_format,
+            log_path: config.log_path.clone(),
+            waiting: Vec::new(),
+            matches: Vec::new(),
+        })
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.listener.local_addr().map(|x| x.port()).unwrap_or_default()
+    }
+
+    pub fn get_game_type(&self) -> &GameType {
+        &self.game_type
+    }
+
+    pub fn get_match_format(&self) -> MatchFormat {
+        self.match_format
+    }
+
+    // Accepts and pairs clients and plays all matches as far as received messages allow. Returns entries which were
+    // written to the log.
+    pub fn step(&mut self, timestamp: u64) -> Vec<ServerLogEntry> {
+        let mut entries = Vec::new();
+        while let Ok(Some(connection)) = accept(&self.listener) {
+            self.waiting.push(WaitingClient {
+                connection,
+                hello: None,
+                spectator_name: None,
+                resume: None,
+            });
+        }
+        self.waiting.retain_mut(receive_hello);
+        self.resume_clients();
+        self.pair_clients();
+
+        let mut finished = Vec::new();
+        for (index, x) in self.matches.iter_mut().enumerate() {
+            if let Err(reason) = step_match(x, &self.game_type, self.match_format, timestamp, &mut entries) {
+                for connection in x.seats.iter_mut().filter_map(|x| x.connection.as_mut()) {
+                    let _ = connection.send(&LanMessage::Bye);
+                }
+                x.spectators.close();
+                if x.is_started && !x.is_finished {
+                    entries.push(x.get_finished_entry(None, reason, timestamp));
+                }
+                finished.push(index);
+            }
+        }
+        for index in finished.into_iter().rev() {
+            self.matches.remove(index);
+        }
+        self.attach_spectators();
+
+        for entry in entries.iter() {
+            if let Err(e) = append_server_log(&self.log_path, entry) {
+                eprintln!("Unable to write server log {}: {}", self.log_path.display(), e);
+            }
+        }
+        entries
+    }
+
+    // Player who lost the connection takes its seat back by the profile from Hello. Resume without such seat is dropped.
+    fn resume_clients(&mut self) {
+        for client in std::mem::take(&mut self.waiting) {
+            let Some(profile) = client.resume else {
+                self.waiting.push(client);
+                continue;
+            };
+            let seat = self
+                .matches
+                .iter_mut()
+                .filter(|x| x.is_started)
+                .find_map(|x| x.seats.iter().position(|y| y.profile == profile).map(|y| (x, y)));
+            if let Some((server_match, seat)) = seat {
+                // Connection that fails right away is dropped again by the next step.
+                let _ = server_match.resume(seat, client.connection);
+            }
+        }
+    }
+
+    fn pair_clients(&mut self) {
+        loop {
+            let ready: Vec<usize> = self
+                .waiting
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| x.hello.is_some())
+                .map(|(i, _)| i)
+                .take(2)
+                .collect();
+            if ready.len() < 2 {
+                return;
+            }
+            let second = self.waiting.remove(ready[1]);
+            let first = self.waiting.remove(ready[0]);
+            let (first_name, first_profile) = first.hello.unwrap();
+            let (second_name, second_profile) = second.hello.unwrap();
+            let ruleset = self.game_type.ruleset();
+            let proposal = LanMessage::Ruleset {
+                id: ruleset.id.clone(),
+                fingerprint: get_ruleset_fingerprint(ruleset),
+                match_format: self.match_format,
+            };
+            let mut server_match = ServerMatch {
+                id: Uuid::new_v4(),
+                seats: [
+                    Seat::new(first.connection, first_name.clone(), first_profile),
+                    Seat::new(second.connection, second_name.clone(), second_profile),
+                ],
+                round: 0,
+                score: ResultCounts::default(),
+                is_started: false,
+                is_finished: false,
+                spectators: Spectators::default(),
+            };
+            // Each client sees the other one as the host, so the game client needs nothing special for the server.
+            for (seat, name, profile) in [(0, second_name, second_profile), (1, first_name, first_profile)] {
+                let hello = LanMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    name,
+                    profile,
+                };
+                let _ = server_match.send(seat, &hello).and_then(|_| server_match.send(seat, &proposal));
+            }
+            self.matches.push(server_match);
+        }
+    }
+
+    // Spectators watch the newest started match, they wait while there is none.
+    fn attach_spectators(&mut self) {
+        let Some(server_match) = self.matches.iter_mut().rev().find(|x| x.is_started) else {
+            return;
+        };
+        let welcome = server_match.get_welcome(&self.game_type, self.match_format);
+        for client in std::mem::take(&mut self.waiting) {
+            match client.spectator_name {
+                Some(name) => server_match.spectators.watch(client.connection, name, &welcome),
+                None => self.waiting.push(client),
+            }
+        }
+    }
+}
+
+// Client which sends anything else than Hello or Resume of this protocol version or Spectate is dropped. Returns false
+// when the client should be dropped.
+fn receive_hello(client: &mut WaitingClient) -> bool {
+    let messages = match client.connection.receive() {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+    let mut messages = messages.into_iter();
+    while let Some(message) = messages.next() {
+        match message {
+            LanMessage::Hello { version, name, profile } if version == PROTOCOL_VERSION && !client.is_introduced() => {
+                client.hello = Some((name, profile));
+            }
+            // Messages after Resume belong to the match, they are read again once the seat is taken back.
+            LanMessage::Resume { version, profile } if version == PROTOCOL_VERSION && !client.is_introduced() => {
+                client.resume = Some(profile);
+                client.connection.unread(messages.collect());
+                break;
+            }
+            LanMessage::Spectate { name, .. } if !client.is_introduced() => {
+                client.spectator_name = Some(name);
+            }
+            LanMessage::Ping if client.hello.is_some() => {}
+            LanMessage::Hello { .. } => {
+                // Client reports version mismatch when it gets Hello of another version.
+                let _ = client.connection.send(&LanMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    name: String::from(SERVER_NAME),
+                    profile: Uuid::nil(),
+                });
+                return false;
+            }
+            _ => return false,
+        }
+    }
+    client.connection.flush().and_then(|_| client.connection.keep_alive()).is_ok()
+}
+
+fn step_match(
+    server_match: &mut ServerMatch,
+    game_type: &GameType,
+    match_format: MatchFormat,
+    timestamp: u64,
+    entries: &mut Vec<ServerLogEntry>,
+) -> Result<(), String> {
+    for seat in 0..2 {
+        let Some(connection) = server_match.seats[seat].connection.as_mut() else {
+            continue;
+        };
+        match connection.receive() {
+            Ok(messages) => {
+                for message in messages {
+                    server_match.handle(seat, message, game_type, match_format, timestamp, entries)?;
+                }
+            }
+            Err(e) => server_match.drop_seat(seat, e)?,
+        }
+    }
+    for seat in 0..2 {
+        let Some(connection) = server_match.seats[seat].connection.as_mut() else {
+            continue;
+        };
+        if let Err(e) = connection.flush().and_then(|_| connection.keep_alive()) {
+            server_match.drop_seat(seat, e)?;
+        }
+    }
+    server_match.check_forfeit(timestamp, entries)?;
+    server_match.spectators.update(|_| false, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::thread;
+    use std::time::Duration;
+
+    use rand::rngs::mock::StepRng;
+
+    use crate::chat::{ChatContent, Emote};
+    use crate::commitment::get_hash;
+    use crate::lan::{poll, LanEvent, LanSession};
+    use crate::spectator::{poll_spectator, SpectatorEvent, SpectatorSession};
+
+    use crate::test_support::bundled_rulesets;
+
+    use super::*;
+
+    fn start_server(name: &str, match_format: MatchFormat) -> Server {
+        let log_path = env::temp_dir().join(format!("rps-server-{}-{}.log", name, Uuid::new_v4()));
+        let config = ServerConfig {
+            port: 0,
+            match_format,
+            log_path,
+            ..ServerConfig::default()
+        };
+        Server::new(&config).unwrap()
+    }
+
+    fn connect(server: &Server, name: &str) -> (LanConnection, LanSession) {
+        let connection = LanConnection::connect(&format!("127.0.0.1:{}", server.get_port())).unwrap();
+        (connection, LanSession::new_guest(name.to_string(), Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_config_from_args() {
+        let args = ["--port", "9000", "--ruleset", "rps_7", "--format", "first-to-5", "--log", "matches.log"];
+        let config = ServerConfig::from_args(args.iter().map(|x| x.to_string())).unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.ruleset_id, "rps_7");
+        assert_eq!(config.match_format, MatchFormat::FirstTo(5));
+        assert_eq!(config.log_path, PathBuf::from("matches.log"));
+        assert_eq!(config.rulesets_path, get_rulesets_dir());
+
+        assert_eq!(ServerConfig::from_args(std::iter::empty()), Ok(ServerConfig::default()));
+        assert!(ServerConfig::from_args(["--port"].iter().map(|x| x.to_string())).is_err());
+        assert!(ServerConfig::from_args(["--format", "best-of-0"].iter().map(|x| x.to_string())).is_err());
+        assert!(ServerConfig::from_args(["--seed", "1"].iter().map(|x| x.to_string())).is_err());
+    }
+
+    #[test]
+    fn test_scripted_match() {
+        let mut server = start_server("match", MatchFormat::FirstTo(2));
+        let (mut alice_connection, mut alice) = connect(&server, "Alice");
+        let (mut bob_connection, mut bob) = connect(&server, "Bob");
+        let mut carol_connection = LanConnection::connect(&format!("127.0.0.1:{}", server.get_port())).unwrap();
+        let mut carol = SpectatorSession::new(String::from("Carol"));
+        let mut carol_events = Vec::new();
+        // Rock against Scissors, Paper against Paper, Rock against Scissors.
+        let alice_moves = [0, 1, 0];
+        let bob_moves = [2, 1, 2];
+        let mut bob_events = Vec::new();
+        let mut is_chat_sent = false;
+        for _ in 0..1000 {
+            server.step(100);
+            poll(&mut alice_connection, &mut alice, bundled_rulesets()).unwrap();
+            bob_events.extend(poll(&mut bob_connection, &mut bob, bundled_rulesets()).unwrap());
+            carol_events.extend(poll_spectator(&mut carol_connection, &mut carol, bundled_rulesets()).unwrap());
+            if let Some(x) = alice_moves.get(alice.round as usize) {
+                alice.commit(GameMove(*x), &mut StepRng::new(alice.round as u64, 1));
+            }
+            if let Some(x) = bob_moves.get(bob.round as usize) {
+                bob.commit(GameMove(*x), &mut StepRng::new(bob.round as u64 + 7, 1));
+            }
+            if alice.round == 1 && !is_chat_sent {
+                is_chat_sent = alice.chat(ChatContent::Emote(Emote::GoodGame));
+            }
+            if alice.get_winner().is_some() && bob.get_winner().is_some() && carol.get_winner().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(alice.get_opponent_name(), "Bob");
+        assert_eq!(bob.get_opponent_name(), "Alice");
+        assert_eq!(alice.get_winner(), Some(GameResult::Win));
+        assert_eq!(bob.get_winner(), Some(GameResult::Lose));
+        assert!(bob_events.contains(&LanEvent::RoundFinished(GameResult::Draw)));
+        assert!(bob_events.contains(&LanEvent::Chat(ChatContent::Emote(Emote::GoodGame))));
+        assert_eq!(carol.players, [String::from("Alice"), String::from("Bob")]);
+        assert_eq!(carol_events.iter().filter(|x| **x == SpectatorEvent::RoundPlayed).count(), 3);
+        assert_eq!(carol.get_winner(), Some(GameResult::Win));
+
+        let log = load_server_log(&server.log_path);
+        fs::remove_file(&server.log_path).unwrap();
+        assert_eq!(log.len(), 5);
+        let match_id = match &log[0] {
+            ServerLogEntry::MatchStarted {
+                match_id,
+                ruleset,
+                match_format,
+                players,
+                ..
+            } => {
+                assert_eq!(ruleset, "normal");
+                assert_eq!(*match_format, MatchFormat::FirstTo(2));
+                assert_eq!(players, &[String::from("Alice"), String::from("Bob")]);
+                *match_id
+            }
+            x => panic!("Unexpected entry {:?}", x),
+        };
+        assert_eq!(
+            log[2],
+            ServerLogEntry::RoundPlayed {
+                timestamp: 100,
+                match_id,
+                round: 1,
+                moves: [String::from("Paper"), String::from("Paper")],
+                winner: None,
+            }
+        );
+        assert_eq!(
+            log[4],
+            ServerLogEntry::MatchFinished {
+                timestamp: 100,
+                match_id,
+                wins: [2, 0],
+                draws: 1,
+                winner: Some(String::from("Alice")),
+                reason: String::from("Match decided"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_resume_after_drop() {
+        let mut server = start_server("resume", MatchFormat::FirstTo(2));
+        let (mut alice_connection, mut alice) = connect(&server, "Alice");
+        let (bob_connection, mut bob) = connect(&server, "Bob");
+        let mut bob_connection = Some(bob_connection);
+        let mut alice_events = Vec::new();
+        let mut bob_events = Vec::new();
+        let mut is_dropped = false;
+        // Rock against Scissors twice. Bob loses the connection after the first round and Alice locks in the second
+        // round while he is away.
+        for _ in 0..1000 {
+            server.step(100);
+            alice_events.extend(poll(&mut alice_connection, &mut alice, bundled_rulesets()).unwrap());
+            if let Some(x) = bob_connection.as_mut() {
+                bob_events.extend(poll(x, &mut bob, bundled_rulesets()).unwrap());
+            }
+            alice.commit(GameMove(0), &mut StepRng::new(alice.round as u64, 1));
+            if bob.round == 1 && !is_dropped {
+                bob_connection = None;
+                is_dropped = true;
+            }
+            if bob_connection.is_none() && server.matches[0].seats[1].connection.is_none() && server.matches[0].seats[0].hash.is_some() {
+                bob.rejoin();
+                bob_connection = Some(LanConnection::connect(&format!("127.0.0.1:{}", server.get_port())).unwrap());
+            }
+            if bob_connection.is_some() {
+                bob.commit(GameMove(2), &mut StepRng::new(bob.round as u64 + 7, 1));
+            }
+            if alice.get_winner().is_some() && bob.get_winner().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(alice.get_winner(), Some(GameResult::Win));
+        assert_eq!(bob.get_winner(), Some(GameResult::Lose));
+        assert!(alice_events.contains(&LanEvent::Resumed));
+        let resumed = bob_events.iter().position(|x| *x == LanEvent::Resumed).unwrap();
+        assert_eq!(bob_events[resumed + 1], LanEvent::OpponentCommitted);
+
+        let log = load_server_log(&server.log_path);
+        fs::remove_file(&server.log_path).unwrap();
+        assert_eq!(log.len(), 4);
+        match &log[3] {
+            ServerLogEntry::MatchFinished { wins, winner, reason, .. } => {
+                assert_eq!(*wins, [2, 0]);
+                assert_eq!(*winner, Some(String::from("Alice")));
+                assert_eq!(reason, "Match decided");
+            }
+            x => panic!("Unexpected entry {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_forfeit_after_grace_period() {
+        let mut server = start_server("forfeit", MatchFormat::BestOf(7));
+        let (mut alice_connection, mut alice) = connect(&server, "Alice");
+        let (bob_connection, mut bob) = connect(&server, "Bob");
+        let mut bob_connection = Some(bob_connection);
+        let mut timestamp = 100;
+        let mut result = Ok(Vec::new());
+        for _ in 0..1000 {
+            server.step(timestamp);
+            result = poll(&mut alice_connection, &mut alice, bundled_rulesets());
+            if result.is_err() {
+                break;
+            }
+            if let Some(x) = bob_connection.as_mut() {
+                poll(x, &mut bob, bundled_rulesets()).unwrap();
+            }
+            if server.matches[..].first().is_some_and(|x| x.is_started) {
+                bob_connection = None;
+            }
+            if server.matches[..].first().is_some_and(|x| x.seats[1].dropped_at.is_some()) {
+                timestamp += 1;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(result, Err(LanError::OpponentForfeited));
+
+        // Seat is given up once the match is forfeited.
+        let mut bob_connection = LanConnection::connect(&format!("127.0.0.1:{}", server.get_port())).unwrap();
+        bob.rejoin();
+        let mut result = Ok(Vec::new());
+        for _ in 0..1000 {
+            server.step(timestamp);
+            result = poll(&mut bob_connection, &mut bob, bundled_rulesets());
+            if result.is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert!(result.unwrap_err().is_connection_lost());
+
+        let log = load_server_log(&server.log_path);
+        fs::remove_file(&server.log_path).unwrap();
+        assert_eq!(log.len(), 2);
+        match &log[1] {
+            ServerLogEntry::MatchFinished {
+                timestamp,
+                wins,
+                winner,
+                reason,
+                ..
+            } => {
+                assert_eq!(*timestamp, 100 + RECONNECT_GRACE.as_secs());
+                assert_eq!(*wins, [0, 0]);
+                assert_eq!(*winner, Some(String::from("Alice")));
+                assert_eq!(reason, "Bob did not come back, match is forfeited");
+            }
+            x => panic!("Unexpected entry {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_rematch() {
+        let mut server = start_server("rematch", MatchFormat::FirstTo(1));
+        let (mut alice_connection, mut alice) = connect(&server, "Alice");
+        let (mut bob_connection, mut bob) = connect(&server, "Bob");
+        let mut alice_events = Vec::new();
+        // Alice wins with Rock against Scissors, then Bob wins the rematch with Paper against Rock.
+        for _ in 0..1000 {
+            server.step(300);
+            alice_events.extend(poll(&mut alice_connection, &mut alice, bundled_rulesets()).unwrap());
+            poll(&mut bob_connection, &mut bob, bundled_rulesets()).unwrap();
+            let is_rematch = alice_events.contains(&LanEvent::MatchRestarted);
+            alice.commit(GameMove(0), &mut StepRng::new(alice.round as u64, 1));
+            bob.commit(GameMove(if is_rematch { 1 } else { 2 }), &mut StepRng::new(bob.round as u64 + 7, 1));
+            if !is_rematch && alice.get_winner().is_some() && bob.get_winner().is_some() {
+                alice.request_rematch();
+                bob.request_rematch();
+            }
+            if is_rematch && bob.get_winner().is_some() && alice.get_winner().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(alice.get_winner(), Some(GameResult::Lose));
+        assert_eq!(bob.get_winner(), Some(GameResult::Win));
+
+        let log = load_server_log(&server.log_path);
+        fs::remove_file(&server.log_path).unwrap();
+        let winners: Vec<Option<String>> = log
+            .iter()
+            .filter_map(|x| match x {
+                ServerLogEntry::MatchFinished { winner, .. } => Some(winner.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(winners, vec![Some(String::from("Alice")), Some(String::from("Bob"))]);
+        assert_eq!(log.iter().filter(|x| matches!(x, ServerLogEntry::MatchStarted { .. })).count(), 2);
+    }
+
+    #[test]
+    fn test_forged_reveal() {
+        let mut server = start_server("forged", MatchFormat::BestOf(3));
+        let (mut honest_connection, mut honest) = connect(&server, "Honest");
+        let mut cheater = LanConnection::connect(&format!("127.0.0.1:{}", server.get_port())).unwrap();
+        cheater
+            .send(&LanMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: String::from("Cheater"),
+                profile: Uuid::new_v4(),
+            })
+            .unwrap();
+        let mut result = Ok(Vec::new());
+        for _ in 0..1000 {
+            server.step(200);
+            result = poll(&mut honest_connection, &mut honest, bundled_rulesets());
+            if result.is_err() {
+                break;
+            }
+            honest.commit(GameMove(0), &mut StepRng::new(3, 1));
+            // Cheater commits to Rock and reveals Paper once it saw the honest commit.
+            for message in cheater.receive().unwrap_or_default() {
+                match message {
+                    LanMessage::Ruleset { .. } => {
+                        cheater.send(&LanMessage::RulesetAccepted).unwrap();
+                        let hash = get_hash("Rock", "00");
+                        cheater.send(&LanMessage::Commit { round: 0, hash }).unwrap();
+                    }
+                    LanMessage::Commit { .. } => {
+                        let reveal = LanMessage::Reveal {
+                            round: 0,
+                            game_move: String::from("Paper"),
+                            nonce: String::from("00"),
+                        };
+                        cheater.send(&reveal).unwrap();
+                    }
+                    _ => {}
+                }
+            }
+            let _ = cheater.flush();
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(result, Err(LanError::OpponentLeft));
+        assert_eq!(honest.round, 0);
+
+        let log = load_server_log(&server.log_path);
+        fs::remove_file(&server.log_path).unwrap();
+        assert_eq!(log.len(), 2);
+        match &log[1] {
+            ServerLogEntry::MatchFinished { wins, winner, reason, .. } => {
+                assert_eq!(*wins, [0, 0]);
+                assert_eq!(*winner, None);
+                assert_eq!(reason, "Cheater revealed move which does not match commitment");
+            }
+            x => panic!("Unexpected entry {:?}", x),
+        }
+    }
+}
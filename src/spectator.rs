@@ -0,0 +1,358 @@
+use crate::game_move::GameMove;
+use crate::game_result::{GameResult, ResultCounts};
+use crate::game_type::GameType;
+use crate::lan::{get_ruleset_fingerprint, LanConnection, LanError, LanMessage, PROTOCOL_VERSION};
+use crate::match_format::MatchFormat;
+use crate::ruleset::Rulesets;
+
+#[derive(Debug)]
+struct Newcomer {
+    connection: LanConnection,
+    spectator_name: Option<String>,
+}
+
+// Connections accepted by the host are newcomers until their first message tells whether they want to play or watch.
+// Spectators get the match only after it started, they never send anything but Bye.
+#[derive(Debug, Default)]
+pub struct Spectators {
+    newcomers: Vec<Newcomer>,
+    watching: Vec<(LanConnection, String)>,
+}
+
+impl Spectators {
+    pub fn add(&mut self, connection: LanConnection) {
+        self.newcomers.push(Newcomer {
+            connection,
+            spectator_name: None,
+        });
+    }
+
+    pub fn watch(&mut self, mut connection: LanConnection, name: String, welcome: &LanMessage) {
+        if connection.send(welcome).is_ok() {
+            self.watching.push((connection, name));
+        }
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        self.watching.iter().map(|(_, name)| name.as_str()).collect()
+    }
+
+    // Returns the first newcomer whose first message is wanted from a player (Hello or Resume), its messages are left
+    // unread for the session. Other players are sent Bye. Waiting spectators are welcomed once there is a welcome.
+    pub fn update(&mut self, is_player_wanted: impl Fn(&LanMessage) -> bool, welcome: Option<&LanMessage>) -> Option<LanConnection> {
+        let mut player = None;
+        for mut newcomer in std::mem::take(&mut self.newcomers) {
+            let Ok(messages) = newcomer.connection.receive() else {
+                continue;
+            };
+            if newcomer.spectator_name.is_none() {
+                match messages.first() {
+                    Some(LanMessage::Spectate { name, .. }) => newcomer.spectator_name = Some(name.clone()),
+                    Some(x) if player.is_none() && is_player_wanted(x) => {
+                        newcomer.connection.unread(messages);
+                        player = Some(newcomer.connection);
+                        continue;
+                    }
+                    Some(_) => {
+                        let _ = newcomer.connection.send(&LanMessage::Bye);
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+            match (newcomer.spectator_name, welcome) {
+                (Some(name), Some(welcome)) => self.watch(newcomer.connection, name, welcome),
+                (spectator_name, _) => self.newcomers.push(Newcomer {
+                    connection: newcomer.connection,
+                    spectator_name,
+                }),
+            }
+        }
+        self.watching
+            .retain_mut(|(connection, _)| connection.receive().is_ok_and(|x| !x.contains(&LanMessage::Bye)) && connection.flush().is_ok());
+        player
+    }
+
+    pub fn broadcast(&mut self, message: &LanMessage) {
+        self.watching.retain_mut(|(connection, _)| connection.send(message).is_ok());
+    }
+
+    pub fn close(&mut self) {
+        self.broadcast(&LanMessage::Bye);
+        for newcomer in self.newcomers.iter_mut().filter(|x| x.spectator_name.is_some()) {
+            let _ = newcomer.connection.send(&LanMessage::Bye);
+        }
+        *self = Spectators::default();
+    }
+}
+
+// Score is [wins of first player, wins of second player, draws].
+fn get_score(score: &ResultCounts) -> [usize; 3] {
+    [score.wins, score.loses, score.draws]
+}
+
+pub fn get_welcome(game_type: &GameType, match_format: MatchFormat, players: [String; 2], round: u32, score: &ResultCounts) -> LanMessage {
+    let ruleset = game_type.ruleset();
+    LanMessage::SpectatorWelcome {
+        version: PROTOCOL_VERSION,
+        id: ruleset.id.clone(),
+        fingerprint: get_ruleset_fingerprint(ruleset),
+        match_format,
+        players,
+        round,
+        score: get_score(score),
+    }
+}
+
+pub fn get_round_played(game_type: &GameType, round: u32, moves: (GameMove, GameMove), score: &ResultCounts) -> LanMessage {
+    LanMessage::RoundPlayed {
+        round,
+        moves: [moves.0.get_name(game_type).to_string(), moves.1.get_name(game_type).to_string()],
+        score: get_score(score),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectatorEvent {
+    Welcomed,
+    RoundPlayed,
+    MatchRestarted,
+}
+
+// Read-only view of a match, score and last round are from the first player's point of view.
+#[derive(Debug)]
+pub struct SpectatorSession {
+    pub name: String,
+    pub game_type: GameType,
+    pub match_format: MatchFormat,
+    pub players: [String; 2],
+    pub round: u32,
+    pub score: ResultCounts,
+    pub last_round: Option<(GameMove, GameMove)>,
+    pub is_ready: bool,
+    outgoing: Vec<LanMessage>,
+}
+
+impl SpectatorSession {
+    pub fn new(name: String) -> Self {
+        Self {
+            outgoing: vec![LanMessage::Spectate {
+                version: PROTOCOL_VERSION,
+                name: name.clone(),
+            }],
+            name,
+            game_type: GameType::None,
+            match_format: MatchFormat::default(),
+            players: Default::default(),
+            round: 0,
+            score: ResultCounts::default(),
+            last_round: None,
+            is_ready: false,
+        }
+    }
+
+    pub fn take_outgoing(&mut self) -> Vec<LanMessage> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    pub fn get_winner(&self) -> Option<GameResult> {
+        self.match_format.get_winner(self.score.wins as u32, self.score.loses as u32)
+    }
+
+    pub fn leave(&mut self) {
+        self.outgoing.push(LanMessage::Bye);
+    }
+
+    pub fn handle(&mut self, message: LanMessage, rulesets: &Rulesets) -> Result<Option<SpectatorEvent>, LanError> {
+        match message {
+            LanMessage::SpectatorWelcome {
+                version,
+                id,
+                fingerprint,
+                match_format,
+                players,
+                round,
+                score,
+            } if !self.is_ready => {
+                if version != PROTOCOL_VERSION {
+                    return Err(LanError::VersionMismatch(version));
+                }
+                let ruleset = rulesets
+                    .0
+                    .iter()
+                    .find(|x| x.id == id && x.validate().is_ok() && get_ruleset_fingerprint(x) == fingerprint)
+                    .ok_or_else(|| LanError::RulesetMismatch(format!("ruleset {} is missing or different on {}", id, self.name)))?;
+                self.game_type = GameType::Ruleset(ruleset.clone());
+                self.match_format = match_format;
+                self.players = players;
+                self.round = round;
+                self.score = ResultCounts {
+                    wins: score[0],
+                    loses: score[1],
+                    draws: score[2],
+                };
+                self.is_ready = true;
+                Ok(Some(SpectatorEvent::Welcomed))
+            }
+            LanMessage::RoundPlayed { round, moves, score } if self.is_ready => {
+                let ruleset = self.game_type.ruleset();
+                let [first, second] = moves.map(|x| ruleset.find_move(&x).map(GameMove));
+                let (Some(first), Some(second)) = (first, second) else {
+                    return Err(LanError::InvalidMessage(format!("Unknown move in round {}", round)));
+                };
+                self.last_round = Some((first, second));
+                self.round = round + 1;
+                self.score = ResultCounts {
+                    wins: score[0],
+                    loses: score[1],
+                    draws: score[2],
+                };
+                Ok(Some(SpectatorEvent::RoundPlayed))
+            }
+            LanMessage::MatchRestarted if self.is_ready => {
+                self.score = ResultCounts::default();
+                self.last_round = None;
+                Ok(Some(SpectatorEvent::MatchRestarted))
+            }
+            LanMessage::Bye => Err(LanError::MatchEnded),
+            x => Err(LanError::UnexpectedMessage(format!("{:?}", x))),
+        }
+    }
+}
+
+pub fn poll_spectator(connection: &mut LanConnection, session: &mut SpectatorSession, rulesets: &Rulesets) -> Result<Vec<SpectatorEvent>, LanError> {
+    for message in session.take_outgoing() {
+        connection.send(&message)?;
+    }
+    connection.flush()?;
+    let mut events = Vec::new();
+    for message in connection.receive()? {
+        events.extend(session.handle(message, rulesets)?);
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use rand::rngs::mock::StepRng;
+    use uuid::Uuid;
+
+    use crate::lan::{accept, listen, poll, LanEvent, LanSession};
+    use crate::test_support::{bundled_game_type, bundled_rulesets};
+
+    use super::*;
+
+    #[test]
+    fn test_spectator_session() {
+        let game_type = bundled_game_type("normal");
+        let mut session = SpectatorSession::new(String::from("Carol"));
+        assert_eq!(
+            session.take_outgoing(),
+            vec![LanMessage::Spectate {
+                version: PROTOCOL_VERSION,
+                name: String::from("Carol"),
+            }]
+        );
+        let round = get_round_played(&game_type, 0, (GameMove(0), GameMove(2)), &ResultCounts::default());
+        assert!(session.handle(round.clone(), bundled_rulesets()).is_err());
+
+        let score = ResultCounts { wins: 1, loses: 0, draws: 2 };
+        let players = [String::from("Alice"), String::from("Bob")];
+        let welcome = get_welcome(&game_type, MatchFormat::BestOf(3), players.clone(), 3, &score);
+        assert_eq!(session.handle(welcome, bundled_rulesets()), Ok(Some(SpectatorEvent::Welcomed)));
+        assert_eq!(session.players, players);
+        assert_eq!(session.score, score);
+
+        let score = ResultCounts { wins: 2, loses: 0, draws: 2 };
+        let round = get_round_played(&game_type, 3, (GameMove(1), GameMove(0)), &score);
+        assert_eq!(session.handle(round, bundled_rulesets()), Ok(Some(SpectatorEvent::RoundPlayed)));
+        assert_eq!(session.last_round, Some((GameMove(1), GameMove(0))));
+        assert_eq!(session.round, 4);
+        assert_eq!(session.get_winner(), Some(GameResult::Win));
+
+        let forged = LanMessage::RoundPlayed {
+            round: 4,
+            moves: [String::from("Rock"), String::from("Spock")],
+            score: [2, 0, 2],
+        };
+        assert!(matches!(session.handle(forged, bundled_rulesets()), Err(LanError::InvalidMessage(_))));
+        assert_eq!(session.handle(LanMessage::Bye, bundled_rulesets()), Err(LanError::MatchEnded));
+    }
+
+    // Spectator of a hosted match sees a round only after both players revealed.
+    #[test]
+    fn test_watch_on_localhost() {
+        let listener = listen(0).unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let mut spectator_connection = LanConnection::connect(&address).unwrap();
+        let mut spectator = SpectatorSession::new(String::from("Carol"));
+        poll_spectator(&mut spectator_connection, &mut spectator, bundled_rulesets()).unwrap();
+        let mut guest_connection = LanConnection::connect(&address).unwrap();
+        let mut host = LanSession::new_host(String::from("Alice"), Uuid::new_v4(), bundled_game_type("normal"), MatchFormat::BestOf(1));
+        let mut guest = LanSession::new_guest(String::from("Bob"), Uuid::new_v4());
+        let mut spectators = Spectators::default();
+        let mut host_connection = None;
+        let mut spectator_events = Vec::new();
+        for _ in 0..1000 {
+            while let Ok(Some(x)) = accept(&listener) {
+                spectators.add(x);
+            }
+            let welcome = host.is_ready.then(|| {
+                get_welcome(
+                    &host.game_type,
+                    host.match_format,
+                    [host.name.clone(), guest.name.clone()],
+                    host.round,
+                    &host.score,
+                )
+            });
+            let is_player_wanted = |x: &LanMessage| host_connection.is_none() && matches!(x, LanMessage::Hello { .. });
+            if let Some(x) = spectators.update(is_player_wanted, welcome.as_ref()) {
+                host_connection = Some(x);
+            }
+            if let Some(connection) = host_connection.as_mut() {
+                for event in poll(connection, &mut host, bundled_rulesets()).unwrap() {
+                    if event == LanEvent::RoundFinished(GameResult::Win) {
+                        spectators.broadcast(&get_round_played(&host.game_type, host.round - 1, host.last_round.unwrap(), &host.score));
+                    }
+                }
+            }
+            poll(&mut guest_connection, &mut guest, bundled_rulesets()).unwrap();
+            spectator_events.extend(poll_spectator(&mut spectator_connection, &mut spectator, bundled_rulesets()).unwrap());
+            if spectator.is_ready && host.commitment.is_none() && host.round == 0 {
+                assert_eq!(spectators.get_names(), vec!["Carol"]);
+                host.commit(GameMove(0), &mut StepRng::new(1, 1));
+            }
+            if host.commitment.is_some() && guest.round == 0 {
+                assert!(spectator.last_round.is_none());
+                guest.commit(GameMove(2), &mut StepRng::new(2, 1));
+            }
+            if spectator_events.contains(&SpectatorEvent::RoundPlayed) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(spectator_events, vec![SpectatorEvent::Welcomed, SpectatorEvent::RoundPlayed]);
+        assert_eq!(spectator.players, [String::from("Alice"), String::from("Bob")]);
+        assert_eq!(spectator.last_round, Some((GameMove(0), GameMove(2))));
+        assert_eq!(spectator.get_winner(), Some(GameResult::Win));
+        assert_eq!(
+            GameMove::get_phrase(&spectator.game_type, &GameMove(0), &GameMove(2)),
+            GameMove::get_phrase(&host.game_type, &GameMove(0), &GameMove(2))
+        );
+
+        spectators.close();
+        let mut result = Ok(Vec::new());
+        for _ in 0..500 {
+            result = poll_spectator(&mut spectator_connection, &mut spectator, bundled_rulesets());
+            if result.is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(result, Err(LanError::MatchEnded));
+    }
+}
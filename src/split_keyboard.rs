@@ -0,0 +1,436 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::button::{spawn_button, Actions};
+use crate::common::*;
+use crate::game_move::GameMove;
+use crate::game_result::GameResult;
+use crate::game_settings::GameSettings;
+use crate::game_type::GameType;
+
+const MIN_KEYS: usize = 2;
+const RESERVED_KEYS: [KeyCode; 3] = [KeyCode::Escape, KeyCode::Return, KeyCode::Space];
+const MAX_LEGEND_MOVES: usize = 15;
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum SplitKeyboardState {
+    #[default]
+    NotInit,
+    Choosing,
+    RoundFinish,
+}
+
+impl LocalMatchState for SplitKeyboardState {
+    const NOT_INIT: Self = SplitKeyboardState::NotInit;
+    const NEXT_ROUND: Self = SplitKeyboardState::Choosing;
+}
+
+#[derive(Component)]
+pub struct OnSplitKeyboardScreen;
+
+#[derive(Component)]
+pub struct OnSplitKeyboardResultScreen;
+
+#[derive(Component)]
+pub struct OnLockIndicator(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPress {
+    Pending,
+    Locked(GameMove),
+    Invalid,
+}
+
+// Moves are entered as numbers written with the player's keys, first key is digit 0. When the game has no more moves
+// than keys, every move is one key press, otherwise a fixed number of presses is needed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SplitPlayer {
+    pub keys: Vec<KeyCode>,
+    pub digits: Vec<usize>,
+    pub locked: Option<GameMove>,
+    pub is_last_invalid: bool,
+}
+
+impl SplitPlayer {
+    pub fn new(keys: Vec<KeyCode>) -> Self {
+        Self { keys, ..default() }
+    }
+
+    pub fn get_number_of_presses(&self, number_of_moves: usize) -> usize {
+        let mut presses = 1;
+        let mut capacity = self.keys.len();
+        while capacity < number_of_moves {
+            presses += 1;
+            capacity *= self.keys.len();
+        }
+        presses
+    }
+
+    pub fn press(&mut self, key_code: KeyCode, number_of_moves: usize) -> Option<KeyPress> {
+        if self.locked.is_some() {
+            return None;
+        }
+        let digit = self.keys.iter().position(|x| *x == key_code)?;
+        self.digits.push(digit);
+        self.is_last_invalid = false;
+        if self.digits.len() < self.get_number_of_presses(number_of_moves) {
+            return Some(KeyPress::Pending);
+        }
+        let value = self.digits.iter().fold(0, |value, x| value * self.keys.len() + x);
+        self.digits.clear();
+        if value < number_of_moves {
+            self.locked = Some(GameMove(value));
+            Some(KeyPress::Locked(GameMove(value)))
+        } else {
+            self.is_last_invalid = true;
+            Some(KeyPress::Invalid)
+        }
+    }
+
+    pub fn get_key_sequence(&self, game_move: GameMove, number_of_moves: usize) -> String {
+        let presses = self.get_number_of_presses(number_of_moves);
+        let mut value = game_move.0;
+        let mut digits = vec![0; presses];
+        for x in digits.iter_mut().rev() {
+            *x = value % self.keys.len();
+            value /= self.keys.len();
+        }
+        digits.iter().map(|x| get_key_name(self.keys[*x])).collect::<Vec<&str>>().join("")
+    }
+
+    // Shows how far the player is, never which keys were pressed.
+    pub fn get_indicator_text(&self, number_of_moves: usize) -> String {
+        if self.locked.is_some() {
+            return String::from("Locked in!");
+        }
+        if self.is_last_invalid {
+            return String::from("No such move, try again");
+        }
+        let presses = self.get_number_of_presses(number_of_moves);
+        if presses == 1 {
+            return String::from("Choosing...");
+        }
+        format!("Choosing... {}{}", "*".repeat(self.digits.len()), "-".repeat(presses - self.digits.len()))
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SplitKeyboardPlayers(pub [SplitPlayer; 2]);
+
+const KEY_NAMES: [(KeyCode, &str); 46] = [
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Apostrophe, "'"),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Backslash, "\\"),
+    (KeyCode::BracketLeft, "["),
+    (KeyCode::BracketRight, "]"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equals, "="),
+];
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(_, x)| x.eq_ignore_ascii_case(name)).map(|(x, _)| *x)
+}
+
+pub fn get_key_name(key_code: KeyCode) -> &'static str {
+    KEY_NAMES.iter().find(|(x, _)| *x == key_code).map_or("?", |(_, x)| x)
+}
+
+pub fn default_split_keyboard_keys() -> [Vec<String>; 2] {
+    [
+        ["A", "S", "D", "F", "G"].map(String::from).to_vec(),
+        ["J", "K", "L", ";", "'"].map(String::from).to_vec(),
+    ]
+}
+
+// Keys from settings are used only when both players have at least two known keys and no key is shared.
+pub fn get_split_keyboard_keys(names: &[Vec<String>; 2]) -> [Vec<KeyCode>; 2] {
+    let parse = |x: &Vec<String>| x.iter().map(|x| parse_key(x)).collect::<Option<Vec<KeyCode>>>();
+    if let (Some(first), Some(second)) = (parse(&names[0]), parse(&names[1])) {
+        let mut all: Vec<KeyCode> = first.iter().chain(second.iter()).copied().collect();
+        all.sort_by_key(|x| *x as u32);
+        all.dedup();
+        if first.len() >= MIN_KEYS && second.len() >= MIN_KEYS && all.len() == first.len() + second.len() {
+            return [first, second];
+        }
+    }
+    warn!("Invalid split keyboard keys {:?}, default keys are used", names);
+    let default = default_split_keyboard_keys();
+    [parse(&default[0]).unwrap(), parse(&default[1]).unwrap()]
+}
+
+pub struct SplitKeyboardPlugin;
+
+impl Plugin for SplitKeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<SplitKeyboardState>()
+            .init_resource::<SplitKeyboardPlayers>()
+            .add_systems(OnEnter(AppState::SplitKeyboard), init_split_keyboard)
+            .add_systems(OnEnter(SplitKeyboardState::Choosing), setup_choosing_screen)
+            .add_systems(Update, press_split_keyboard_key.run_if(in_state(SplitKeyboardState::Choosing)))
+            .add_systems(OnExit(SplitKeyboardState::Choosing), despawn_screen::<OnSplitKeyboardScreen>)
+            .add_systems(OnEnter(SplitKeyboardState::RoundFinish), setup_result_screen)
+            .add_systems(
+                Update,
+                confirm_local_result_action::<SplitKeyboardState>.run_if(in_state(SplitKeyboardState::RoundFinish)),
+            )
+            .add_systems(OnExit(SplitKeyboardState::RoundFinish), despawn_screen::<OnSplitKeyboardResultScreen>);
+    }
+}
+
+pub fn init_split_keyboard(mut local_match: ResMut<LocalMatch>, mut split_keyboard_state: ResMut<NextState<SplitKeyboardState>>) {
+    *local_match = LocalMatch::default();
+    split_keyboard_state.set(SplitKeyboardState::Choosing);
+}
+
+fn get_legend_lines(players: &SplitKeyboardPlayers, game_type: &GameType) -> Vec<String> {
+    let number_of_moves = game_type.max_number_of_moves() as usize;
+    let mut lines = Vec::new();
+    if players.0[0].get_number_of_presses(number_of_moves) > 1 {
+        lines.push(format!(
+            "Type move number minus one in base {} / {}, first key is 0",
+            players.0[0].keys.len(),
+            players.0[1].keys.len()
+        ));
+    }
+    lines.extend((0..number_of_moves.min(MAX_LEGEND_MOVES)).map(|x| {
+        format!(
+            "{}: {} / {}",
+            GameMove(x).get_name(game_type),
+            players.0[0].get_key_sequence(GameMove(x), number_of_moves),
+            players.0[1].get_key_sequence(GameMove(x), number_of_moves)
+        )
+    }));
+    if number_of_moves > MAX_LEGEND_MOVES {
+        lines.push(format!("... and {} more moves", number_of_moves - MAX_LEGEND_MOVES));
+    }
+    lines
+}
+
+pub fn setup_choosing_screen(
+    mut commands: Commands,
+    mut players: ResMut<SplitKeyboardPlayers>,
+    game_font: Res<GameFont>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    local_match: Res<LocalMatch>,
+) {
+    let font = &game_font.0;
+    let keys = get_split_keyboard_keys(&game_settings.split_keyboard_keys);
+    *players = SplitKeyboardPlayers(keys.map(SplitPlayer::new));
+    let number_of_moves = game_type.max_number_of_moves() as usize;
+
+    spawn_panel(&mut commands, OnSplitKeyboardScreen, |parent| {
+        spawn_text(parent, String::from("Choose your moves"), TITLE_SIZE, TITLE_COLOR, font);
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::SpaceEvenly,
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for (index, options) in [&game_settings.player_options, &game_settings.second_player_options].into_iter().enumerate() {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            spawn_text(parent, options.name.clone(), BUTTON_TEXT_SIZE, BUTTON_TITLE_COLOR, font);
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    players.0[index].get_indicator_text(number_of_moves),
+                                    TextStyle {
+                                        font_size: BUTTON_TEXT_SMALL_SIZE,
+                                        color: OVERVIEW_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                ),
+                                OnLockIndicator(index),
+                            ));
+                        });
+                }
+            });
+        for line in get_legend_lines(&players, &game_type) {
+            parent.spawn(TextBundle::from_section(
+                line,
+                TextStyle {
+                    font_size: 12.0,
+                    color: OVERVIEW_SUB_TITLE_COLOR,
+                    font: font.clone(),
+                },
+            ));
+        }
+        spawn_text(parent, get_score_text(&local_match, &game_settings), 16.0, OVERVIEW_SUB_TITLE_COLOR, font);
+        spawn_button(parent, "ESC to finish", KeyCode::Escape, font);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn press_split_keyboard_key(
+    actions: Res<Actions>,
+    mut query: Query<(&mut Text, &OnLockIndicator)>,
+    mut players: ResMut<SplitKeyboardPlayers>,
+    mut local_match: ResMut<LocalMatch>,
+    mut split_keyboard_state: ResMut<NextState<SplitKeyboardState>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    audio: Res<Audio>,
+    game_sounds: Res<GameSounds>,
+) {
+    if actions.just_pressed(KeyCode::Escape) {
+        split_keyboard_state.set(SplitKeyboardState::NotInit);
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+        return;
+    }
+
+    let number_of_moves = game_type.max_number_of_moves() as usize;
+    let mut is_changed = false;
+    for key_code in actions.get_just_pressed().filter(|x| !RESERVED_KEYS.contains(x)) {
+        for player in players.0.iter_mut() {
+            if player.press(*key_code, number_of_moves).is_some() {
+                is_changed = true;
+            }
+        }
+    }
+    if !is_changed {
+        return;
+    }
+    for (mut text, indicator) in &mut query {
+        text.sections[0].value = players.0[indicator.0].get_indicator_text(number_of_moves);
+    }
+
+    if let [SplitPlayer { locked: Some(first_move), .. }, SplitPlayer { locked: Some(second_move), .. }] = &players.0 {
+        let result = local_match.add_round(&game_type, *first_move, *second_move);
+        split_keyboard_state.set(SplitKeyboardState::RoundFinish);
+        let result_sound = match result {
+            GameResult::Draw => &game_sounds.drawn,
+            _ => &game_sounds.win,
+        };
+        play_sound(&audio, game_settings.is_sound_on, result_sound);
+    } else {
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
+
+pub fn setup_result_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_type: Res<GameType>,
+    game_settings: Res<GameSettings>,
+    local_match: Res<LocalMatch>,
+) {
+    spawn_round_result(
+        &mut commands,
+        OnSplitKeyboardResultScreen,
+        &game_font.0,
+        &game_type,
+        &game_settings,
+        &local_match,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> SplitPlayer {
+        SplitPlayer::new(get_split_keyboard_keys(&default_split_keyboard_keys())[0].clone())
+    }
+
+    #[test]
+    fn test_direct_keys() {
+        let mut player = player();
+        assert_eq!(player.get_number_of_presses(3), 1);
+        assert_eq!(player.press(KeyCode::J, 3), None);
+        assert_eq!(player.press(KeyCode::G, 3), Some(KeyPress::Invalid));
+        assert_eq!(player.get_indicator_text(3), "No such move, try again");
+        assert_eq!(player.press(KeyCode::D, 3), Some(KeyPress::Locked(GameMove(2))));
+        assert_eq!(player.press(KeyCode::A, 3), None);
+        assert_eq!(player.locked, Some(GameMove(2)));
+        assert_eq!(player.get_key_sequence(GameMove(1), 3), "S");
+    }
+
+    #[test]
+    fn test_multi_press_codes() {
+        let mut player = player();
+        assert_eq!(player.get_number_of_presses(5), 1);
+        assert_eq!(player.get_number_of_presses(7), 2);
+        assert_eq!(player.get_number_of_presses(25), 2);
+        assert_eq!(player.get_number_of_presses(101), 3);
+
+        // 57 = 2 * 25 + 1 * 5 + 2
+        assert_eq!(player.get_key_sequence(GameMove(57), 101), "DSD");
+        assert_eq!(player.press(KeyCode::D, 101), Some(KeyPress::Pending));
+        assert_eq!(player.press(KeyCode::S, 101), Some(KeyPress::Pending));
+        assert_eq!(player.get_indicator_text(101), "Choosing... **-");
+        assert_eq!(player.press(KeyCode::D, 101), Some(KeyPress::Locked(GameMove(57))));
+
+        let mut player = self::player();
+        for _ in 0..3 {
+            player.press(KeyCode::G, 101);
+        }
+        assert!(player.is_last_invalid);
+        assert!(player.digits.is_empty());
+        assert_eq!(player.locked, None);
+    }
+
+    #[test]
+    fn test_get_split_keyboard_keys() {
+        let keys = |x: [&[&str]; 2]| get_split_keyboard_keys(&x.map(|x| x.iter().map(|x| x.to_string()).collect()));
+        assert_eq!(keys([&["q", "w"], &["o", "p"]]), [vec![KeyCode::Q, KeyCode::W], vec![KeyCode::O, KeyCode::P]]);
+        let default = keys([&["A", "S", "D", "F", "G"], &["J", "K", "L", ";", "'"]]);
+        assert_eq!(keys([&["q"], &["o", "p"]]), default);
+        assert_eq!(keys([&["q", "w"], &["w", "p"]]), default);
+        assert_eq!(keys([&["q", "enter"], &["o", "p"]]), default);
+    }
+}
@@ -0,0 +1,387 @@
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use uuid::Uuid;
+
+use crate::button::{Actions, HotKey};
+use crate::common::*;
+use crate::game_result::{GameResult, ResultCounts};
+use crate::game_settings::GameSettings;
+use crate::history::{HistoryQuery, MatchHistory, MatchRecord, MatchResults};
+use crate::history_statistics::HistoryStatistics;
+use crate::rating::Ratings;
+use crate::ruleset::Rulesets;
+
+const MAX_BARS: usize = 8;
+const BAR_WIDTH: f32 = 300.0;
+const MAX_BAR_RATING: f64 = 3000.0;
+const BAR_BACKGROUND_COLOR: Color = Color::rgb(0.35, 0.25, 0.28);
+const WIN_BAR_COLOR: Color = Color::rgb(0.16471, 0.61569, 0.56078);
+const DRAW_BAR_COLOR: Color = Color::rgb(0.91373, 0.76863, 0.41569);
+const LOSE_BAR_COLOR: Color = Color::rgb(0.90588, 0.43529, 0.31765);
+
+#[derive(Component)]
+pub struct OnStatisticsScreen;
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Statistics), setup_statistics_screen)
+            .add_systems(Update, confirm_button_action.run_if(in_state(AppState::Statistics)))
+            .add_systems(OnExit(AppState::Statistics), despawn_screen::<OnStatisticsScreen>);
+    }
+}
+
+struct Bar {
+    label: String,
+    segments: Vec<(f32, Color)>,
+    text: String,
+}
+
+fn get_game_type_name(rulesets: &Rulesets, id: &str) -> String {
+    rulesets.0.iter().find(|x| x.id == id).map_or(id.to_string(), |x| x.name.clone())
+}
+
+// Most played entries first, the text shows current rating and the best one from rating history.
+fn get_rating_bars(ratings: &Ratings, rulesets: &Rulesets, profile: Uuid) -> Vec<Bar> {
+    let mut entries = ratings.get_profile_entries(profile);
+    entries.sort_by_key(|x| Reverse(x.history.len()));
+    entries
+        .into_iter()
+        .take(MAX_BARS)
+        .map(|x| {
+            let best = x.history.iter().map(|x| x.rating.rating).fold(x.rating.rating, f64::max);
+            Bar {
+                label: format!("{} / {}", get_game_type_name(rulesets, &x.game_type), x.opponent.get_friendly_name()),
+                segments: vec![((x.rating.rating / MAX_BAR_RATING) as f32, WIN_BAR_COLOR)],
+                text: format!("{:.0} (best {:.0}, {} matches)", x.rating.rating, best, x.history.len()),
+            }
+        })
+        .collect()
+}
+
+fn get_game_type_bars(statistics: &HistoryStatistics, rulesets: &Rulesets) -> Vec<Bar> {
+    statistics
+        .per_game_type
+        .iter()
+        .take(MAX_BARS)
+        .map(|(id, counts)| Bar {
+            label: get_game_type_name(rulesets, id),
+            segments: vec![(counts.win_rate() as f32, WIN_BAR_COLOR)],
+            text: format!("{:.0}% of {}", counts.win_rate() * 100.0, counts.totals()),
+        })
+        .collect()
+}
+
+// Bar length is the share of picks compared to the most picked move, split by results of that move.
+fn get_move_bars(statistics: &HistoryStatistics) -> Vec<Bar> {
+    let max_picks = statistics.per_move.first().map_or(1, |(_, x)| x.totals()) as f32;
+    statistics
+        .per_move
+        .iter()
+        .take(MAX_BARS)
+        .map(|(name, counts)| Bar {
+            label: name.clone(),
+            segments: vec![
+                (counts.wins as f32 / max_picks, WIN_BAR_COLOR),
+                (counts.draws as f32 / max_picks, DRAW_BAR_COLOR),
+                (counts.loses as f32 / max_picks, LOSE_BAR_COLOR),
+            ],
+            text: format!("{} ({}/{}/{})", counts.totals(), counts.wins, counts.draws, counts.loses),
+        })
+        .collect()
+}
+
+fn get_summary_lines(statistics: &HistoryStatistics, matches: &[MatchRecord]) -> Vec<String> {
+    let totals: &ResultCounts = &statistics.totals;
+    let count = |winner: Option<GameResult>| matches.iter().filter(|x| x.winner == winner).count();
+    vec![
+        format!(
+            "Matches: {}, won: {}, lost: {}, not decided: {}",
+            matches.len(),
+            count(Some(GameResult::Win)),
+            count(Some(GameResult::Lose)),
+            count(None)
+        ),
+        format!(
+            "Rounds: {}, wins: {}, loses: {}, draws: {}",
+            totals.totals(),
+            totals.wins,
+            totals.loses,
+            totals.draws
+        ),
+        format!("Win rate: {:.0}%, timeouts: {}", totals.win_rate() * 100.0, statistics.timeouts),
+        format!(
+            "Longest win streak: {}, longest lose streak: {}",
+            statistics.longest_win_streak, statistics.longest_lose_streak
+        ),
+    ]
+}
+
+fn spawn_bar(parent: &mut ChildBuilder, bar: Bar, text_style: &TextStyle) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(bar.label, text_style.clone()).with_style(Style {
+                width: Val::Px(220.0),
+                ..default()
+            }));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        width: Val::Px(BAR_WIDTH),
+                        height: Val::Px(16.0),
+                        ..default()
+                    },
+                    background_color: BAR_BACKGROUND_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (value, color) in bar.segments {
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Percent(value.clamp(0.0, 1.0) * 100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        });
+                    }
+                });
+            parent.spawn(TextBundle::from_section(bar.text, text_style.clone()).with_style(Style {
+                margin: UiRect::left(Val::Px(10.0)),
+                ..default()
+            }));
+        });
+}
+
+pub fn setup_statistics_screen(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    game_settings: Res<GameSettings>,
+    match_history: Res<MatchHistory>,
+    match_results: Res<MatchResults>,
+    rulesets: Res<Rulesets>,
+    ratings: Res<Ratings>,
+) {
+    let font = &game_font.0;
+    let matches: Vec<MatchRecord> = match_results
+        .load()
+        .into_iter()
+        .filter(|x| x.profile == game_settings.player_options.uuid)
+        .collect();
+    let statistics = HistoryStatistics::new(&match_history.query(&HistoryQuery {
+        profile: Some(game_settings.player_options.uuid),
+        ..default()
+    }));
+
+    let header_style = TextStyle {
+        font: font.clone(),
+        font_size: 18.0,
+        color: OVERVIEW_TITLE_COLOR,
+    };
+
+    let body_style = TextStyle {
+        font: font.clone(),
+        font_size: 12.0,
+        color: OVERVIEW_SUB_TITLE_COLOR,
+    };
+
+    let header_margin = UiRect::new(Val::Px(10.0), Val::Px(10.0), Val::Px(20.0), Val::Px(10.0));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Start,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnStatisticsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                (TextBundle::from_section(
+                    game_settings.player_options.name.clone(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Right))
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: OVERVIEW_BACKGROUND_COLOR.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    "Statistics",
+                                    TextStyle {
+                                        font_size: 40.0,
+                                        color: OVERVIEW_TITLE_COLOR,
+                                        font: font.clone(),
+                                    },
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    ..default()
+                                }),
+                            );
+
+                            for line in get_summary_lines(&statistics, &matches) {
+                                parent.spawn(TextBundle::from_section(line, body_style.clone()).with_style(Style {
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    ..default()
+                                }));
+                            }
+
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        justify_content: JustifyContent::SpaceEvenly,
+                                        flex_wrap: FlexWrap::Wrap,
+                                        width: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    let columns = [
+                                        ("Win rate per game", get_game_type_bars(&statistics, &rulesets)),
+                                        ("Moves (wins/draws/loses)", get_move_bars(&statistics)),
+                                        ("Ratings", get_rating_bars(&ratings, &rulesets, game_settings.player_options.uuid)),
+                                    ];
+                                    for (header, bars) in columns {
+                                        parent
+                                            .spawn(NodeBundle {
+                                                style: Style {
+                                                    flex_direction: FlexDirection::Column,
+                                                    align_items: AlignItems::Start,
+                                                    ..default()
+                                                },
+                                                ..default()
+                                            })
+                                            .with_children(|parent| {
+                                                parent.spawn(TextBundle::from_section(header, header_style.clone()).with_style(Style {
+                                                    margin: header_margin,
+                                                    ..default()
+                                                }));
+                                                if bars.is_empty() {
+                                                    parent.spawn(TextBundle::from_section("No rounds played yet.", body_style.clone()));
+                                                }
+                                                for bar in bars {
+                                                    spawn_bar(parent, bar, &body_style);
+                                                }
+                                            });
+                                    }
+                                });
+
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        justify_content: JustifyContent::End,
+                                        width: Val::Percent(100.0),
+                                        margin: UiRect::top(Val::Px(20.0)),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(250.0),
+                                                    height: Val::Px(50.0),
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    border: UiRect::all(Val::Px(5.0)),
+                                                    justify_content: JustifyContent::Center,
+                                                    align_items: AlignItems::Center,
+                                                    ..default()
+                                                },
+                                                border_color: Color::WHITE.into(),
+                                                background_color: Color::NONE.into(),
+                                                ..default()
+                                            },
+                                            HotKey(KeyCode::B),
+                                        ))
+                                        .with_children(|parent| {
+                                            parent.spawn(
+                                                TextBundle::from_section(
+                                                    "(B)ack",
+                                                    TextStyle {
+                                                        font_size: BUTTON_TEXT_SIZE,
+                                                        color: Color::WHITE,
+                                                        font: font.clone(),
+                                                    },
+                                                )
+                                                .with_style(Style {
+                                                    margin: UiRect::all(Val::Px(10.0)),
+                                                    ..default()
+                                                }),
+                                            );
+                                        });
+                                });
+                        });
+                });
+        });
+}
+
+pub fn confirm_button_action(
+    actions: Res<Actions>,
+    mut app_state: ResMut<NextState<AppState>>,
+    audio: Res<Audio>,
+    game_settings: Res<GameSettings>,
+    game_sounds: Res<GameSounds>,
+) {
+    if actions.just_pressed(KeyCode::B) {
+        app_state.set(AppState::Menu);
+
+        play_sound(&audio, game_settings.is_sound_on, &game_sounds.mode_switch);
+    }
+}
@@ -0,0 +1,280 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+use crate::common::Round;
+use crate::game_move::GameMove;
+use crate::game_type::GameType;
+use crate::nash::Equilibrium;
+
+pub trait Strategy: Send + Sync {
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove;
+
+    // Forgets what was learned in the previous match. Forfeited rounds are not in the history, so a new match cannot be
+    // told apart from a forfeit by the history length.
+    fn reset(&mut self) {}
+
+    fn get_difficulty(&self) -> Option<usize> {
+        None
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub predictor: String,
+    pub expected_move: Option<GameMove>,
+    pub confidence: f64,
+}
+
+impl Explanation {
+    // Expects the player move with the biggest weight. Confidence is its share of all weights.
+    pub fn from_weights(predictor: String, weights: &[f64]) -> Self {
+        let total: f64 = weights.iter().sum();
+        let mut best: Option<usize> = None;
+        for (index, weight) in weights.iter().enumerate() {
+            if *weight > 0.0 && best.is_none_or(|x| *weight > weights[x]) {
+                best = Some(index);
+            }
+        }
+        Self {
+            predictor,
+            expected_move: best.map(GameMove),
+            confidence: best.map_or(0.0, |x| weights[x] / total),
+        }
+    }
+
+    pub fn get_lines(&self, game_type: &GameType) -> Vec<String> {
+        match self.expected_move {
+            Some(x) => vec![
+                format!("Predictor: {}", self.predictor),
+                format!("Expected your move: {}", x.get_name(game_type)),
+                format!("Confidence: {:.0}%", self.confidence * 100.0),
+            ],
+            None => vec![format!("Predictor: {}", self.predictor), String::from("No prediction, the move was random.")],
+        }
+    }
+}
+
+pub fn random_move(game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+    GameMove(rng.gen_range(0..game_type.ruleset().moves.len()))
+}
+
+// Picks the move with the best expected payoff against the given weights of player moves. Ties are broken randomly.
+pub fn best_response(game_type: &GameType, weights: &[f64], rng: &mut dyn RngCore) -> GameMove {
+    if weights.iter().all(|x| *x <= 0.0) {
+        return random_move(game_type, rng);
+    }
+
+    let ruleset = game_type.ruleset();
+    let scores: Vec<f64> = (0..ruleset.moves.len())
+        .map(|x| weights.iter().enumerate().map(|(y, weight)| weight * ruleset.payoff(x, y) as f64).sum())
+        .collect();
+    let best = scores.iter().fold(f64::NEG_INFINITY, |x, y| x.max(*y));
+    let candidates: Vec<usize> = (0..scores.len()).filter(|x| best - scores[*x] < 1e-9).collect();
+    GameMove(candidates[rng.gen_range(0..candidates.len())])
+}
+
+pub struct UniformStrategy;
+
+impl Strategy for UniformStrategy {
+    fn get_move(&mut self, _: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        random_move(game_type, rng)
+    }
+}
+
+// The equilibrium depends only on the ruleset, so it is solved once per ruleset id.
+#[derive(Default)]
+pub struct OptimalStrategy {
+    distribution: Option<(String, WeightedIndex<f64>)>,
+}
+
+impl Strategy for OptimalStrategy {
+    fn get_move(&mut self, _: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        let id = &game_type.ruleset().id;
+        if self.distribution.as_ref().is_none_or(|(x, _)| x != id) {
+            let equilibrium = Equilibrium::for_game_type(game_type);
+            let distribution = WeightedIndex::new(equilibrium.column_strategy.iter().map(|x| x.max(0.0))).expect("Equilibrium is not a distribution.");
+            self.distribution = Some((id.clone(), distribution));
+        }
+        let (_, distribution) = self.distribution.as_ref().unwrap();
+        GameMove(distribution.sample(rng))
+    }
+}
+
+#[derive(Default)]
+pub struct FrequencyStrategy {
+    explanation: Option<Explanation>,
+}
+
+impl Strategy for FrequencyStrategy {
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        let mut weights = vec![0.0; game_type.ruleset().moves.len()];
+        for round in history {
+            weights[round.player_move.0] += 1.0;
+        }
+        self.explanation = Some(Explanation::from_weights(String::from("Your most frequent move"), &weights));
+        best_response(game_type, &weights, rng)
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+}
+
+pub struct MarkovStrategy {
+    order: usize,
+    explanation: Option<Explanation>,
+}
+
+impl MarkovStrategy {
+    pub fn new(order: usize) -> Self {
+        Self { order, explanation: None }
+    }
+}
+
+impl Strategy for MarkovStrategy {
+    // Counts what the player played after each earlier occurrence of their last `order` moves.
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        let mut weights = vec![0.0; game_type.ruleset().moves.len()];
+        if history.len() > self.order {
+            let player_moves: Vec<usize> = history.iter().map(|x| x.player_move.0).collect();
+            let context = &player_moves[player_moves.len() - self.order..];
+            for window in player_moves.windows(self.order + 1) {
+                if &window[..self.order] == context {
+                    weights[window[self.order]] += 1.0;
+                }
+            }
+        }
+        self.explanation = Some(Explanation::from_weights(
+            format!("What you played after your last {} move(s)", self.order),
+            &weights,
+        ));
+        best_response(game_type, &weights, rng)
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+}
+
+#[derive(Default)]
+pub struct BeatLastStrategy {
+    explanation: Option<Explanation>,
+}
+
+impl Strategy for BeatLastStrategy {
+    fn get_move(&mut self, history: &[Round], game_type: &GameType, rng: &mut dyn RngCore) -> GameMove {
+        let mut weights = vec![0.0; game_type.ruleset().moves.len()];
+        if let Some(round) = history.last() {
+            weights[round.player_move.0] = 1.0;
+        }
+        self.explanation = Some(Explanation::from_weights(String::from("Your last move repeated"), &weights));
+        best_response(game_type, &weights, rng)
+    }
+
+    fn get_explanation(&self) -> Option<&Explanation> {
+        self.explanation.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::mock::StepRng;
+
+    use crate::game_result::GameResult;
+    use crate::test_support::bundled_game_type;
+
+    use super::*;
+
+    fn game_move(game_type: &GameType, name: &str) -> GameMove {
+        GameMove(game_type.ruleset().find_move(name).unwrap())
+    }
+
+    fn history(game_type: &GameType, player_moves: &[&str]) -> Vec<Round> {
+        player_moves
+            .iter()
+            .map(|x| Round {
+                player_move: game_move(game_type, x),
+                computer_move: game_move(game_type, "Rock"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_optimal_never_plays_dominated_moves() {
+        let game_type = bundled_game_type("fire_water");
+        let mut rng = StepRng::new(0, u64::MAX / 97);
+        let mut strategy = OptimalStrategy::default();
+        for _ in 0..200 {
+            let game_move = strategy.get_move(&[], &game_type, &mut rng);
+            assert_ne!(game_move, self::game_move(&game_type, "Rock"));
+            assert_ne!(game_move, self::game_move(&game_type, "Scissors"));
+        }
+
+        let game_type = bundled_game_type("normal");
+        let mut rng = StdRng::seed_from_u64(1);
+        let moves: Vec<GameMove> = (0..30).map(|_| strategy.get_move(&[], &game_type, &mut rng)).collect();
+        assert!((0..3).all(|x| moves.contains(&GameMove(x))));
+    }
+
+    #[test]
+    fn test_explanation_from_weights() {
+        let explanation = Explanation::from_weights(String::from("x"), &[1.0, 3.0, 0.0, 3.0, 1.0]);
+        assert_eq!(explanation.expected_move, Some(GameMove(1)));
+        assert!((explanation.confidence - 0.375).abs() < 1e-9);
+
+        let explanation = Explanation::from_weights(String::from("x"), &[0.0, 0.0, 0.0]);
+        assert_eq!(explanation.expected_move, None);
+        assert_eq!(explanation.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_frequency() {
+        let game_type = bundled_game_type("normal");
+        let history = history(&game_type, &["Rock", "Paper", "Rock", "Scissors", "Rock"]);
+        let game_move = FrequencyStrategy::default().get_move(&history, &game_type, &mut StepRng::new(0, 1));
+        assert_eq!(game_move, self::game_move(&game_type, "Paper"));
+    }
+
+    #[test]
+    fn test_markov() {
+        let game_type = bundled_game_type("normal");
+        // After Rock the player mostly played Scissors, but after Paper and Rock they played Paper.
+        let history = history(
+            &game_type,
+            &[
+                "Paper", "Rock", "Paper", "Scissors", "Rock", "Scissors", "Scissors", "Rock", "Scissors", "Paper", "Rock",
+            ],
+        );
+        let game_move = MarkovStrategy::new(1).get_move(&history, &game_type, &mut StepRng::new(0, 1));
+        assert_eq!(game_move, self::game_move(&game_type, "Rock"));
+
+        let game_move = MarkovStrategy::new(2).get_move(&history, &game_type, &mut StepRng::new(0, 1));
+        assert_eq!(game_move, self::game_move(&game_type, "Scissors"));
+    }
+
+    #[test]
+    fn test_beat_last() {
+        let game_type = bundled_game_type("rps_101");
+        let mut rng = StepRng::new(0, u64::MAX / 13);
+        for x in 0..101 {
+            let history = [Round {
+                player_move: GameMove(x),
+                computer_move: GameMove(0),
+            }];
+            let game_move = BeatLastStrategy::default().get_move(&history, &game_type, &mut rng);
+            assert_eq!(game_move.beats_other(&game_type, &GameMove(x)), GameResult::Win);
+        }
+    }
+
+    #[test]
+    fn test_empty_history_is_random() {
+        let game_type = bundled_game_type("spock_lizard");
+        let mut rng = StdRng::seed_from_u64(5);
+        let moves: Vec<GameMove> = (0..5).map(|_| MarkovStrategy::new(2).get_move(&[], &game_type, &mut rng)).collect();
+        assert!(moves.iter().any(|x| *x != moves[0]));
+    }
+}
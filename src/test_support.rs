@@ -0,0 +1,16 @@
+use once_cell::sync::Lazy;
+
+use crate::game_type::GameType;
+use crate::ruleset::Rulesets;
+
+// Rulesets from the asset folder are parsed once and shared by all tests. The library and the game binary both declare
+// this module, so tests of either crate can use it.
+static BUNDLED_RULESETS: Lazy<Rulesets> = Lazy::new(Rulesets::init);
+
+pub fn bundled_rulesets() -> &'static Rulesets {
+    &BUNDLED_RULESETS
+}
+
+pub fn bundled_game_type(id: &str) -> GameType {
+    GameType::Ruleset(bundled_rulesets().0.iter().find(|x| x.id == id).unwrap().clone())
+}